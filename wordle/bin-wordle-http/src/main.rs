@@ -1,30 +1,84 @@
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::env;
+use std::path::{Path, PathBuf};
 use hyper::{Body, Request, Response, Server};
 use hyper::service::{make_service_fn, service_fn};
 use once_cell::sync::Lazy;
 use lib_wordle::wordle_tree::{WordleTree, tree_player};
-use lib_wordle::{check, word::Word};
+use lib_wordle::{check, response_matrix::ResponseMatrix, word::Word};
 
 static ANSWERS: &str = std::include_str!("../../data/2315/answers.txt");
 static VALID: &str = std::include_str!("../../data/2315/valid.txt");
 static STRATEGY: &str = std::include_str!("../../data/v13.txt");
 static INDEX: &str = std::include_str!("../index.html");
 
+// Cached responses for the embedded word set, under the data folder unless $WORDLE_MATRIX is set.
+//  Without them (ex: when deployed without the data folder), responses are scored as needed; pass --build-matrix to build and save them.
+static MATRIX_FILE: &str = "2315/responses.bin";
+static BUILD_MATRIX_ARG: &str = "--build-matrix";
+
 struct AppState {
     answers: Vec<Word>,
     valid: Vec<Word>,
+    matrix: Option<ResponseMatrix>,
 }
 
 impl AppState {
     fn new() -> AppState {
         let answers = Word::parse_lines(ANSWERS);
         let valid = Word::parse_lines(VALID);
-        AppState { answers, valid }
+        let build = env::args().any(|arg| arg == BUILD_MATRIX_ARG);
+        let matrix = load_matrix(&matrix_path(), &valid, &answers, build);
+        AppState { answers, valid, matrix }
+    }
+}
+
+/// The cached responses: $WORDLE_MATRIX if set, otherwise MATRIX_FILE under the data folder
+fn matrix_path() -> PathBuf {
+    match env::var_os("WORDLE_MATRIX") {
+        Some(path) => PathBuf::from(path),
+        None => data_dir().join(MATRIX_FILE)
     }
 }
 
+/// Load the cached responses if they match the word set; otherwise build and save them only when asked, since it takes a while
+fn load_matrix(path: &Path, valid: &[Word], answers: &[Word], build: bool) -> Option<ResponseMatrix> {
+    match ResponseMatrix::load(path) {
+        Ok(matrix) if matrix.guesses() == valid && matrix.answers() == answers => {
+            println!("Loaded responses from {path:?}.");
+            return Some(matrix);
+        },
+        Ok(_) => println!("Responses in {path:?} are for a different word set."),
+        Err(e) => println!("{e}"),
+    }
+
+    if !build {
+        println!("Scoring responses as needed; run with {BUILD_MATRIX_ARG} to build and save them.");
+        return None;
+    }
+
+    println!("Building responses for {} guesses and {} answers...", valid.len(), answers.len());
+    let matrix = ResponseMatrix::new(valid, answers);
+    match matrix.save(path) {
+        Ok(()) => println!("Saved responses to {path:?}."),
+        Err(e) => eprintln!("Unable to cache responses: {e}"),
+    }
+
+    Some(matrix)
+}
+
+/// The data folder: $WORDLE_DATA if set, otherwise 'data' beside the server executable (found when the server starts, not when it is built)
+fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("WORDLE_DATA") {
+        return PathBuf::from(dir);
+    }
+
+    let exe_dir = env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()));
+    exe_dir.unwrap_or_default().join("data")
+}
+
 static APP_STATE: Lazy<AppState> = Lazy::new(|| { AppState::new() });
 
 async fn assess(req: Request<Body>) -> Result<Response<Body>, Infallible> {
@@ -34,11 +88,13 @@ async fn assess(req: Request<Body>) -> Result<Response<Body>, Infallible> {
             .collect::<HashMap<String, String>>();
         
         let guesses = params.get("g").as_ref().map(|g| g.as_str());
+        let json = params.get("format").is_some_and(|f| f == "json");
 
-        match assess_inner(guesses, &APP_STATE.valid, &APP_STATE.answers) {
+        match assess_inner(guesses, &APP_STATE.valid, &APP_STATE.answers, APP_STATE.matrix.as_ref(), json) {
             Ok(result) => {
+                let content_type = if json { "application/json; charset=utf-8" } else { "text/plain; charset=utf-8" };
                 return Ok(Response::builder()
-                    .header("Content-Type", "text/plain; charset=utf-8")
+                    .header("Content-Type", content_type)
                     .body(result.into())
                     .unwrap()
                 );
@@ -60,13 +116,33 @@ async fn assess(req: Request<Body>) -> Result<Response<Body>, Infallible> {
     )
 }
 
-fn assess_inner(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>) -> Result<String, String> {
+fn assess_inner(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, matrix: Option<&ResponseMatrix>, json: bool) -> Result<String, String> {
     let simulate_game_count = 10000;
 
     let tree = WordleTree::parse(STRATEGY.lines())?;
     let player = tree_player::TreePlayer::new(&tree);
 
-    return check::assess_and_simulate(guesses, valid, answers, simulate_game_count, player);
+    if json {
+        return check::assess_and_simulate_json(guesses, valid, answers, simulate_game_count, player, matrix).map(|json| json.to_string());
+    }
+
+    return check::assess_and_simulate(guesses, valid, answers, simulate_game_count, player, matrix);
+}
+
+async fn strategy(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    // Return the embedded strategy tree as JSON, for front ends to draw or walk
+    match WordleTree::parse(STRATEGY.lines()) {
+        Ok(tree) => Ok(Response::builder()
+            .header("Content-Type", "application/json; charset=utf-8")
+            .body(tree.to_json().to_string().into())
+            .unwrap()
+        ),
+        Err(e) => Ok(Response::builder()
+            .status(500)
+            .body(e.into())
+            .unwrap()
+        )
+    }
 }
 
 async fn index(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
@@ -101,6 +177,7 @@ async fn route(req: Request<Body>) -> Result<Response<Body>, Infallible> {
     match req.uri().path() {
         "/" => index(req).await,
         "/assess" => assess(req).await,
+        "/strategy" => strategy(req).await,
         _ => Ok(Response::builder()
             .status(404)
             .body("Not Found".into())
@@ -112,6 +189,9 @@ async fn route(req: Request<Body>) -> Result<Response<Body>, Infallible> {
 #[tokio::main]
 async fn main() {
     let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
+
+    // Load word sets and responses before accepting requests
+    Lazy::force(&APP_STATE);
     println!("Starting on {addr:?}...");
 
 
@@ -37,7 +37,18 @@ impl Wordle {
 
     pub fn assess(&self, guesses: &str, simulate_game_count: usize) -> Result<String, String> {
         let player = tree_player::TreePlayer::new(&self.strategy);
-        check::assess_and_simulate(Some(guesses), &self.valid, &self.answers, simulate_game_count, player)
+        check::assess_and_simulate(Some(guesses), &self.valid, &self.answers, simulate_game_count, player, None)
+    }
+
+    /// assess, as JSON text (turns, options for each next guess, and the simulation report)
+    pub fn assess_json(&self, guesses: &str, simulate_game_count: usize) -> Result<String, String> {
+        let player = tree_player::TreePlayer::new(&self.strategy);
+        check::assess_and_simulate_json(Some(guesses), &self.valid, &self.answers, simulate_game_count, player, None).map(|json| json.to_string())
+    }
+
+    /// The strategy tree as JSON text
+    pub fn strategy_json(&self) -> String {
+        self.strategy.to_json().to_string()
     }
 }
 
@@ -1,5 +1,5 @@
 use std::{time::Instant, env, path::Path, fs};
-use lib_wordle::{clubs::Clubs, letter_orders::LetterOrders, response::Response, scrappy, single_use::NeighborSide, word::Word, wordle_tree::{tree_player, WordleTree, WordleTreeToStringOptions}, *};
+use lib_wordle::{clubs::Clubs, letter_orders::LetterOrders, objective::Objective, response::Response, weights::AnswerWeights, response_matrix::ResponseMatrix, scrappy, single_use::NeighborSide, word::Word, wordle_tree::{tree_player, WordleTree, WordleTreeToStringOptions}, *};
 
 /*
    I used this code to improve my Wordle play.
@@ -20,12 +20,17 @@ use lib_wordle::{clubs::Clubs, letter_orders::LetterOrders, response::Response,
 
 // Modes
 // =====
-const USAGE: &str = "Usage: wordle_v2 <mode> [--set <wordSet>]? <args>...
- assess <strategyPath> <guessesIncludingAnswer>
+const USAGE: &str = "Usage: wordle_v2 <mode> [--set <wordSet>]? [--hard]? [--objective <objective>]? [--weighted]? <args>...
+ --hard: Play by hard mode rules (every revealed hint must be used) in 'best', 'build', 'simulate', and 'evaluate'.
+ --objective: What 'best', 'build', 'search', and 'adaptive' optimize: 'turns' (default), 'failures' (over six turns), 'max' (worst case turns), or 'within:N' (most solved in N turns).
+ --weighted: Weight answers by likelihood, from 'weights.txt' (word<TAB>weight lines) next to the set's answers, in 'best', 'solve', 'build', 'search', 'adaptive', and 'simulate'.
+
+ assess <strategyPath> <guessesIncludingAnswer> [--json]?
    ex: assess ../data/v13.txt CLINT SOARE ELATE PLATE
    Assess play compared to a pre-planned strategy.
    Shows how many answers were left, the best guesses, and how the actual next guess compared.
    Simulates 10,000 games with the strategy for that answer to show expected turns to solve.
+   With --json, writes the turns, options, and simulation report as JSON instead.
 
  analyze <guessesWithOptionalResponses>
    ex: analyze soare gbbby clint (SOARE with green, black, black, black, yellow, then show all possible responses for CLINT...)
@@ -35,10 +40,63 @@ const USAGE: &str = "Usage: wordle_v2 <mode> [--set <wordSet>]? <args>...
 
  build <strategy> <startingGuesses>
   Generate a strategy tree file given the strategy name and initial guesses.
-  Strategies: 'standard', 'hybrid', 'best', 'first', 'v11'
+  Strategies: 'standard', 'hybrid', 'best', 'first', 'v11', or a quoted chain of strategy parts tried in order each turn (naming its own guesses, so no <startingGuesses>):
+   ex: build 'random<=3 | standard(soare, clint) | under_letters(dumpy, 3) | best'
+   Parts: 'random<=N', 'standard(words)', 'under_letters(word, N)', 'best', 'first', 'separate', 'random', 'each(<strategy>)' (a copy per cluster).
+
+ pareto <strategy> <startingGuesses>
+  Build a strategy tree as in 'build', then switch its specific guesses to random guessing one at a time (cheapest first),
+  showing each strategy where no other is both simpler to memorize and takes fewer turns.
+  Complexity counts specific guesses to remember, response patterns to recognize, and the latest turn with a specific guess.
+
+ solve [<firstGuess>]?
+  Find the strategy with the fewest total turns for all answers (optionally after a required first guess) and write it as a strategy tree.
+  Exhaustive; can take a very long time for the full answer list.
   
- simulate <game_count> <strategyPath> [--games <answers_file_path> | --answer <single_answer> | --cluster <target_word> <at_turn>]? [--total]
+ simulate <game_count> <strategyPath> [--games <answers_file_path> | --answer <single_answer> | --cluster <target_word> <at_turn>]? [--total]? [--seed <n>]? [--threads <n>]? [--json]?
   Simulate games using a strategy tree file. Can run for a specific answer or cluster only to check average turns for specific games.
+  Shows games solved on each turn (and lost, over six) through each node, the standard error of the average, and the slowest answers.
+  Games are split across threads (default: all cores); the same --seed repeats a simulation exactly, with any thread count.
+  With --json, writes only the simulation report, as JSON, with the turns and games for every answer.
+
+ compare <game_count> <leftStrategyPath> <rightStrategyPath> [--seed <n>]? [--threads <n>]?
+  ex: compare 1000000 ../data/v12.txt ../data/v13.txt
+  Play both strategy trees on the same answers with the same random numbers, and show the difference in average turns (right - left)
+  with a 95% confidence interval, the answers which differ most, and the nodes in each tree where turns differ most.
+
+ diff <leftStrategyPath> <rightStrategyPath>
+  ex: diff ../data/v12.txt ../data/v13.txt
+  Show what changed between two strategy tree files, matching nodes by the identifiers leading to them (ignoring spacing and order):
+  added (+) and removed (-) subtrees, and changed (~) guesses, answers, and total turns.
+
+ human <strategyPath>
+  ex: human ../data/v13_human.txt
+  Write a strategy tree file in the human cheat sheet form ('CL...  >  pause', nested by indentation, with comments after the guess).
+  Every mode reading strategy files also reads this form, so the sheet you memorize can be simulated, evaluated, and assessed directly.
+
+ json <strategyPath>
+  ex: json ../data/v13.txt > v13.json
+  Convert a strategy tree file to JSON, keeping full cluster vectors, {answers}, and comments, for other tools and the web front ends.
+  A JSON tree file is converted back to the text form.
+
+ graph <strategyPath> [--svg]? [--average]? [--collapse <n>]?
+  ex: graph ../data/v13.txt --svg --collapse 20 > v13.svg
+  Draw a strategy tree as a Graphviz DOT digraph (or a standalone SVG image with --svg), labeling nodes with the identifier, next guess,
+  answer count, and turns (average per game with --average), colored green to red by average turns. Subtrees under n answers are collapsed.
+
+ lint <strategyPath>
+  ex: lint ../data/v13.txt
+  Replay every answer through a strategy tree file and report problems by line: parse errors, answer counts and {answers} which don't match,
+  nodes no answers reach, siblings shadowed by a less specific one listed before them, and clusters of 3+ answers falling through to random guessing.
+
+ retarget <strategyPath> <newAnswersPath>
+  ex: retarget --set 2315 ../data/v13.txt ../data/2309/answers.txt
+  Move a strategy tree written for the --set answers to a new answer list. Clusters follow the path their old answers took; Cluster identifiers,
+  counts, {answers}, cluster vectors, and turns are rewritten. Lists nodes removed (-), clusters which gained or lost answers (~), and new clusters split off (+).
+
+ evaluate <strategyPath> [--total]?
+  Compute the exact expected turns for every answer with a strategy tree file (random guesses included), without simulating.
+  Shows turns through each node, the number of answers solved on each turn, and the tree's own total for comparison.
 
  orders          : For a set of letter+position, show possible orders. (Uppercase green, lowercase yellow)
  best            : For a strategy tree and in-cluster word, show best choices after the strategy.
@@ -47,6 +105,8 @@ const USAGE: &str = "Usage: wordle_v2 <mode> [--set <wordSet>]? <args>...
  explain_hybrid  : Show turns for perfect 'hybrid' play (after each guess, when there are 1-2 answers left, guess, otherwise, use next standard)
  
  search          : Find the best starting words after specific starting guesses by considering all possible guesses and scoring them. (expensive)
+                   --ranker picks the scoring: 'predicted' (default), 'pessimistic', 'ideal', 'random', 'entropy', 'remaining' (expected answers left), or 'clusters'.
+ adaptive        : For each opening guess, find the best second guess for each response to it (with --ranker) and compare the openers' expected turns.
 
  score_answers   : Score answers by 'difficulty' (the sum of cluster size containing this answer across every possible guess)
  score_guesses   : Score each allowed guess by the number of clusters created (number of different tile responses)
@@ -86,12 +146,53 @@ fn main() {
         }
     }
 
+    // See if hard mode is requested
+    let mut hard_mode = false;
+    if let Some(next) = args.first() {
+        if *next == "--hard" {
+            hard_mode = true;
+            args = &args[1..];
+        }
+    }
+
+    // See if a different objective is requested
+    let mut objective = Objective::TotalTurns;
+    if let Some(next) = args.first() {
+        if *next == "--objective" {
+            if let Some(text) = args.get(1) {
+                objective = match Objective::parse(text) {
+                    Ok(objective) => objective,
+                    Err(e) => { println!("{e}"); return; }
+                };
+                args = &args[2..];
+            }
+        }
+    }
+
+    // See if answers should be weighted by likelihood
+    let mut weighted = false;
+    if let Some(next) = args.first() {
+        if *next == "--weighted" {
+            weighted = true;
+            args = &args[1..];
+        }
+    }
+
     let mut _answers = Word::parse_file(&Path::new(&format!("../data/{set}/answers.txt")));
     let mut _valid = Word::parse_file(&Path::new(&format!("../data/{set}/valid.txt")));
 
     // Note: Answers must be sorted for tree identifiers to be correct
     _answers.sort();
 
+    let weights = if weighted {
+        match AnswerWeights::load(Path::new(&format!("../data/{set}/weights.txt")), &_answers) {
+            Ok(weights) => Some(weights),
+            Err(e) => { println!("{e}"); return; }
+        }
+    } else {
+        None
+    };
+
     let start = Instant::now();
 
     match mode {
@@ -112,7 +213,7 @@ fn main() {
             let tree = WordleTree::parse(strategy_text.lines()).unwrap();
             let mut player = tree_player::TreePlayer::new(&tree);
 
-            let answers_left = player.cluster(w(args[1]), &_answers, 6);
+            let (answers_left, guesses) = player.cluster_and_guesses(w(args[1]), &_answers, 6);
             println!("For ({}, {})", answers_left[0], answers_left.len());
 
             for (i, answer) in answers_left.iter().enumerate() {
@@ -121,14 +222,17 @@ fn main() {
             }
             println!();
 
-            let clubs = Clubs::new(&answers_left, &_valid); 
+            let mut clubs = Clubs::new(&answers_left, &_valid); 
+            clubs.set_hard_mode(hard_mode, &guesses);
+            clubs.set_objective(objective, guesses.len());
+            if let Some(weights) = &weights { clubs.set_weights(weights); }
             let within = clubs.all_vector();
 
             let mut choices = std::collections::HashMap::new();
-            let best = clubs.count_best_turns(within, &mut choices);
+            let best = clubs.count_best_turns(&within, &mut choices);
 
             let mut sentinel = WordleTree::new_sentinel();
-            clubs.best_strategy(within, &choices, true, &mut sentinel);
+            clubs.best_strategy(&within, &choices, true, &mut sentinel);
             let mut tree = sentinel.take_first_child().unwrap();
             tree.outer_total_turns = best as f64;
 
@@ -139,10 +243,12 @@ fn main() {
         "assess" => {
             // Convert args to comma delimited
             let strategy_path = args[0];
-            let args = args[1..].iter().map(|s| s.to_string()).collect::<Vec<String>>();
+            let json = args.contains(&"--json");
+            let args = args[1..].iter().filter(|s| **s != "--json").map(|s| s.to_string()).collect::<Vec<String>>();
             let guesses = args.join(",");
 
-            let result = assess_inner(Some(&guesses), &_valid, &_answers, &strategy_path);
+            let matrix = load_matrix(set, &_valid, &_answers);
+            let result = assess_inner(Some(&guesses), &_valid, &_answers, &matrix, &strategy_path, json);
             match result {
                 Ok(result) => println!("{}", result),
                 Err(e) => println!("{}", e),
@@ -157,14 +263,13 @@ fn main() {
             // Simulating for a set of games estimates how different strategies would've done in a real life sequence of time.
             // Simulating for one answer shows average turns over many plays when random guesses are involved in the game.
             // Simulating for a cluster shows how the strategy performs in a particular cluster (and whether the "total turns" computed for it by build is accurate)
-            const USAGE: &str = "Usage: wordle_v2 simulate <game_count> <tree_file_path> [--games <answers_file_path> | --answer <single_answer> | --cluster <target_word> <at_turn>]? [--total]";
+            const USAGE: &str = "Usage: wordle_v2 simulate <game_count> <tree_file_path> [--games <answers_file_path> | --answer <single_answer> | --cluster <target_word> <at_turn>]? [--total]? [--seed <n>]? [--threads <n>]? [--json]?";
             if args.len() < 2 {
                 println!("Not enough arguments.\n{}", USAGE);
                 return;
             }
 
             let game_count = args[0].parse::<usize>().unwrap();
-            let print = game_count < 100;
             args = &args[1..];
 
             let strategy_path = args[0];
@@ -172,9 +277,13 @@ fn main() {
             let strategy_text = fs::read_to_string(&strategy_path).unwrap();
             let tree = WordleTree::parse(strategy_text.lines()).unwrap();
             let mut player = tree_player::TreePlayer::new(&tree);
+            player.set_hard_mode(hard_mode);
 
             let mut game_answers = None;
             let mut show_average_turns = true;
+            let mut json = false;
+            let mut run = check::SimulationRun::default();
+            run.print = game_count < 100;
             while args.len() > 0 {
                 if args[0] == "--games" {
                     game_answers = Some(Word::parse_file(Path::new(args[1])));
@@ -195,6 +304,16 @@ fn main() {
                 } else if args[0] == "--total" {
                     show_average_turns = false;
                     args = &args[1..];
+                } else if args[0] == "--seed" {
+                    run.seed = args[1].parse::<u64>().unwrap();
+                    args = &args[2..];
+                } else if args[0] == "--threads" {
+                    run.threads = args[1].parse::<usize>().unwrap();
+                    args = &args[2..];
+                } else if args[0] == "--json" {
+                    json = true;
+                    run.print = false;
+                    args = &args[1..];
                 } else {
                     println!("Unrecognized argument '{}', {}", args[0], USAGE);
                     return;
@@ -205,26 +324,203 @@ fn main() {
             let answer_count = game_answers.len();
             let answer_description = if answer_count <= 10 { format!("{{{}}}", game_answers.iter().map(|w| w.to_string()).collect::<Vec<String>>().join(", ")) } else { format!("({}, {})", game_answers.first().unwrap(), answer_count) };
 
-            println!("Simulating {game_count} games for {strategy_path} in {answer_description}:");
-            let average_turns = check::simulate(&_answers, &game_answers, &Vec::new(), game_count, &mut |g, t, a| player.choose(g, t, a), print);
+            if json {
+                let (report, _) = check::simulate_tree(&tree, &_answers, &game_answers, game_count, hard_mode, weights.as_ref(), &run);
+                println!("{}", report.to_json().to_pretty_string());
+                return;
+            }
+
+            println!("Simulating {game_count} games for {strategy_path} in {answer_description} (--seed {}):", run.seed);
+            let (report, mut player) = check::simulate_tree(&tree, &_answers, &game_answers, game_count, hard_mode, weights.as_ref(), &run);
+            let average_turns = report.average_turns();
 
             let mut options = WordleTreeToStringOptions::default();
             options.show_average_turns = show_average_turns;
             options.show_zero_turn_paths = true;
             options.always_show_identifiers = true;
             options.show_original_turns = false;
+            options.show_turn_histogram = true;
             println!();
             println!("{}", player.to_string(game_answers.len(), &options));
 
             let total_turns = average_turns * answer_count as f64;
             println!();
+            println!("{report}");
             println!("{total_turns:.0} ({average_turns:.3})");
         }
 
+        "compare" => {
+            const USAGE: &str = "Usage: wordle_v2 compare <game_count> <left_tree_path> <right_tree_path> [--seed <n>]? [--threads <n>]?";
+            if args.len() < 3 {
+                println!("Not enough arguments.\n{}", USAGE);
+                return;
+            }
+
+            let game_count = args[0].parse::<usize>().unwrap();
+            let left = WordleTree::parse(fs::read_to_string(args[1]).unwrap().lines()).unwrap();
+            let right = WordleTree::parse(fs::read_to_string(args[2]).unwrap().lines()).unwrap();
+            let (left_path, right_path) = (args[1], args[2]);
+            args = &args[3..];
+
+            let mut run = check::SimulationRun::default();
+            while args.len() > 0 {
+                if args[0] == "--seed" {
+                    run.seed = args[1].parse::<u64>().unwrap();
+                    args = &args[2..];
+                } else if args[0] == "--threads" {
+                    run.threads = args[1].parse::<usize>().unwrap();
+                    args = &args[2..];
+                } else {
+                    println!("Unrecognized argument '{}', {}", args[0], USAGE);
+                    return;
+                }
+            }
+
+            println!("Comparing {left_path} (left) to {right_path} (right) over {game_count} games (--seed {}):", run.seed);
+            let comparison = compare::compare(&left, &right, &_answers, &_answers, game_count, hard_mode, weights.as_ref(), &run);
+            println!("{comparison}");
+
+            println!();
+            println!("Answers differing most (right - left):");
+            for (answer, difference) in comparison.answer_differences().iter().take(20) {
+                println!("  {answer}  {difference:+.3}");
+            }
+
+            for (name, tree, other) in [("left", &left, &right), ("right", &right, &left)] {
+                println!();
+                println!("Nodes in {name} where turns differ most (total, right - left):");
+                for (node, difference) in comparison.node_differences(tree, other, &_answers).iter().take(10) {
+                    println!("  {difference:+8.1}  {node}");
+                }
+            }
+        }
+
+        "diff" => {
+            if args.len() < 2 {
+                println!("Usage: wordle_v2 diff <left_tree_path> <right_tree_path>");
+                return;
+            }
+
+            let left = WordleTree::parse(fs::read_to_string(args[0]).unwrap().lines()).unwrap();
+            let right = WordleTree::parse(fs::read_to_string(args[1]).unwrap().lines()).unwrap();
+            println!("{}", left.diff(&right));
+        }
+
+        "human" => {
+            if args.is_empty() {
+                println!("Usage: wordle_v2 human <tree_file_path>");
+                return;
+            }
+
+            let tree = WordleTree::parse(fs::read_to_string(args[0]).unwrap().lines()).unwrap();
+            match tree.to_human_string() {
+                Ok(text) => print!("{text}"),
+                Err(message) => println!("{message}"),
+            }
+        }
+
+        "json" => {
+            if args.is_empty() {
+                println!("Usage: wordle_v2 json <tree_file_path>");
+                return;
+            }
+
+            let text = fs::read_to_string(args[0]).unwrap();
+            if text.trim_start().starts_with('{') {
+                match json::Json::parse(&text).and_then(|json| WordleTree::from_json(&json)) {
+                    Ok(tree) => println!("{}", tree.to_string()),
+                    Err(message) => println!("{message}"),
+                }
+            } else {
+                let tree = WordleTree::parse(text.lines()).unwrap();
+                println!("{}", tree.to_json().to_pretty_string());
+            }
+        }
+
+        "graph" => {
+            if args.is_empty() {
+                println!("Usage: wordle_v2 graph <tree_file_path> [--svg]? [--average]? [--collapse <n>]?");
+                return;
+            }
+
+            let tree = WordleTree::parse(fs::read_to_string(args[0]).unwrap().lines()).unwrap();
+
+            let mut options = WordleTreeToStringOptions::default();
+            options.show_average_turns = args.contains(&"--average");
+            let collapse_under = args.iter().position(|a| *a == "--collapse").map(|i| args[i + 1].parse::<usize>().unwrap()).unwrap_or(0);
+
+            if args.contains(&"--svg") {
+                print!("{}", tree.to_svg(&options, collapse_under));
+            } else {
+                print!("{}", tree.to_dot(&options, collapse_under));
+            }
+        }
+
+        "lint" => {
+            if args.is_empty() {
+                println!("Usage: wordle_v2 lint <tree_file_path>");
+                return;
+            }
+
+            let issues = wordle_tree::lint::lint(&fs::read_to_string(args[0]).unwrap(), &_answers);
+            for issue in issues.iter() {
+                println!("{issue}");
+            }
+            println!("{} issues", issues.len());
+        }
+
+        "retarget" => {
+            if args.len() < 2 {
+                println!("Usage: wordle_v2 retarget <tree_file_path> <new_answers_path>");
+                return;
+            }
+
+            let tree = WordleTree::parse(fs::read_to_string(args[0]).unwrap().lines()).unwrap();
+            let new_answers = Word::parse_file(Path::new(args[1]));
+            let retargeted = tree.retarget(&_answers, &new_answers);
+
+            let mut options = WordleTreeToStringOptions::default();
+            options.always_show_identifiers = true;
+            let mut output = String::new();
+            retargeted.tree.add_to_string(&options, 0, &mut output);
+            println!("{output}");
+            for note in retargeted.notes.iter() {
+                println!("{note}");
+            }
+        }
+
+        "evaluate" => {
+            if args.is_empty() {
+                println!("Usage: wordle_v2 evaluate <tree_file_path> [--total]?");
+                return;
+            }
+
+            let strategy_text = fs::read_to_string(args[0]).unwrap();
+            let tree = WordleTree::parse(strategy_text.lines()).unwrap();
+            let mut player = tree_player::TreePlayer::new(&tree);
+            player.set_hard_mode(hard_mode);
+
+            let evaluation = player.evaluate(&_answers);
+
+            let mut options = WordleTreeToStringOptions::default();
+            options.show_average_turns = !args.contains(&"--total");
+            options.show_zero_turn_paths = true;
+            options.always_show_identifiers = true;
+            options.show_original_turns = false;
+            println!("{}", evaluation.to_string(&tree, &options));
+
+            println!();
+            println!("Solved on each turn: {}", evaluation.turn_counts);
+            println!("{:.1} ({:.4}) exact; {:.0} in the tree", evaluation.total_turns(), evaluation.average_turns(), tree.outer_total_turns);
+        }
+
         "build" => {
             let strategy = args[0].to_ascii_lowercase();
             let guesses = args[1..].iter().map(|s| Word::new(s).unwrap()).collect::<Vec<Word>>();
-            let tree = wordle_tree::builders::build(&strategy, &_answers, &guesses);
+            let tree = match wordle_tree::builders::build(&strategy, &_answers, &_valid, &guesses, hard_mode, objective, weights.as_ref()) {
+                Ok(tree) => tree,
+                Err(message) => { println!("{message}"); return; }
+            };
 
             let mut options = WordleTreeToStringOptions::default();
             options.show_average_turns  = options.show_average_turns;
@@ -234,26 +530,69 @@ fn main() {
             println!("{}", output);
         }
 
+        "pareto" => {
+            let strategy = args[0].to_ascii_lowercase();
+            let guesses = args[1..].iter().map(|s| Word::new(s).unwrap()).collect::<Vec<Word>>();
+            let tree = match wordle_tree::builders::build(&strategy, &_answers, &_valid, &guesses, hard_mode, objective, weights.as_ref()) {
+                Ok(tree) => tree,
+                Err(message) => { println!("{message}"); return; }
+            };
+
+            let answer_count = tree.answer_count;
+            let front = wordle_tree::complexity::pareto_front(tree, &_answers);
+
+            println!("Complexity                            Turns");
+            for point in front.iter() {
+                let last = point.collapsed.last().map(|s| format!("after collapsing {s} ({} collapsed)", point.collapsed.len())).unwrap_or_else(|| "as built".to_string());
+                println!("{:<36}  {:.0} ({:.3})  {last}", point.complexity.to_string(), point.total_turns, point.total_turns / answer_count as f64);
+            }
+        }
+
+        "solve" => {
+            let first_guess = args.first().map(|s| Word::new(s).unwrap());
+            let matrix = load_matrix(set, &_valid, &_answers);
+
+            let mut solver = match solver::Solver::new(&matrix) {
+                Ok(solver) => solver,
+                Err(e) => { println!("{e}"); return; }
+            };
+
+            if let Some(weights) = &weights { solver.set_weights(weights); }
+
+            match solver.solve(first_guess) {
+                Ok(tree) => {
+                    println!("{}", tree.to_string());
+                    println!("{:.0} ({:.3}); {} clusters searched", tree.outer_total_turns, tree.outer_total_turns / tree.answer_count as f64, solver.clusters_searched);
+                },
+                Err(e) => println!("{e}")
+            }
+        }
+
         "analyze" => {
             let guesses_and_responses = read_guesses_and_responses(args);
             let guesses_and_responses = analyze::parse_into_guesses_and_responses(guesses_and_responses);
-            analyze::analyze(&_answers, &_valid, guesses_and_responses);
+            let matrix = load_matrix(set, &_valid, &_answers);
+            analyze::analyze(&_answers, &_valid, Some(&matrix), guesses_and_responses);
         }
 
         "stats" => {
             let guesses_and_responses = read_guesses_and_responses(args);
             let guesses_and_responses = analyze::parse_into_guesses_and_responses(guesses_and_responses);
-            analyze::stats(&_answers, &_valid, guesses_and_responses);
+            let matrix = load_matrix(set, &_valid, &_answers);
+            analyze::stats(&_answers, &_valid, Some(&matrix), guesses_and_responses);
         }
 
         "search" => {
             if args.len() < 1 {
-                println!("Usage: wordle_v2 search [--after <after_word>]? [--cutoff <cutoff>]? [--ratio <cutoff_ratio>]? <count_to_find> <starting_guesses>...");
+                println!("Usage: wordle_v2 search [--after <after_word>]? [--cutoff <cutoff>]? [--ratio <cutoff_ratio>]? [--ranker <ranker>]? [--threads <thread_count>]? [--checkpoint <checkpoint_file_path>]? <count_to_find> <starting_guesses>...");
                 return;
             }
 
             let mut cutoff = 0.0;
             let mut cutoff_ratio = 0.0;
+            let mut ranker_name = "predicted";
+            let mut run = search::SearchRun::default();
+            let mut checkpoint = None;
 
             while let Some(first) = args.first() {
                 match *first {
@@ -265,6 +604,18 @@ fn main() {
                         cutoff_ratio = args[1].parse::<f64>().unwrap();
                         args = &args[2..];
                     },
+                    "--ranker" => {
+                        ranker_name = args[1];
+                        args = &args[2..];
+                    },
+                    "--threads" => {
+                        run.threads = args[1].parse::<usize>().unwrap();
+                        args = &args[2..];
+                    },
+                    "--checkpoint" => {
+                        checkpoint = Some(Path::new(args[1]));
+                        args = &args[2..];
+                    },
                     "--after" => {
                         let word = Word::new(args[1]).unwrap();
                         let index = _valid.iter().position(|w| *w >= word).unwrap();
@@ -278,8 +629,25 @@ fn main() {
             let count_left = args.first().unwrap().parse::<usize>().unwrap();
             let initial_guesses = args[1..].iter().map(|s| Word::new(s).unwrap()).collect::<Vec<Word>>();
 
-            let mut best = search::find_best(
+            // Show progress at most every few seconds
+            let last_shown = std::sync::Mutex::new(Instant::now());
+            let progress = |p: &search::SearchProgress| {
+                let mut last_shown = last_shown.lock().unwrap();
+                if p.options_done < p.option_count && last_shown.elapsed().as_secs() < 5 { return; }
+                *last_shown = Instant::now();
+
+                let eta = p.eta.map(|eta| format!("{}s", eta.as_secs())).unwrap_or("?".to_string());
+                let best = p.best.as_ref().map(|(score, guesses)| format!("{}: {:?}", score, guesses)).unwrap_or_default();
+                println!("  {} / {} options, {} ranked, cutoff {:.0}, {}s elapsed, {} left; best {}", p.options_done, p.option_count, p.count_ranked, p.cluster_cutoff, p.elapsed.as_secs(), eta, best);
+            };
+
+            run.checkpoint = checkpoint;
+            run.progress = Some(&progress);
+
+            let matrix = load_matrix(set, &_valid, &_answers);
+            let best = search::find_best(
                 &_answers, 
+                Some(&matrix),
                 &_valid, 
                 initial_guesses,
                 count_left,
@@ -288,12 +656,19 @@ fn main() {
                 //search::score_cluster_count
                 //score::total_turns_ideal_map
                 //score::total_turns_pessimistic_map
-                rank::total_turns_predicted_map,
-                //rank::total_turns_random_map,
+                ranker_name,
+                objective,
+                weights.as_ref(),
                 cutoff,
-                cutoff_ratio
+                cutoff_ratio,
+                &run
             );
 
+            let mut best = match best {
+                Ok(best) => best,
+                Err(message) => { println!("{message}"); return; }
+            };
+
             println!();
             println!("Top {} (last is best):", best.len());
             while let Some((score, guesses, cv)) = best.pop() {
@@ -301,6 +676,38 @@ fn main() {
             }
         }
 
+        "adaptive" => {
+            if args.len() < 1 {
+                println!("Usage: wordle_v2 adaptive [--ranker <ranker>]? <opening_guesses>...");
+                return;
+            }
+
+            let mut ranker_name = "predicted";
+            if args[0] == "--ranker" && args.len() > 1 {
+                ranker_name = args[1];
+                args = &args[2..];
+            }
+
+            let ranker = match if weights.is_some() { rank::weighted_map_ranker(ranker_name) } else { rank::map_ranker(ranker_name) } {
+                Ok(ranker) => ranker,
+                Err(message) => { println!("{message}"); return; }
+            };
+
+            // Find the best second guess per response for each opener, then compare them
+            let matrix = load_matrix(set, &_valid, &_answers);
+            let mut results = Vec::new();
+            for opener in args.iter().map(|s| Word::new(s).unwrap()) {
+                let tree = search::find_best_adaptive(&_answers, Some(&matrix), &_valid, opener, ranker, objective, weights.as_ref());
+                println!("{}", tree.to_string());
+                results.push((tree.outer_total_turns, opener, tree.answer_count));
+            }
+
+            results.sort_by(|l, r| l.0.total_cmp(&r.0));
+            for (total_turns, opener, answer_count) in results {
+                println!("{:.0} ({:.3}) {}", total_turns, total_turns / answer_count as f64, opener);
+            }
+        }
+
         "orders" => {
             // Show the possible letter orderings given known letters.
             // Pass letter+position pairs. Uppercase for green letters, lowercase for yellow.
@@ -372,7 +779,22 @@ fn main() {
                 }
             }
 
-            println!("{} score iterations", count);
+            println!("{} score iterations in {:.3} sec", count, start.elapsed().as_secs_f64());
+
+            // Compare to loading (or building) and looking up responses in a ResponseMatrix
+            let lookup_start = Instant::now();
+            let matrix = load_matrix(set, &_valid, &_answers);
+            println!("ResponseMatrix loaded in {:.3} sec", lookup_start.elapsed().as_secs_f64());
+
+            let lookup_start = Instant::now();
+            let mut green_count = 0;
+            for guess_index in 0..matrix.guesses().len() {
+                for answer_index in 0..matrix.answers().len() {
+                    if matrix.response(guess_index, answer_index).value == response::ALL_GREEN { green_count += 1; }
+                }
+            }
+
+            println!("{} lookups ({} all green) in {:.3} sec", count, green_count, lookup_start.elapsed().as_secs_f64());
         }
 
         "setup" => {
@@ -390,6 +812,11 @@ fn main() {
     eprintln!(" -> {duration:.3} sec");
 }
 
+/// Load the cached responses for every valid guess and answer in a word set, building and caching them the first time.
+fn load_matrix(set: &str, valid: &Vec<Word>, answers: &Vec<Word>) -> ResponseMatrix {
+    ResponseMatrix::load_or_build(Path::new(&format!("../data/{set}/responses.bin")), valid, answers)
+}
+
 fn read_guesses_and_responses(args: &[&str]) -> Vec<String> {
     if args.len() > 0 {
         args.iter().map(|s| s.to_string()).collect::<Vec<String>>()
@@ -411,12 +838,16 @@ fn read_guesses_and_responses(args: &[&str]) -> Vec<String> {
     }
 }
 
-fn assess_inner(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, strategy_path: &str) -> Result<String, String> {
+fn assess_inner(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, matrix: &ResponseMatrix, strategy_path: &str, json: bool) -> Result<String, String> {
     let simulate_game_count = 10000;
 
     let strategy_text = fs::read_to_string(&strategy_path).unwrap();
     let tree = WordleTree::parse(strategy_text.lines()).unwrap();
     let player = tree_player::TreePlayer::new(&tree);
 
-    return check::assess_and_simulate(guesses, valid, answers, simulate_game_count, player);
+    if json {
+        return check::assess_and_simulate_json(guesses, valid, answers, simulate_game_count, player, Some(matrix)).map(|json| json.to_pretty_string());
+    }
+
+    return check::assess_and_simulate(guesses, valid, answers, simulate_game_count, player, Some(matrix));
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
 use std::{collections::HashMap, mem};
 
-use crate::{cluster_vector::*, response::{Response, ResponseSet}, state::State, word::Word, rank};
+use crate::{cluster_vector::*, response::{Response, ResponseSet}, response_matrix::ResponseMatrix, state::State, word::Word, rank};
 
-pub fn analyze(answers: &Vec<Word>, valid: &Vec<Word>, guesses_and_responses: Vec<(Word, Option<Response>)>) {
+pub fn analyze(answers: &Vec<Word>, valid: &Vec<Word>, matrix: Option<&ResponseMatrix>, guesses_and_responses: Vec<(Word, Option<Response>)>) {
     let mut state = State::new(answers, valid);
+    if let Some(matrix) = matrix { state.set_matrix(matrix); }
 
     for (word, response) in guesses_and_responses {
         state.filter(word, response);
@@ -12,8 +13,9 @@ pub fn analyze(answers: &Vec<Word>, valid: &Vec<Word>, guesses_and_responses: Ve
     state.print();
 }
 
-pub fn stats(answers: &Vec<Word>, valid: &Vec<Word>, guesses_and_responses: Vec<(Word, Option<Response>)>) {
+pub fn stats(answers: &Vec<Word>, valid: &Vec<Word>, matrix: Option<&ResponseMatrix>, guesses_and_responses: Vec<(Word, Option<Response>)>) {
     let mut state = State::new(answers, valid);
+    if let Some(matrix) = matrix { state.set_matrix(matrix); }
 
     for (word, response) in guesses_and_responses {
         state.filter(word, response);
@@ -0,0 +1,244 @@
+/// A growable set of indices stored as an array of u64 words.
+///  There is no limit on the largest index, so it can hold clusters of any size.
+///  Trailing zero words are always trimmed, so equal sets compare and hash equally regardless of how they were built.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BitVector {
+    words: Vec<u64>
+}
+
+impl BitVector {
+    pub fn new() -> BitVector {
+        BitVector { words: Vec::new() }
+    }
+
+    pub fn new_all(limit: usize) -> BitVector {
+        let mut result = BitVector::new();
+        result.all(limit);
+        result
+    }
+
+    pub fn from_vec(indices: Vec<usize>) -> BitVector {
+        let mut result = BitVector::new();
+
+        for index in indices {
+            result.add(index);
+        }
+
+        result
+    }
+
+    pub fn add(&mut self, index: usize) {
+        let word = index / 64;
+        while self.words.len() <= word { self.words.push(0u64); }
+        self.words[word] |= 1u64 << (index % 64);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        let word = index / 64;
+        if word < self.words.len() {
+            self.words[word] &= !(1u64 << (index % 64));
+            self.trim();
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        match self.words.get(index / 64) {
+            Some(bits) => (bits & (1u64 << (index % 64))) != 0,
+            None => false
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    pub fn all(&mut self, limit: usize) {
+        self.words.clear();
+        self.words.resize(limit / 64, !0u64);
+
+        let remainder = limit % 64;
+        if remainder > 0 {
+            self.words.push((!0u64) >> (64 - remainder));
+        }
+    }
+
+    pub fn not(&mut self, limit: usize) {
+        let mut mask = BitVector::new_all(limit);
+        mask.except_with(self);
+        *self = mask;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.words.iter().map(|bits| bits.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn iter(&self) -> BitVectorIterator<'_> {
+        BitVectorIterator { words: &self.words, word_index: 0, bits: self.words.first().copied().unwrap_or(0) }
+    }
+
+    pub fn union_with(&mut self, other: &BitVector) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0u64);
+        }
+
+        for (bits, other_bits) in self.words.iter_mut().zip(other.words.iter()) {
+            *bits |= other_bits;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitVector) {
+        self.words.truncate(other.words.len());
+
+        for (bits, other_bits) in self.words.iter_mut().zip(other.words.iter()) {
+            *bits &= other_bits;
+        }
+
+        self.trim();
+    }
+
+    pub fn except_with(&mut self, other: &BitVector) {
+        for (bits, other_bits) in self.words.iter_mut().zip(other.words.iter()) {
+            *bits &= !other_bits;
+        }
+
+        self.trim();
+    }
+
+    /// Return a new set with the indices in both 'self' and 'other', without copying all of either one first.
+    pub fn intersection(&self, other: &BitVector) -> BitVector {
+        let mut result = BitVector { words: self.words.iter().zip(other.words.iter()).map(|(l, r)| l & r).collect() };
+        result.trim();
+        result
+    }
+
+    /// Return a new set with the indices in 'self' which are not in 'other'.
+    pub fn difference(&self, other: &BitVector) -> BitVector {
+        let mut result = self.clone();
+        result.except_with(other);
+        result
+    }
+
+    // Remove trailing empty words, so that equal sets always have equal representations.
+    fn trim(&mut self) {
+        while let Some(0u64) = self.words.last() {
+            self.words.pop();
+        }
+    }
+}
+
+pub struct BitVectorIterator<'a> {
+    words: &'a Vec<u64>,
+    word_index: usize,
+    bits: u64
+}
+
+impl Iterator for BitVectorIterator<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.bits == 0 {
+            self.word_index += 1;
+            if self.word_index >= self.words.len() { return None; }
+            self.bits = self.words[self.word_index];
+        }
+
+        let index = self.bits.trailing_zeros();
+        self.bits &= !(1u64 << index);
+        Some(self.word_index * 64 + index as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_vector_basics() {
+        // Vector starts empty
+        let mut vector = BitVector::new();
+        assert_eq!(vector.count(), 0);
+        assert_eq!(format!("{:?}", vector.iter().collect::<Vec<_>>()), "[]");
+
+        // Verify add, count, iterator
+        vector.add(4);
+        assert_eq!(vector.count(), 1);
+        assert_eq!(format!("{:?}", vector.iter().collect::<Vec<_>>()), "[4]");
+
+        // Add more (including beyond the first 64), verify contains
+        vector.add(8);
+        vector.add(130);
+        assert_eq!(vector.count(), 3);
+        assert!(vector.contains(130));
+        assert!(vector.contains(8));
+        assert!(!vector.contains(2));
+        assert!(!vector.contains(5000));
+        assert_eq!(format!("{:?}", vector.iter().collect::<Vec<_>>()), "[4, 8, 130]");
+
+        // Remove, verify does not contain, iterator, and equal to a vector never containing it
+        vector.remove(130);
+        assert_eq!(vector.count(), 2);
+        assert!(!vector.contains(130));
+        assert_eq!(format!("{:?}", vector.iter().collect::<Vec<_>>()), "[4, 8]");
+        assert_eq!(vector, BitVector::from_vec(vec![4, 8]));
+        vector.add(130);
+
+        let other = BitVector::from_vec(vec![4, 8, 70]);
+
+        // Test AND
+        let mut result = vector.clone();
+        result.intersect_with(&other);
+        assert_eq!(format!("{:?}", result.iter().collect::<Vec<_>>()), "[4, 8]");
+        assert_eq!(result, vector.intersection(&other));
+
+        // Test OR
+        result = vector.clone();
+        result.union_with(&other);
+        assert_eq!(format!("{:?}", result.iter().collect::<Vec<_>>()), "[4, 8, 70, 130]");
+
+        // Test AND NOT
+        result = vector.clone();
+        result.except_with(&other);
+        assert_eq!(format!("{:?}", result.iter().collect::<Vec<_>>()), "[130]");
+        assert_eq!(result, vector.difference(&other));
+
+        // Test Clear
+        result.clear();
+        assert_eq!(result.count(), 0);
+        assert!(result.is_empty());
+        assert_eq!(format!("{:?}", result.iter().collect::<Vec<_>>()), "[]");
+
+        // Test All
+        result.all(9);
+        assert_eq!(result.count(), 9);
+        assert_eq!(format!("{:?}", result.iter().collect::<Vec<_>>()), "[0, 1, 2, 3, 4, 5, 6, 7, 8]");
+        result.all(64);
+        assert_eq!(result.count(), 64);
+        result.all(2315);
+        assert_eq!(result.count(), 2315);
+        assert_eq!(result.iter().last(), Some(2314));
+
+        // Test Not
+        vector.clear();
+        vector.add(2);
+        vector.add(3);
+        vector.add(100);
+        vector.not(102);
+        assert_eq!(vector.count(), 99);
+        assert!(!vector.contains(100));
+        assert!(vector.contains(101));
+
+        // Test new_all
+        vector = BitVector::new_all(6);
+        assert_eq!(vector.count(), 6);
+        assert_eq!(format!("{:?}", vector.iter().collect::<Vec<_>>()), "[0, 1, 2, 3, 4, 5]");
+
+        // Sets emptied by intersection are equal to new ones (so they hash identically)
+        let mut high = BitVector::from_vec(vec![200]);
+        high.intersect_with(&BitVector::from_vec(vec![1]));
+        assert_eq!(high, BitVector::new());
+    }
+}
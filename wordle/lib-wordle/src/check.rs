@@ -1,89 +1,362 @@
-use std::collections::HashMap;
-use rand::seq::SliceRandom;
-use crate::{word::Word, response::{Response, Constraint}, rank, cluster_vector::ClusterVector, wv_safe, wordle_tree::tree_player::TreePlayer, clubs::Clubs};
+use std::{collections::HashMap, fmt::{Display, Formatter}, ops::Range, sync::{Mutex, atomic::{AtomicUsize, Ordering}}, thread};
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use crate::{word::Word, response::{Response, Knowledge}, rank, cluster_vector::ClusterVector, wv_safe, wordle_tree::tree_player::TreePlayer, clubs::Clubs, hard_mode::HardMode, response_matrix::ResponseMatrix, weights::AnswerWeights, objective::MAX_TURNS, wordle_tree::WordleTree, json::Json};
+
+/// The outcome of a set of simulated games: how many were solved on each turn, how precise the average is, and which answers took longest.
+#[derive(Clone, Debug, Default)]
+pub struct SimulationReport {
+    pub game_count: usize,
+    pub histogram: [usize; MAX_TURNS + 1],      // Games solved on turn 1..=MAX_TURNS, then games lost (taking more turns)
+    total_turns: usize,
+    total_turns_squared: usize,
+    by_answer: HashMap<Word, (usize, usize)>,   // Total turns and games for each answer, if known
+}
 
-pub fn simulate(answers: &Vec<Word>, game_answer_pool: &Vec<Word>, guesses: &Vec<Word>, game_count: usize, strategy: &mut dyn FnMut(&Vec<Word>, usize, &Vec<Word>) -> Option<Word>, print: bool) -> f64 {
-    // Use a faster implementation if we're only considering one answer repeatedly
-    if game_answer_pool.len() == 1 {
-        return simulate_single(answers, game_answer_pool, game_answer_pool[0], game_count, strategy);
+impl SimulationReport {
+    pub fn new() -> SimulationReport {
+        SimulationReport::default()
     }
 
-    let mut rng = rand::thread_rng();
-    let mut total_turns = 0;
+    /// Count a game which took 'turns' turns, for an unknown answer
+    pub fn add_turns(&mut self, turns: usize) {
+        self.game_count += 1;
+        self.histogram[turns.clamp(1, MAX_TURNS + 1) - 1] += 1;
+        self.total_turns += turns;
+        self.total_turns_squared += turns * turns;
+    }
 
-    for _game in 0..game_count {
-        // Choose an answer
-        let answer = if game_count < game_answer_pool.len() * 2 {
-            *game_answer_pool.choose(&mut rng).unwrap()
-        } else {
-            game_answer_pool[_game % game_answer_pool.len()]
+    /// Count a game for 'answer' which took 'turns' turns
+    pub fn add_game(&mut self, answer: Word, turns: usize) {
+        self.add_turns(turns);
+        let entry = self.by_answer.entry(answer).or_default();
+        entry.0 += turns;
+        entry.1 += 1;
+    }
+
+    /// Add the games from another report
+    pub fn add_report(&mut self, other: &SimulationReport) {
+        self.game_count += other.game_count;
+        for (count, other_count) in self.histogram.iter_mut().zip(other.histogram.iter()) {
+            *count += other_count;
+        }
+        self.total_turns += other.total_turns;
+        self.total_turns_squared += other.total_turns_squared;
+
+        for (answer, (turns, games)) in other.by_answer.iter() {
+            let entry = self.by_answer.entry(*answer).or_default();
+            entry.0 += turns;
+            entry.1 += games;
+        }
+    }
+
+    pub fn total_turns(&self) -> usize {
+        self.total_turns
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        if self.game_count == 0 { return 0.0; }
+        self.total_turns as f64 / self.game_count as f64
+    }
+
+    /// The standard error of average_turns (how far it is likely to be from the true average for the strategy)
+    pub fn standard_error(&self) -> f64 {
+        standard_error(self.game_count, self.total_turns as f64, self.total_turns_squared as f64)
+    }
+
+    /// Games taking more than MAX_TURNS turns
+    pub fn failures(&self) -> usize {
+        self.histogram[MAX_TURNS]
+    }
+
+    pub fn failure_rate(&self) -> f64 {
+        if self.game_count == 0 { return 0.0; }
+        self.failures() as f64 / self.game_count as f64
+    }
+
+    /// The answers with the highest average turns, worst first (ties alphabetically)
+    pub fn worst_answers(&self, count: usize) -> Vec<(Word, f64)> {
+        let mut answers = self.by_answer.iter().map(|(answer, (turns, games))| (*answer, *turns as f64 / *games as f64)).collect::<Vec<(Word, f64)>>();
+        answers.sort_by(|l, r| r.1.total_cmp(&l.1).then(l.0.cmp(&r.0)));
+        answers.truncate(count);
+        answers
+    }
+
+    /// The histogram alone, as "[1, 2, 3, 4, 5, 6; 1 lost]"
+    pub fn histogram_string(&self) -> String {
+        let solved = self.histogram[..MAX_TURNS].iter().map(|c| c.to_string()).collect::<Vec<String>>();
+        format!("[{}; {} lost]", solved.join(", "), self.failures())
+    }
+
+    /// The report as JSON, with every count (so it can be read back and combined), plus average_turns and standard_error for convenience.
+    ///  by_answer is {"crane": [total turns, games], ...}, sorted by answer.
+    pub fn to_json(&self) -> Json {
+        let mut by_answer = self.by_answer.iter().collect::<Vec<(&Word, &(usize, usize))>>();
+        by_answer.sort();
+
+        Json::object(vec![
+            ("game_count", self.game_count.into()),
+            ("histogram", Json::Array(self.histogram.iter().map(|c| Json::from(*c)).collect())),
+            ("total_turns", self.total_turns.into()),
+            ("total_turns_squared", self.total_turns_squared.into()),
+            ("average_turns", self.average_turns().into()),
+            ("standard_error", self.standard_error().into()),
+            ("by_answer", Json::Object(by_answer.iter().map(|(answer, (turns, games))| (answer.to_string(), Json::Array(vec![(*turns).into(), (*games).into()]))).collect())),
+        ])
+    }
+
+    /// Read a report written by to_json (the derived average_turns and standard_error are recomputed, not read)
+    pub fn from_json(json: &Json) -> Result<SimulationReport, String> {
+        let mut report = SimulationReport {
+            game_count: json.usize_field("game_count")?,
+            total_turns: json.usize_field("total_turns")?,
+            total_turns_squared: json.usize_field("total_turns_squared")?,
+            ..SimulationReport::default()
         };
-        //let _a = answer.to_string();
 
-        // Simulate the game
-        let mut turn: usize = 0;
-        let mut answers_left = answers.clone();
-        let mut constraint = Constraint::new();
-    
-        if print {
-            println!("=== {} ===", answer.to_string().to_ascii_uppercase());
+        let histogram = json.array_field("histogram")?;
+        if histogram.len() != report.histogram.len() {
+            return Err(format!("'histogram' must have {} counts", report.histogram.len()));
         }
-    
-        loop {
-            turn += 1;
 
-            // Choose a guess
-            let guess = strategy(guesses, turn, &answers_left).unwrap_or_else(|| *answers_left.choose(&mut rng).unwrap());
-            
-            // Score
-            let response = Response::score(guess, answer);
-            constraint.add(guess, response);
-    
-            // Filter remaining answers
-            answers_left.retain(|a| constraint.matches(*a) && Response::score(guess, *a) == response);
-            let count_left = answers_left.len();
-    
-            if print { 
-                print!("{turn}) {guess}: {response} -> {count_left}");
-                if count_left < 10 { 
-                    println!("     {:?}", answers_left); 
-                } else {
-                    println!();
+        for (count, value) in report.histogram.iter_mut().zip(histogram.iter()) {
+            *count = value.as_usize().ok_or("'histogram' counts must be whole numbers")?;
+        }
+
+        match json.get("by_answer") {
+            None => {},
+            Some(Json::Object(by_answer)) => {
+                for (answer, value) in by_answer.iter() {
+                    let word = Word::new(answer).ok_or_else(|| format!("'{answer}' is not a valid word"))?;
+                    let counts = value.as_array().map(|v| v.iter().filter_map(|c| c.as_usize()).collect::<Vec<usize>>()).unwrap_or_default();
+                    let [turns, games] = counts[..] else { return Err(format!("'{answer}' must have [total turns, games]")); };
+                    report.by_answer.insert(word, (turns, games));
                 }
-            }
-    
-            if guess == answer || answers_left.len() == 0 { break; }
+            },
+            Some(_) => return Err("'by_answer' must be an object of answers to [total turns, games]".to_string()),
         }
-    
-        if print { println!(); }
-        total_turns += turn;
+
+        Ok(report)
     }
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Solved on each turn: {}", self.histogram_string())?;
+        writeln!(f, "{:.4} ± {:.4} avg turns over {} games; {:.2}% lost", self.average_turns(), self.standard_error(), self.game_count, self.failure_rate() * 100.0)?;
+
+        let worst = self.worst_answers(10);
+        if !worst.is_empty() {
+            let worst = worst.iter().map(|(answer, turns)| format!("{answer} {turns:.2}")).collect::<Vec<String>>();
+            write!(f, "Worst answers: {}", worst.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
 
-    let average_turns = (total_turns as f64) / (game_count as f64);
+/// The standard error of the mean of 'count' samples, from their total and the total of their squares
+pub(crate) fn standard_error(count: usize, total: f64, total_squared: f64) -> f64 {
+    if count < 2 { return 0.0; }
+    let n = count as f64;
+    let mean = total / n;
+    let variance = (total_squared - n * mean * mean) / (n - 1.0);
+    (variance.max(0.0) / n).sqrt()
+}
+
+// Games per block in run_blocks; each block has its own seed, so results don't depend on the thread count
+const SIMULATION_BLOCK_SIZE: usize = 10_000;
+
+/// Simulate games with a strategy, returning the turn counts and average turns per game.
+///  In hard mode, any strategy guess which doesn't use all revealed hints is replaced with a random remaining answer.
+///  With weights, each game's answer is chosen from the pool in proportion to its weight.
+///  Pass an rng from a fixed seed (StdRng::seed_from_u64) to repeat a simulation exactly.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate(answers: &Vec<Word>, game_answer_pool: &Vec<Word>, guesses: &Vec<Word>, game_count: usize, strategy: &mut dyn FnMut(&Vec<Word>, usize, &Vec<Word>) -> Option<Word>, hard_mode: bool, weights: Option<&AnswerWeights>, rng: &mut StdRng, print: bool) -> SimulationReport {
+    simulate_range(answers, game_answer_pool, guesses, 0..game_count, game_count, strategy, hard_mode, weights, rng, print)
+}
+
+/// How to split up simulate_tree and compare::compare.
+pub struct SimulationRun {
+    pub seed: u64,
+    pub threads: usize,
+    pub print: bool,        // Print each game (on one thread, so they print in order)
+}
+
+impl Default for SimulationRun {
+    /// A random seed (show it to repeat the simulation), on every core
+    fn default() -> SimulationRun {
+        SimulationRun::seeded(rand::random())
+    }
+}
+
+impl SimulationRun {
+    /// Use every core, with a seed to repeat the simulation exactly
+    pub fn seeded(seed: u64) -> SimulationRun {
+        SimulationRun { seed, threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1), print: false }
+    }
+}
+
+/// Simulate games playing a strategy tree, split across threads, returning the report and a TreePlayer with the turns through each node.
+///  Games are simulated in blocks with their own seed, so the same seed gives the same results with any thread count.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_tree<'a>(tree: &'a WordleTree, answers: &Vec<Word>, game_answer_pool: &Vec<Word>, game_count: usize, hard_mode: bool, weights: Option<&AnswerWeights>, run: &SimulationRun) -> (SimulationReport, TreePlayer<'a>) {
+    let new_state = || {
+        let mut player = TreePlayer::new(tree);
+        player.set_hard_mode(hard_mode);
+        (SimulationReport::new(), player)
+    };
+
+    let play_block = |(report, player): &mut (SimulationReport, TreePlayer<'a>), games: Range<usize>, rng: &mut StdRng| {
+        let block_report = simulate_range(answers, game_answer_pool, &Vec::new(), games, game_count, &mut |g, t, a| player.choose(g, t, a), hard_mode, weights, rng, run.print);
+        report.add_report(&block_report);
+    };
+
+    let mut result = (SimulationReport::new(), TreePlayer::new(tree));
+    for (report, mut player) in run_blocks(game_count, run, new_state, play_block) {
+        result.0.add_report(&report);
+        result.1.add_play_stats(&mut player);
+    }
+
+    result
+}
+
+/// Play 'game_count' games in blocks split across threads, as 'run' says. Each thread gets its own state from 'new_state',
+///  and 'play_block' plays a range of games with an rng seeded for the block. Returns each thread's state, to combine.
+pub(crate) fn run_blocks<S: Send>(game_count: usize, run: &SimulationRun, new_state: impl Fn() -> S + Sync, play_block: impl Fn(&mut S, Range<usize>, &mut StdRng) + Sync) -> Vec<S> {
+    let block_count = game_count.div_ceil(SIMULATION_BLOCK_SIZE);
+    let next_block = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        let threads = if run.print { 1 } else { run.threads.clamp(1, block_count.max(1)) };
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let mut state = new_state();
+
+                loop {
+                    let block = next_block.fetch_add(1, Ordering::Relaxed);
+                    if block >= block_count { break; }
+
+                    let games = (block * SIMULATION_BLOCK_SIZE)..((block + 1) * SIMULATION_BLOCK_SIZE).min(game_count);
+                    let mut rng = StdRng::seed_from_u64(run.seed ^ (block as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                    play_block(&mut state, games, &mut rng);
+                }
+
+                results.lock().unwrap().push(state);
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+/// Choose the answer for game number 'game' of 'game_count': weighted if there are weights, at random if there aren't
+///  enough games to play every answer twice, and otherwise each answer in turn.
+pub(crate) fn choose_answer(game_answer_pool: &[Word], game: usize, game_count: usize, weights: Option<&AnswerWeights>, rng: &mut StdRng) -> Word {
+    if let Some(weights) = weights {
+        weights.choose(game_answer_pool, rng)
+    } else if game_count < game_answer_pool.len() * 2 {
+        *game_answer_pool.choose(rng).unwrap()
+    } else {
+        game_answer_pool[game % game_answer_pool.len()]
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn simulate_range(answers: &Vec<Word>, game_answer_pool: &Vec<Word>, guesses: &Vec<Word>, games: Range<usize>, game_count: usize, strategy: &mut dyn FnMut(&Vec<Word>, usize, &Vec<Word>) -> Option<Word>, hard_mode: bool, weights: Option<&AnswerWeights>, rng: &mut StdRng, print: bool) -> SimulationReport {
+    // Use a faster implementation if we're only considering one answer repeatedly
+    if game_answer_pool.len() == 1 {
+        return simulate_single(answers, game_answer_pool, game_answer_pool[0], games.len(), strategy, hard_mode, rng);
+    }
+
+    let mut report = SimulationReport::new();
+
+    for _game in games {
+        // Choose an answer
+        let answer = choose_answer(game_answer_pool, _game, game_count, weights, rng);
+        //let _a = answer.to_string();
+
+        let turn = play_game(answers, answer, guesses, strategy, hard_mode, rng, print);
+        report.add_game(answer, turn);
+    }
+
+    let average_turns = report.average_turns();
     let total_turns_est = average_turns * (answers.len() as f64);
     
     if print {
-        println!("{total_turns} / {game_count} = {average_turns:.4} ({total_turns_est:.0}) turns per game.");
+        println!("{} / {} = {average_turns:.4} ({total_turns_est:.0}) turns per game.", report.total_turns(), report.game_count);
+    }
+
+    report
+}
+
+/// Play one game for 'answer' with a strategy, returning the turns taken.
+///  When the strategy has no guess (or an illegal one, in hard mode), a random remaining answer is guessed.
+#[allow(clippy::type_complexity)]
+pub(crate) fn play_game(answers: &[Word], answer: Word, guesses: &Vec<Word>, strategy: &mut dyn FnMut(&Vec<Word>, usize, &Vec<Word>) -> Option<Word>, hard_mode: bool, rng: &mut StdRng, print: bool) -> usize {
+    let mut turn: usize = 0;
+    let mut answers_left = answers.to_vec();
+    let mut knowledge = Knowledge::new();
+    let mut rules = HardMode::new();
+
+    if print {
+        println!("=== {} ===", answer.to_string().to_ascii_uppercase());
+    }
+
+    loop {
+        turn += 1;
+
+        // Choose a guess (remaining answers are always legal in hard mode)
+        let guess = match strategy(guesses, turn, &answers_left) {
+            Some(guess) if !hard_mode || rules.allows(guess) => guess,
+            _ => *answers_left.choose(rng).unwrap()
+        };
+        
+        // Score
+        let response = Response::score(guess, answer);
+        knowledge.add(guess, response);
+        rules.add(guess, response);
+
+        // Filter remaining answers
+        answers_left.retain(|a| knowledge.is_possible(*a));
+        let count_left = answers_left.len();
+
+        if print { 
+            print!("{turn}) {guess}: {response} -> {count_left}");
+            if count_left < 10 { 
+                println!("     {:?}", answers_left); 
+            } else {
+                println!();
+            }
+        }
+
+        if guess == answer || answers_left.len() == 0 { break; }
     }
 
-    average_turns
+    if print { println!(); }
+    turn
 }
 
 /// Simulate a single game many times with the same strategy
-pub fn simulate_single(answers: &Vec<Word>, valid: &Vec<Word>, answer: Word, game_count: usize, strategy: &mut dyn FnMut(&Vec<Word>, usize, &Vec<Word>) -> Option<Word>) -> f64 {
+pub fn simulate_single(answers: &Vec<Word>, valid: &Vec<Word>, answer: Word, game_count: usize, strategy: &mut dyn FnMut(&Vec<Word>, usize, &Vec<Word>) -> Option<Word>, hard_mode: bool, rng: &mut StdRng) -> SimulationReport {
     // Play the fixed strategy part of the game once
     let mut from_turn = 1;
     let mut from_answers = answers.clone();
+    let mut rules = HardMode::new();
     
     while let Some(guess) = strategy(valid, from_turn, &from_answers) {
         //let _guess_text = guess.to_string();
 
+        // In hard mode, an illegal strategy guess means random guessing from here
+        if hard_mode && !rules.allows(guess) { break; }
+
         if guess == answer {
             from_answers.clear();
             break;
         } else {
             // Score guess against answer
             let response = Response::score(guess, answer);
+            rules.add(guess, response);
     
             // Filter remaining answers
             from_answers.retain(|a| Response::score(guess, *a) == response);
@@ -93,41 +366,42 @@ pub fn simulate_single(answers: &Vec<Word>, valid: &Vec<Word>, answer: Word, gam
         from_turn += 1;
     }
 
-    // If planned guesses fully solve this game, return the turn count
+    // If planned guesses fully solve this game, every game takes the same turns
+    let mut report = SimulationReport::new();
     if from_answers.len() == 0 {
-        return from_turn as f64;
+        for _game in 0..game_count {
+            report.add_game(answer, from_turn);
+        }
+        return report;
     }
 
-    let mut rng = rand::thread_rng();
-    let mut total_turns = 0;
-
     // Simulate the random part of each game
     for _game in 0..game_count {
         let mut turn: usize = from_turn - 1;
         let mut answers_left = from_answers.clone();
-        let mut constraint = Constraint::new();
+        let mut knowledge = Knowledge::new();
     
         loop {
             turn += 1;
 
             // Choose a guess
-            let guess = *answers_left.choose(&mut rng).unwrap();
+            let guess = *answers_left.choose(rng).unwrap();
             if guess == answer { break; }
             
             // Score
             let response = Response::score(guess, answer);
-            constraint.add(guess, response);
+            knowledge.add(guess, response);
     
             // Filter remaining answers
-            answers_left.retain(|a| constraint.matches(*a) && Response::score(guess, *a) == response);
+            answers_left.retain(|a| knowledge.is_possible(*a));
     
             if answers_left.len() == 0 { break; }
         }
 
-        total_turns += turn;
+        report.add_game(answer, turn);
     }
 
-    (total_turns as f64) / (game_count as f64)
+    report
 }
 
 pub fn get_strategy(name: &str) -> fn(&Vec<Word>, usize, &Vec<Word>) -> Option<Word> {
@@ -220,26 +494,147 @@ pub fn choose_best_predicted(guesses: &Vec<Word>, turn: usize, answers_left: &Ve
     choose_best(guesses, turn, answers_left, rank::total_turns_predicted_map)
 }
 
-pub fn assess_and_simulate(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, simulate_game_count: usize, mut player: TreePlayer) -> Result<String, String> {
-    let mut output = String::new();
+/// An assessed game: each turn, with the options considered for the next guess, and a simulation of the strategy for the same answer.
+pub struct Assessment {
+    pub answer: Word,
+    pub original_answer: bool,      // Whether the answer is on the answer list (if not, it was added for the assessment)
+    pub turns: Vec<AssessedTurn>,
+    pub simulation: SimulationReport,
+}
 
-    let guesses = guesses.ok_or("Must provide guesses")?;
-    let guesses = wv_safe(&guesses)?;
-    let answer = *guesses.last().ok_or("Must have one or more guesses")?;
-    let mut answers_to_use = answers.clone();
+/// One guess in an assessed game, and the options shown for the next guess.
+pub struct AssessedTurn {
+    pub guess: Word,
+    pub response: Response,
+    pub answers_left: usize,
+    pub options: Vec<AssessedOption>,
+}
 
-    if !answers.contains(&answer) {
-        answers_to_use.push(answer);
-        output += &format!("WARNING: {answer} isn't an original Wordle answer.\n\n");
+/// A possible next guess, with the expected total game turns if chosen and the clusters it leaves.
+pub struct AssessedOption {
+    pub kind: OptionKind,
+    pub guess: Word,
+    pub turns: f64,
+    pub cluster_vector: ClusterVector,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionKind {
+    InCluster,      // A remaining answer
+    BestInCluster,  // A remaining answer tied for best
+    BestValid,      // A valid word better than any remaining answer
+    Strategy,       // The strategy's next guess
+    Actual,         // The guess actually made next
+}
+
+impl OptionKind {
+    /// The mark shown before the option in the text form
+    pub fn mark(&self) -> &'static str {
+        match self {
+            OptionKind::InCluster => "",
+            OptionKind::BestInCluster => "*",
+            OptionKind::BestValid => "x",
+            OptionKind::Strategy => "s",
+            OptionKind::Actual => ">",
+        }
     }
 
-    let simulate_answers = vec![answer];
-    let turns = simulate(&answers_to_use, &simulate_answers, &Vec::new(), simulate_game_count, &mut |g, t, a| player.choose(g, t, a), false);
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptionKind::InCluster => "in_cluster",
+            OptionKind::BestInCluster => "best_in_cluster",
+            OptionKind::BestValid => "best_valid",
+            OptionKind::Strategy => "strategy",
+            OptionKind::Actual => "actual",
+        }
+    }
 
-    output += &assess(answer, guesses, valid, answers_to_use, &mut player);
+    pub fn from_name(name: &str) -> Option<OptionKind> {
+        [OptionKind::InCluster, OptionKind::BestInCluster, OptionKind::BestValid, OptionKind::Strategy, OptionKind::Actual].into_iter().find(|kind| kind.name() == name)
+    }
+
+    fn is_in_cluster(&self) -> bool {
+        matches!(self, OptionKind::InCluster | OptionKind::BestInCluster)
+    }
+}
+
+impl Assessment {
+    /// The assessment as JSON: {"answer", "original_answer", "turns": [{"guess", "response", "answers_left", "options": [{"kind", "guess", "turns", "cluster_vector"}]}], "simulation"}.
+    ///  Responses are written as knowns strings ("CRA.."), like Response identifiers in tree JSON.
+    pub fn to_json(&self) -> Json {
+        let turns = self.turns.iter().map(|turn| {
+            let options = turn.options.iter().map(|option| Json::object(vec![
+                ("kind", option.kind.name().into()),
+                ("guess", option.guess.to_string().into()),
+                ("turns", option.turns.into()),
+                ("cluster_vector", option.cluster_vector.to_json()),
+            ])).collect();
+
+            Json::object(vec![
+                ("guess", turn.guess.to_string().into()),
+                ("response", turn.response.to_knowns_string(&turn.guess).into()),
+                ("answers_left", turn.answers_left.into()),
+                ("options", Json::Array(options)),
+            ])
+        }).collect();
+
+        Json::object(vec![
+            ("answer", self.answer.to_string().into()),
+            ("original_answer", self.original_answer.into()),
+            ("turns", Json::Array(turns)),
+            ("simulation", self.simulation.to_json()),
+        ])
+    }
+
+    /// Read an assessment written by to_json.
+    pub fn from_json(json: &Json) -> Result<Assessment, String> {
+        let word = |json: &Json, key: &str| json.str_field(key).and_then(|text| Word::new(text).ok_or_else(|| format!("'{text}' is not a valid word")));
+
+        let mut turns = Vec::new();
+        for turn in json.array_field("turns")?.iter() {
+            let guess = word(turn, "guess")?;
+            let text = turn.str_field("response")?;
+            let response = match Response::from_knowns_str(text) {
+                Some(response) if response.to_knowns_string(&guess) == text.replace('_', ".") => response,
+                _ => return Err(format!("'{text}' is not a valid response to '{guess}'")),
+            };
+
+            let mut options = Vec::new();
+            for option in turn.array_field("options")?.iter() {
+                let kind = option.str_field("kind")?;
+                options.push(AssessedOption {
+                    kind: OptionKind::from_name(kind).ok_or_else(|| format!("Unknown option kind '{kind}'"))?,
+                    guess: word(option, "guess")?,
+                    turns: option.f64_field("turns")?,
+                    cluster_vector: ClusterVector::from_json(option.field("cluster_vector")?)?,
+                });
+            }
+
+            turns.push(AssessedTurn { guess, response, answers_left: turn.usize_field("answers_left")?, options });
+        }
+
+        Ok(Assessment {
+            answer: word(json, "answer")?,
+            original_answer: json.field("original_answer")?.as_bool().ok_or("'original_answer' must be true or false")?,
+            turns,
+            simulation: SimulationReport::from_json(json.field("simulation")?)?,
+        })
+    }
+}
+
+pub fn assess_and_simulate(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, simulate_game_count: usize, player: TreePlayer, matrix: Option<&ResponseMatrix>) -> Result<String, String> {
+    let assessment = assess_and_simulate_game(guesses, valid, answers, simulate_game_count, player, matrix)?;
+    let answer = assessment.answer;
+    let mut output = String::new();
+
+    if !assessment.original_answer {
+        output += &format!("WARNING: {answer} isn't an original Wordle answer.\n\n");
+    }
+
+    output += &turns_string(answer, &assessment.turns);
 
     output += "\n\n";
-    output += &format!("=> {turns:.3} avg turns ({answer} x{simulate_game_count})\n\n");
+    output += &format!("=> {:.3} avg turns ({answer} x{simulate_game_count})\n\n", assessment.simulation.average_turns());
     output += "* = best in-cluster guesses\n";
     output += "x = best out-of-cluster guess\n";
     output += "s = strategy guess\n";
@@ -248,6 +643,29 @@ pub fn assess_and_simulate(guesses: Option<&str>, valid: &Vec<Word>, answers: &V
     Ok(output)
 }
 
+/// assess_and_simulate, as JSON (see Assessment::to_json)
+pub fn assess_and_simulate_json(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, simulate_game_count: usize, player: TreePlayer, matrix: Option<&ResponseMatrix>) -> Result<Json, String> {
+    Ok(assess_and_simulate_game(guesses, valid, answers, simulate_game_count, player, matrix)?.to_json())
+}
+
+fn assess_and_simulate_game(guesses: Option<&str>, valid: &Vec<Word>, answers: &Vec<Word>, simulate_game_count: usize, mut player: TreePlayer, matrix: Option<&ResponseMatrix>) -> Result<Assessment, String> {
+    let guesses = guesses.ok_or("Must provide guesses")?;
+    let guesses = wv_safe(&guesses)?;
+    let answer = *guesses.last().ok_or("Must have one or more guesses")?;
+    let mut answers_to_use = answers.clone();
+
+    let original_answer = answers.contains(&answer);
+    if !original_answer {
+        answers_to_use.push(answer);
+    }
+
+    let simulate_answers = vec![answer];
+    let simulation = simulate(&answers_to_use, &simulate_answers, &Vec::new(), simulate_game_count, &mut |g, t, a| player.choose(g, t, a), false, None, &mut StdRng::from_entropy(), false);
+    let turns = assess_turns(answer, guesses, valid, answers_to_use, &mut player, matrix);
+
+    Ok(Assessment { answer, original_answer, turns, simulation })
+}
+
 /// Assess a Game.
 ///  PURPOSE: Show how play compared to optimal choices.
 ///   - Did I pivot to guessing at the right turn?
@@ -266,68 +684,68 @@ pub fn assess_and_simulate(guesses: Option<&str>, valid: &Vec<Word>, answers: &V
 ///  - Show best out-of-cluster guess if no in-cluster choices were optimal.
 ///  - Show next standard guess marked with 's'.
 ///  - Show actual next guess marked with '>'.
-pub fn assess(answer: Word, guesses: Vec<Word>, valid: &Vec<Word>, mut answers_left: Vec<Word>, player: &mut TreePlayer) -> String {
-    let mut result = String::new();
-    let mut turns = 0;
+/// 
+///  Responses are looked up in the ResponseMatrix, if provided.
+pub fn assess(answer: Word, guesses: Vec<Word>, valid: &Vec<Word>, answers_left: Vec<Word>, player: &mut TreePlayer, matrix: Option<&ResponseMatrix>) -> String {
+    turns_string(answer, &assess_turns(answer, guesses, valid, answers_left, player, matrix))
+}
 
-    result += &format!("=== {} ===", answer.to_string().to_ascii_uppercase());
+/// The turns of an assessed game, with the options 'assess' shows for each next guess, in the order shown.
+pub fn assess_turns(answer: Word, guesses: Vec<Word>, valid: &Vec<Word>, mut answers_left: Vec<Word>, player: &mut TreePlayer, matrix: Option<&ResponseMatrix>) -> Vec<AssessedTurn> {
+    let mut result = Vec::new();
+    let mut turns = 0;
 
     // Reset TreePlayer to start of game (it needs to know where in the tree to search)
     player.choose(&guesses, 1, &answers_left);
 
     for (i, guess) in guesses.iter().enumerate() {
-        let _g = guess.to_string();
         turns += 1;
         
-        // Score the guess
-        let response = Response::score(*guess, answer);
-
-        // Filter remaining answers
-        answers_left.retain(|a| Response::score(*guess, *a) == response);
-        let count_left = answers_left.len();
+        // Score the guess and filter remaining answers
+        let response;
+        if let Some(matrix) = matrix {
+            response = matrix.score(*guess, answer);
+            matrix.filter(*guess, response, &mut answers_left);
+        } else {
+            response = Response::score(*guess, answer);
+            answers_left.retain(|a| Response::score(*guess, *a) == response);
+        }
 
-        // Show turn #, guess, response, and answer count left
-        result += &format!("\n{turns}) {guess}: {response} -> {count_left}\n");
+        let mut turn = AssessedTurn { guess: *guess, response, answers_left: answers_left.len(), options: Vec::new() };
+        let mut add = |kind: OptionKind, (score, choice, cv): (f64, Word, ClusterVector)| {
+            turn.options.push(AssessedOption { kind, guess: choice, turns: (turns as f64) + 1.0 + score, cluster_vector: cv });
+        };
 
         // Stop here if we solved it
-        if *guess == answer { break; }
+        if *guess == answer {
+            result.push(turn);
+            break;
+        }
 
         // If there are few enough answers left, list and analyze them
         if let Some(next_guess) = guesses.get(i + 1) {
-            let mut need_newline = false;
             let strategy_next = player.choose(&guesses, turns + 1, &answers_left);
 
-            if count_left < 40 {
+            if answers_left.len() < 40 {
                 // Compute average turns remaining for each answer remaining answer (best last)
                 let ranked = rank_all_cluster(&answers_left, &answers_left);
                 let best_score = ranked.last().unwrap().0;
 
-                // Since we're showing in-cluster options, make sure to add a newline before other choices
-                need_newline = true;
-
-                // Write all in-cluster words with expected total game turns if chosen.
-                for (score, choice, cv) in ranked.iter() {
-                    let mut mark = String::new();
-                    if *score == best_score { mark.push('*'); }
-                    result += &format!("{:>5} {}  {:.2}  {}\n", mark, choice, (turns as f64) + 1.0 + score, cv.to_string());
+                // Add all in-cluster words with expected total game turns if chosen.
+                for option in ranked.into_iter() {
+                    let kind = if option.0 == best_score { OptionKind::BestInCluster } else { OptionKind::InCluster };
+                    add(kind, option);
                 }
 
                 // If no in-cluster guess is ideal, also show the best possible guess from all valid words
                 // (An ideal in-cluster guess will have a "turns left" under 1.0; zero when it's the answer and one for everything else.)
                 // (An ideal out-of-cluster guess has a best turns left of 1.0, so if the in-cluster best is one, out-of-cluster won't be better.)
                 if best_score > 1.0 {
-                    // Find one tie for best valid word.
-                    // let (score, choice, cv) = best(valid, &answers_left);
-                    // if score < best_score {
-                    //     if need_newline { need_newline = false; result += "\n"; }
-                    //     result += &format!("{:>5} {}  {:.2}  {}\n", "x", choice, (turns as f64) + 1.0 + score, &cv.to_string());
-                    // }
-
                     // Show all ties for best valid word
                     let valid_scored = rank_all_cluster(valid, &answers_left);
                     let mut best_valid_score = None;
 
-                    for option in valid_scored.iter().rev() {
+                    for option in valid_scored.into_iter().rev() {
                         if let Some(score) = best_valid_score {
                             if option.0 - score > 0.01 { break; }
                         } else {
@@ -335,23 +753,41 @@ pub fn assess(answer: Word, guesses: Vec<Word>, valid: &Vec<Word>, mut answers_l
                             if option.0 >= best_score { break; }
                         }
 
-                        if need_newline { need_newline = false; result += "\n"; }
-                        result += &format!("{:>5} {}  {:.2}  {}\n", "x", option.1, (turns as f64) + 1.0 + option.0, &option.2.to_string());
+                        add(OptionKind::BestValid, option);
                     }
                 }
             }
 
             // Show the outcome for the next strategy guess, if there is one
             if let Some(next_standard) = strategy_next {
-                if need_newline { need_newline = false; result += "\n"; }
-                let (score, choice, cv) = rank_cluster(next_standard, &answers_left);
-                result += &format!("{:>5} {}  {:.2}  {}\n", "s", choice, (turns as f64) + 1.0 + score, cv.to_string());
+                add(OptionKind::Strategy, rank_cluster(next_standard, &answers_left));
             }
 
             // Show the outcome for the actual next guess made
-            if need_newline { /*need_newline = false;*/ result += "\n"; }
-            let (score, choice, cv) = rank_cluster(*next_guess, &answers_left);
-            result += &format!("{:>5} {}  {:.2}  {}\n", ">", choice, (turns as f64) + 1.0 + score, cv.to_string());
+            add(OptionKind::Actual, rank_cluster(*next_guess, &answers_left));
+        }
+
+        result.push(turn);
+    }
+
+    result
+}
+
+fn turns_string(answer: Word, turns: &[AssessedTurn]) -> String {
+    let mut result = String::new();
+    result += &format!("=== {} ===", answer.to_string().to_ascii_uppercase());
+
+    for (i, turn) in turns.iter().enumerate() {
+        // Show turn #, guess, response, and answer count left
+        result += &format!("\n{}) {}: {} -> {}\n", i + 1, turn.guess, turn.response, turn.answers_left);
+
+        // Add an empty line between in-cluster options and the others
+        let mut last_in_cluster = false;
+        for option in turn.options.iter() {
+            if last_in_cluster && !option.kind.is_in_cluster() { result += "\n"; }
+            last_in_cluster = option.kind.is_in_cluster();
+
+            result += &format!("{:>5} {}  {:.2}  {}\n", option.kind.mark(), option.guess, option.turns, option.cluster_vector.to_string());
         }
     }
 
@@ -371,23 +807,17 @@ fn rank_cluster(guess: Word, cluster: &Vec<Word>) -> (f64, Word, ClusterVector)
 }
 
 fn rank_all_cluster(guess_options: &Vec<Word>, answers: &Vec<Word>) -> Vec<(f64, Word, ClusterVector)> {
-    let mut result;
+    let mut result = Vec::new();
 
-    if answers.len() > 64 {
-        result = answers.iter().map(|a| rank_cluster(*a, &answers)).collect::<Vec<_>>();
-    } else {
-        result = Vec::new();
-
-        let clubs = Clubs::new(answers, answers);
-        let within = clubs.all_vector();
-        let mut cache = HashMap::new();
+    let clubs = Clubs::new(answers, answers);
+    let within = clubs.all_vector();
+    let mut cache = HashMap::new();
 
-        for guess in guess_options {
-            let total_turns = clubs.count_random_turns_after_cache(within, *guess, &mut cache);
-            let average_turns = ((total_turns as f64) / (answers.len() as f64)) - 1.0;
-            let cv = clubs.cluster_vector(within, *guess);
-            result.push((average_turns, *guess, cv));
-        }
+    for guess in guess_options {
+        let total_turns = clubs.count_random_turns_after_cache(&within, *guess, &mut cache);
+        let average_turns = ((total_turns as f64) / (answers.len() as f64)) - 1.0;
+        let cv = clubs.cluster_vector(&within, *guess);
+        result.push((average_turns, *guess, cv));
     }
 
     result.sort_by(|l, r| r.0.total_cmp(&l.0));
@@ -400,7 +830,84 @@ fn rank_all_cluster(guess_options: &Vec<Word>, answers: &Vec<Word>) -> Vec<(f64,
 
 #[cfg(test)]
 mod tests {
-    use crate::{word::Word, wordle_tree};
+    use rand::{SeedableRng, rngs::StdRng};
+    use crate::{word::Word, wordle_tree::{self, WordleTree, WordleTreeToStringOptions}, objective::Objective, weights::AnswerWeights, json::Json, wv};
+
+    #[test]
+    fn simulate_weighted() {
+        let answers = vec![w("crane"), w("crack"), w("crash"), w("crost")];
+
+        // Always guessing CRANE first: only CRANE games take one turn
+        let weights = AnswerWeights::parse("crane\t1\ncrack\t0", &answers).unwrap();
+        let report = super::simulate(&answers, &answers, &Vec::new(), 100, &mut |_, t, _| if t == 1 { Some(w("crane")) } else { None }, false, Some(&weights), &mut StdRng::seed_from_u64(1), false);
+        assert_eq!(report.average_turns(), 1.0);
+
+        // Unweighted, every answer is played
+        let report = super::simulate(&answers, &answers, &Vec::new(), 100, &mut |_, t, _| if t == 1 { Some(w("crane")) } else { None }, false, None, &mut StdRng::seed_from_u64(1), false);
+        assert!(report.average_turns() > 1.0);
+    }
+
+    #[test]
+    fn simulate_seeded() {
+        let answers = wv("dowdy, fatal, tally, waltz, bobby, boxer, fever, wafer, waver");
+        let tree = WordleTree::parse("(*, 9) -> parse\n    (*, 9) -> bobby".lines()).unwrap();
+        let mut player = wordle_tree::tree_player::TreePlayer::new(&tree);
+
+        // The same seed gives the same games
+        let first = super::simulate(&answers, &answers, &Vec::new(), 500, &mut |g, t, a| player.choose(g, t, a), false, None, &mut StdRng::seed_from_u64(13), false);
+        let second = super::simulate(&answers, &answers, &Vec::new(), 500, &mut |g, t, a| player.choose(g, t, a), false, None, &mut StdRng::seed_from_u64(13), false);
+        assert_eq!(first.to_string(), second.to_string());
+
+        // Split across threads, the results (and turns through each node) are the same for any thread count
+        let mut options = WordleTreeToStringOptions::default();
+        options.show_turn_histogram = true;
+
+        let (one, mut one_player) = super::simulate_tree(&tree, &answers, &answers, 25_000, false, None, &super::SimulationRun { threads: 1, ..super::SimulationRun::seeded(13) });
+        let (three, mut three_player) = super::simulate_tree(&tree, &answers, &answers, 25_000, false, None, &super::SimulationRun { threads: 3, ..super::SimulationRun::seeded(13) });
+        assert_eq!(one.game_count, 25_000);
+        assert_eq!(one.to_string(), three.to_string());
+        assert_eq!(one_player.to_string(answers.len(), &options), three_player.to_string(answers.len(), &options));
+
+        // A different seed gives different random guesses
+        let (other, _) = super::simulate_tree(&tree, &answers, &answers, 25_000, false, None, &super::SimulationRun::seeded(14));
+        assert_ne!(one.to_string(), other.to_string());
+    }
+
+    #[test]
+    fn simulation_report() {
+        let mut report = super::SimulationReport::new();
+        report.add_game(w("crane"), 2);
+        report.add_game(w("crack"), 4);
+        report.add_game(w("crack"), 8);
+        report.add_game(w("crash"), 4);
+
+        assert_eq!(report.game_count, 4);
+        assert_eq!(report.histogram_string(), "[0, 1, 0, 2, 0, 0; 1 lost]");
+        assert_eq!(report.average_turns(), 4.5);
+        assert_eq!(report.failure_rate(), 0.25);
+        assert_eq!(format!("{:.4}", report.standard_error()), "1.2583");
+        assert_eq!(report.worst_answers(2), vec![(w("crack"), 6.0), (w("crash"), 4.0)]);
+
+        // Reports combine (for separately simulated games)
+        let mut other = super::SimulationReport::new();
+        other.add_turns(1);
+        other.add_report(&report);
+        assert_eq!(other.histogram_string(), "[1, 1, 0, 2, 0, 0; 1 lost]");
+        assert_eq!(other.average_turns(), 19.0 / 5.0);
+        assert_eq!(other.worst_answers(1), vec![(w("crack"), 6.0)]);
+
+        assert_eq!(report.to_string(), "Solved on each turn: [0, 1, 0, 2, 0, 0; 1 lost]\n4.5000 ± 1.2583 avg turns over 4 games; 25.00% lost\nWorst answers: crack 6.00, crash 4.00, crane 2.00");
+
+        // JSON keeps every count, so reports read back the same
+        let json = report.to_json();
+        assert_eq!(json.get("by_answer").unwrap().to_string(), r#"{"crack":[12,2],"crane":[2,1],"crash":[4,1]}"#);
+        let read = super::SimulationReport::from_json(&Json::parse(&json.to_pretty_string()).unwrap()).unwrap();
+        assert_eq!(read.to_string(), report.to_string());
+        assert_eq!(read.to_json(), json);
+        assert_eq!(super::SimulationReport::from_json(&Json::parse(r#"{"game_count": 1, "total_turns": 1, "total_turns_squared": 1, "histogram": [1]}"#).unwrap()).err().unwrap(), "'histogram' must have 7 counts");
+        assert_eq!(super::SimulationReport::from_json(&Json::parse(r#"{"game_count": 1, "total_turns": 1, "total_turns_squared": 1, "histogram": [1, 0, 0, 0, 0, 0, 0], "by_answer": [1, 1]}"#).unwrap()).err().unwrap(),
+            "'by_answer' must be an object of answers to [total turns, games]");
+    }
 
     #[test]
     fn rank_cluster() {
@@ -428,13 +935,13 @@ mod tests {
         let valid = answers.clone();
 
         let standard = vec![w("crane"), w("spilt"), w("dumbo")];
-        let tree = wordle_tree::builders::build("standard", &answers, &standard);
+        let tree = wordle_tree::builders::build("standard", &answers, &[], &standard, false, Objective::TotalTurns, None).unwrap();
         let mut player = wordle_tree::tree_player::TreePlayer::new(&tree);
 
         let mut result;
 
         // One possible, found on first try: Heading and victory turn
-        result = super::assess(w("crane"), vec![w("crane")], &valid, answers.clone(), &mut player);
+        result = super::assess(w("crane"), vec![w("crane")], &valid, answers.clone(), &mut player, None);
         assert_eq!(result, 
 "=== CRANE ===
 1) crane: 🟩🟩🟩🟩🟩 -> 1
@@ -444,7 +951,7 @@ mod tests {
         //  - Show each option with expected turns (2 or 3 -> 2.50)
         //  - If next guess is standard guess, show only once as '>'
         //  - If next guess is not standard guess, show both (turn two)
-        result = super::assess(w("crash"), vec![w("crane"), w("spilt"), w("crash")], &valid, answers.clone(), &mut player);
+        result = super::assess(w("crash"), vec![w("crane"), w("spilt"), w("crash")], &valid, answers.clone(), &mut player, None);
         assert_eq!(result,
 "=== CRASH ===
 1) crane: 🟩🟩🟩⬛⬛ -> 2
@@ -466,7 +973,7 @@ mod tests {
         // Better out-of-cluster guess
         //  - Show higher expected from any in-cluster guess
         //  - Show out-of-cluster guess with 'x'
-        result = super::assess(w("yyyyy"), vec![w("crane"), w("spilt"), w("waxys"), w("yyyyy")], &vec![w("waxys")], vec![w("wwwww"), w("xxxxx"), w("yyyyy"), w("zzzzz")], &mut player);
+        result = super::assess(w("yyyyy"), vec![w("crane"), w("spilt"), w("waxys"), w("yyyyy")], &vec![w("waxys")], vec![w("wwwww"), w("xxxxx"), w("yyyyy"), w("zzzzz")], &mut player, None);
         assert_eq!(result,
 "=== YYYYY ===
 1) crane: ⬛⬛⬛⬛⬛ -> 4
@@ -499,6 +1006,34 @@ mod tests {
 
     }
 
+    #[test]
+    fn assess_json() {
+        let answers = vec![w("crane"), w("crack"), w("crash"), w("crost"), w("crunk"), w("dowry"), w("sheck")];
+        let standard = vec![w("crane"), w("spilt"), w("dumbo")];
+        let tree = wordle_tree::builders::build("standard", &answers, &[], &standard, false, Objective::TotalTurns, None).unwrap();
+        let player = wordle_tree::tree_player::TreePlayer::new(&tree);
+
+        // The same turns and options as the text form, with full cluster vectors and the simulation report
+        let json = super::assess_and_simulate_json(Some("crane, spilt, crash"), &answers, &answers, 10, player, None).unwrap();
+        assert_eq!(json.str_field("answer").unwrap(), "crash");
+        assert_eq!(json.get("original_answer"), Some(&Json::Bool(true)));
+        assert_eq!(json.get("simulation").unwrap().usize_field("game_count").unwrap(), 10);
+
+        let turns = json.array_field("turns").unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[0].to_string(), r#"{"guess":"crane","response":"CRA..","answers_left":2,"options":[{"kind":"best_in_cluster","guess":"crack","turns":2.5,"cluster_vector":[1]},{"kind":"best_in_cluster","guess":"crash","turns":2.5,"cluster_vector":[1]},{"kind":"strategy","guess":"spilt","turns":3,"cluster_vector":[2]},{"kind":"actual","guess":"spilt","turns":3,"cluster_vector":[2]}]}"#);
+        assert_eq!(turns[2].to_string(), r#"{"guess":"crash","response":"CRASH","answers_left":1,"options":[]}"#);
+
+        // Assessments read back the same
+        let read = super::Assessment::from_json(&Json::parse(&json.to_pretty_string()).unwrap()).unwrap();
+        assert_eq!(read.turns.len(), 3);
+        assert_eq!(read.turns[0].options[0].kind, super::OptionKind::BestInCluster);
+        assert_eq!(read.to_json(), json);
+
+        let unknown = Json::parse(&json.to_string().replace("best_valid", "worst").replace("best_in_cluster", "worst")).unwrap();
+        assert_eq!(super::Assessment::from_json(&unknown).err().unwrap(), "Unknown option kind 'worst'");
+    }
+
     fn w(text: &str) -> Word {
         Word::new(text).unwrap()
     }
@@ -1,43 +1,41 @@
-use std::collections::HashMap;
-use crate::{bit_vector_slice::BitVectorSlice, word::Word, response::{Response, self}, cluster_vector::ClusterVector, wordle_tree::{WordleTree, WordleTreeIdentifier, WordleGuess, self}};
+use std::{cmp::Ordering, collections::HashMap};
+use crate::{bit_vector::BitVector, word::Word, response::{Response, self}, cluster_vector::ClusterVector, wordle_tree::{WordleTree, WordleTreeIdentifier, WordleGuess, self}, hard_mode::HardMode, objective::{Objective, TurnCounts}, weights::AnswerWeights};
 
 pub struct Clubs<'a> {
     letters: [LetterClubs; 26],
     answers: &'a Vec<Word>,
     answer_count: usize,
 
-    valid: &'a Vec<Word>
-}
+    valid: &'a Vec<Word>,
+    hard_mode: bool,
+    rules_before: HardMode,
 
-#[derive(Clone, Copy)]
-struct LetterClubs {
-    pub any: BitVectorSlice,
-    pub pos: [BitVectorSlice; 5],
+    objective: Objective,
+    turns_before: usize,
+
+    weights: Option<&'a AnswerWeights>
 }
 
-impl LetterClubs {
-    pub fn new() -> LetterClubs {
-        LetterClubs {
-            any: BitVectorSlice::new(),
-            pos: [BitVectorSlice::new(); 5],
-        }
-    }
+#[derive(Clone, Default)]
+struct LetterClubs {
+    pub any: BitVector,
+    pub pos: [BitVector; 5],
 }
 
 /// Container for state during best_next searches
 struct BestConsiderState<'a> {
-    pub within: BitVectorSlice, 
+    pub within: &'a BitVector, 
 
     pub best: (Word, usize),
     pub was_worse: bool,
     pub ideal_turns: usize,
 
     pub clubs: &'a Clubs<'a>,
-    pub choices: &'a mut HashMap<BitVectorSlice, (Word, usize)>
+    pub choices: &'a mut HashMap<BitVector, (Word, usize)>
 }
 
 impl BestConsiderState<'_> {
-    fn new<'a>(within: BitVectorSlice, clubs: &'a Clubs, choices: &'a mut HashMap<BitVectorSlice, (Word, usize)>) -> BestConsiderState<'a> {
+    fn new<'a>(within: &'a BitVector, clubs: &'a Clubs, choices: &'a mut HashMap<BitVector, (Word, usize)>) -> BestConsiderState<'a> {
         // The initial best is the first word; find turns for it
         let first_word = clubs.answers[within.iter().next().unwrap()];
         let first_turns = clubs.count_best_turns_after(within, first_word, choices);
@@ -80,13 +78,18 @@ impl BestConsiderState<'_> {
     }
 }
 
-impl Clubs<'_> {
-    pub fn new<'a>(answers: &'a Vec<Word>, valid: &'a Vec<Word>) -> Clubs<'a> {
+impl<'a> Clubs<'a> {
+    pub fn new(answers: &'a Vec<Word>, valid: &'a Vec<Word>) -> Clubs<'a> {
         let mut result = Clubs {
-            letters: [LetterClubs::new(); 26],
+            letters: std::array::from_fn(|_| LetterClubs::default()),
             answer_count: answers.len(),
             answers,
-            valid: valid
+            valid: valid,
+            hard_mode: false,
+            rules_before: HardMode::new(),
+            objective: Objective::TotalTurns,
+            turns_before: 0,
+            weights: None
         };
 
         // Add each Word to the club for each letter+position
@@ -98,27 +101,52 @@ impl Clubs<'_> {
 
         // Fill out the 'any' club per letter to the OR of all positions for the letter
         for club in result.letters.iter_mut() {
+            let mut any = BitVector::new();
             for pos in club.pos.iter() {
-                club.any.union_with(pos);
+                any.union_with(pos);
             }
+            club.any = any;
         }
 
         result
     }
 
+    /// Only consider out-of-cluster guesses which are legal in hard mode.
+    ///  Pass the guesses made before reaching these answers, since their hints must be used too.
+    pub fn set_hard_mode(&mut self, hard_mode: bool, guesses_before: &[Word]) {
+        self.hard_mode = hard_mode;
+        self.rules_before = match self.answers.first() {
+            Some(answer) if hard_mode => HardMode::from_guesses(guesses_before, *answer),
+            _ => HardMode::new()
+        };
+    }
+
+    /// Choose guesses to optimize a different objective than total turns.
+    ///  Pass how many guesses were made before reaching these answers, since objectives like failures depend on the turns left.
+    pub fn set_objective(&mut self, objective: Objective, turns_before: usize) {
+        self.objective = objective;
+        self.turns_before = turns_before;
+    }
+
+    /// Count answers by how likely they are rather than equally.
+    ///  Turn totals become weighted totals (still near the answer count, since weights average one).
+    pub fn set_weights(&mut self, weights: &'a AnswerWeights) {
+        self.weights = Some(weights);
+    }
+
     /// Shortcut to get a vector including all answers in this Clubs instance.
     ///  All search methods take a slice to search within, so that all subsets can also be evaluated easily.
-    pub fn all_vector(&self) -> BitVectorSlice {
-        BitVectorSlice::new_all(self.answer_count)
+    pub fn all_vector(&self) -> BitVector {
+        BitVector::new_all(self.answer_count)
     }
 
     /// Convert a vector to the set of answers included within it
-    pub fn cluster_to_words(&self, cluster: BitVectorSlice) -> Vec<Word> {
-        cluster.iter().map(|index| self.answers[index].clone()).collect::<Vec<Word>>()
+    pub fn cluster_to_words(&self, cluster: &BitVector) -> Vec<Word> {
+        cluster.iter().map(|index| self.answers[index]).collect::<Vec<Word>>()
     }
 
     /// Write a short string 
-    pub fn vector_to_string(&self, within: BitVectorSlice) -> String {
+    pub fn vector_to_string(&self, within: &BitVector) -> String {
         let cluster = within.iter().map(|index| self.answers[index].to_string()).collect::<Vec<String>>();
         if within.count() as usize == self.answer_count {
             "[*]".into()
@@ -129,7 +157,7 @@ impl Clubs<'_> {
 
     /// Return the set of clusters after making a specific guess.
     ///  Call cluster_to_words to convert the answer set to the Words when needed.
-    pub fn split(&self, guess: Word, within: BitVectorSlice) -> Vec<(Response, BitVectorSlice)> {
+    pub fn split(&self, guess: Word, within: &BitVector) -> Vec<(Response, BitVector)> {
         let mut clusters = Vec::new();
 
         self.for_each_cluster(within, guess, &mut |response, subcluster| {
@@ -140,7 +168,7 @@ impl Clubs<'_> {
     }
 
     /// Show all words in a cluster and the ideal turns and cluster vector for each
-    pub fn print_in_cluster(&self, within: BitVectorSlice) -> String {
+    pub fn print_in_cluster(&self, within: &BitVector) -> String {
         let mut options = Vec::new();
 
         for index in within.iter() {
@@ -159,43 +187,47 @@ impl Clubs<'_> {
     }
 
     /// Return the best next guess, or None if it doesn't matter, and the total number of turns left to solve each answer in 'within' with that guess.
-    pub fn best_next_guess(&self, within: BitVectorSlice) -> (Option<Word>, usize) {
+    pub fn best_next_guess(&self, within: &BitVector) -> (Option<Word>, usize) {
         let mut choices = HashMap::new();
         let best_turns = self.count_best_turns(within, &mut choices);
         
-        if let Some(best) = choices.get(&within) {
+        if let Some(best) = choices.get(within) {
             (Some(best.0), best.1)
         } else {
             (None, best_turns)
         }
     }
 
-    pub fn count_best_turns_after(&self, within: BitVectorSlice, next_guess: Word, choices: &mut HashMap<BitVectorSlice, (Word, usize)>) -> usize {
+    pub fn count_best_turns_after(&self, within: &BitVector, next_guess: Word, choices: &mut HashMap<BitVector, (Word, usize)>) -> usize {
         // One per answer left for next_guess itself being guessed
         let outer_count = within.count() as usize;
         let mut best_turns = outer_count;
 
         // Plus the remaining turns per sub-cluster
         self.for_each_cluster(within, next_guess, &mut |_, subcluster| {
-            if subcluster == within {
+            if subcluster == *within {
                 // Don't recurse if the guess leaves all words in the same subcluster
                 best_turns += outer_count * outer_count;
             } else {
-                best_turns += self.count_best_turns(subcluster, choices);
+                best_turns += self.count_best_turns(&subcluster, choices);
             }
         });
 
         best_turns
     }
 
-    pub fn count_best_turns(&self, within: BitVectorSlice, choices: &mut HashMap<BitVectorSlice, (Word, usize)>) -> usize {
+    pub fn count_best_turns(&self, within: &BitVector, choices: &mut HashMap<BitVector, (Word, usize)>) -> usize {
+        if self.objective != Objective::TotalTurns || self.weights.is_some() || self.hard_mode {
+            return self.count_best_turns_for_objective(within, choices);
+        }
+
         let outer_count = within.count();
         if outer_count < 3 {
             // For one or two words, random guessing is the best outcome
             return ((outer_count * 2) - 1) as usize;
         } else {
             // If we've solved this subcluster before, return the previous answer
-            if let Some(cached_best) = choices.get(&within) {
+            if let Some(cached_best) = choices.get(within) {
                 return cached_best.1;
             }
 
@@ -222,20 +254,153 @@ impl Clubs<'_> {
             }
 
             if state.was_worse {
-                state.choices.insert(within, state.best);
+                state.choices.insert(within.clone(), state.best);
             }
 
             return state.best.1;
         }
     }
 
-    pub fn best_strategy(&self, within: BitVectorSlice, choices: &HashMap<BitVectorSlice, (Word, usize)>, show_all: bool, parent: &mut WordleTree) {
+    /// Find the best strategy for the objective, record the guess for every cluster of three or more in 'choices', and return its total turns.
+    ///  Best choices can depend on the turns taken to reach a cluster and the hard mode rules revealed on the way,
+    ///  so they are searched per (cluster, turns before, rules). Weighted answers are also searched here, as fractional answer counts.
+    fn count_best_turns_for_objective(&self, within: &BitVector, choices: &mut HashMap<BitVector, (Word, usize)>) -> usize {
+        let mut cache = HashMap::new();
+        let counts = self.best_turn_counts(within, self.turns_before, self.rules_before, &mut cache);
+        self.add_choices(within, self.turns_before, self.rules_before, &cache, choices);
+
+        (counts.total_turns() - self.turns_before as f64 * self.answer_weight(within)).round() as usize
+    }
+
+    fn best_turn_counts(&self, within: &BitVector, turns_before: usize, rules: HardMode, cache: &mut HashMap<(BitVector, usize, HardMode), (Word, TurnCounts)>) -> TurnCounts {
+        let outer_count = within.count() as usize;
+        if outer_count < 3 {
+            // For one or two words, guessing the likelier first is the best outcome
+            return self.ideal_counts(within, turns_before);
+        }
+
+        let key = (within.clone(), turns_before, rules);
+        if let Some((_, counts)) = cache.get(&key) {
+            return counts.clone();
+        }
+
+        // The initial best is the first word
+        let first_word = self.answers[within.iter().next().unwrap()];
+        let mut best = (first_word, self.turn_counts_after(within, first_word, turns_before, rules, cache).unwrap());
+
+        // Consider each in-cluster guess, stopping if one splits every other answer apart
+        let ideal = self.ideal_counts(within, turns_before);
+        for guess in within.iter().skip(1).map(|index| self.answers[index]) {
+            if self.objective.compare(&best.1, &ideal) != Ordering::Greater { break; }
+            self.consider_for_objective(within, guess, turns_before, rules, &mut best, cache);
+        }
+
+        // If an out-of-cluster choice could be better (every answer solved the turn after it), consider them
+        let mut out_of_cluster_ideal = TurnCounts::new();
+        out_of_cluster_ideal.add(turns_before + 2, self.answer_weight(within));
+
+        if self.objective.compare(&out_of_cluster_ideal, &best.1) == Ordering::Less {
+            for guess in self.valid {
+                if self.hard_mode && !rules.allows(*guess) { continue; }
+
+                self.consider_for_objective(within, *guess, turns_before, rules, &mut best, cache);
+
+                if self.objective.compare(&best.1, &out_of_cluster_ideal) != Ordering::Greater { break; }
+            }
+        }
+
+        let counts = best.1.clone();
+        cache.insert(key, best);
+        counts
+    }
+
+    fn consider_for_objective(&self, within: &BitVector, guess: Word, turns_before: usize, rules: HardMode, best: &mut (Word, TurnCounts), cache: &mut HashMap<(BitVector, usize, HardMode), (Word, TurnCounts)>) {
+        // Skip finding actual turns if the ideal outcome for this guess isn't better than the best so far
+        let mut ideal = TurnCounts::new();
+        let mut left_weight = 0.0;
+        self.for_each_cluster(within, guess, &mut |_, cluster| {
+            left_weight += self.answer_weight(&cluster);
+            ideal.add_counts(&self.ideal_counts(&cluster, turns_before + 1));
+        });
+        ideal.add(turns_before + 1, self.answer_weight(within) - left_weight);
+
+        if self.objective.compare(&ideal, &best.1) != Ordering::Less { return; }
+
+        if let Some(counts) = self.turn_counts_after(within, guess, turns_before, rules, cache) {
+            if self.objective.compare(&counts, &best.1) == Ordering::Less {
+                *best = (guess, counts);
+            }
+        }
+    }
+
+    /// Return the answers solved on each turn with the best play after a guess, or None if the guess doesn't split the cluster.
+    fn turn_counts_after(&self, within: &BitVector, guess: Word, turns_before: usize, rules: HardMode, cache: &mut HashMap<(BitVector, usize, HardMode), (Word, TurnCounts)>) -> Option<TurnCounts> {
+        let mut result = TurnCounts::new();
+        let mut left_weight = 0.0;
+        let mut splits = true;
+
+        self.for_each_cluster(within, guess, &mut |response, subcluster| {
+            if subcluster == *within {
+                splits = false;
+            } else if splits {
+                left_weight += self.answer_weight(&subcluster);
+                result.add_counts(&self.best_turn_counts(&subcluster, turns_before + 1, self.rules_after(rules, guess, response), cache));
+            }
+        });
+
+        // Plus the guess itself, if it was in the cluster
+        result.add(turns_before + 1, self.answer_weight(within) - left_weight);
+
+        if splits { Some(result) } else { None }
+    }
+
+    fn add_choices(&self, within: &BitVector, turns_before: usize, rules: HardMode, cache: &HashMap<(BitVector, usize, HardMode), (Word, TurnCounts)>, choices: &mut HashMap<BitVector, (Word, usize)>) {
+        if let Some((guess, counts)) = cache.get(&(within.clone(), turns_before, rules)) {
+            let turns = (counts.total_turns() - turns_before as f64 * self.answer_weight(within)).round() as usize;
+            choices.insert(within.clone(), (*guess, turns));
+
+            self.for_each_cluster(within, *guess, &mut |response, subcluster| {
+                self.add_choices(&subcluster, turns_before + 1, self.rules_after(rules, *guess, response), cache, choices);
+            });
+        }
+    }
+
+    /// The hard mode rules after a guess, or no rules outside hard mode (so clusters aren't searched again per path)
+    fn rules_after(&self, rules: HardMode, guess: Word, response: Response) -> HardMode {
+        let mut result = rules;
+        if self.hard_mode { result.add(guess, response); }
+        result
+    }
+
+    /// The number of answers in a cluster, or their total weight if answers aren't equally likely
+    fn answer_weight(&self, within: &BitVector) -> f64 {
+        match self.weights {
+            Some(weights) => within.iter().map(|index| weights.weight(self.answers[index])).sum(),
+            None => within.count() as f64
+        }
+    }
+
+    /// The ideal outcome for a cluster: the likeliest answer guessed next and every other answer the turn after
+    fn ideal_counts(&self, within: &BitVector, turns_before: usize) -> TurnCounts {
+        match self.weights {
+            Some(weights) => {
+                let heaviest = within.iter().map(|index| weights.weight(self.answers[index])).fold(0.0, f64::max);
+                let mut result = TurnCounts::new();
+                result.add(turns_before + 1, heaviest);
+                result.add(turns_before + 2, self.answer_weight(within) - heaviest);
+                result
+            },
+            None => TurnCounts::ideal(within.count() as usize, turns_before)
+        }
+    }
+
+    pub fn best_strategy(&self, within: &BitVector, choices: &HashMap<BitVector, (Word, usize)>, show_all: bool, parent: &mut WordleTree) {
         let cluster_count = within.count() as usize;
         if cluster_count < 3 && show_all == false { return; }
 
         let first_word = within.iter().next().map(|index| self.answers[index]).unwrap();
 
-        if let Some((best, turns)) = choices.get(&within) {
+        if let Some((best, turns)) = choices.get(within) {
             let mut node = WordleTree::new(WordleTreeIdentifier::Cluster(first_word), WordleGuess::Specific(*best));
             node.outer_total_turns = *turns as f64;
             node.answer_count = cluster_count;
@@ -245,7 +410,7 @@ impl Clubs<'_> {
             }
 
             self.for_each_cluster(within, *best, &mut |_, subcluster| {
-                self.best_strategy(subcluster, choices, show_all, &mut node);
+                self.best_strategy(&subcluster, choices, show_all, &mut node);
             });
 
             parent.add_child_without_rollup(node);
@@ -258,7 +423,7 @@ impl Clubs<'_> {
             }
 
             self.for_each_cluster(within, first_word, &mut |_, subcluster| {
-                self.best_strategy(subcluster, choices, show_all, &mut node);
+                self.best_strategy(&subcluster, choices, show_all, &mut node);
             });
 
             if node.has_children() {
@@ -269,7 +434,7 @@ impl Clubs<'_> {
         }
     }
 
-    pub fn count_random_turns(&self, within: BitVectorSlice) -> f64 {
+    pub fn count_random_turns(&self, within: &BitVector) -> f64 {
         let outer_count = within.count();
         if outer_count < 3 {
             return ((outer_count * 2) - 1) as f64;
@@ -280,7 +445,7 @@ impl Clubs<'_> {
                 let guess = self.answers[index];
 
                 self.for_each_cluster(within, guess, &mut |_, subcluster| {
-                    inner_total += self.count_random_turns(subcluster);
+                    inner_total += self.count_random_turns(&subcluster);
                 });
             }
 
@@ -288,19 +453,19 @@ impl Clubs<'_> {
         }
     }
 
-    pub fn count_random_turns_after(&self, within: BitVectorSlice, next_guess: Word) -> f64 {
+    pub fn count_random_turns_after(&self, within: &BitVector, next_guess: Word) -> f64 {
         // One per answer left for next_guess itself being guessed
         let mut best_turns = within.count() as f64;
 
         // Plus the remaining turns per sub-cluster
         self.for_each_cluster(within, next_guess, &mut |_, subcluster| {
-            best_turns += self.count_random_turns(subcluster);
+            best_turns += self.count_random_turns(&subcluster);
         });
 
         best_turns
     }
 
-    pub fn count_random_turns_after_cache(&self, within: BitVectorSlice, next_guess: Word, cache: &mut HashMap<BitVectorSlice, f64>) -> f64 {
+    pub fn count_random_turns_after_cache(&self, within: &BitVector, next_guess: Word, cache: &mut HashMap<BitVector, f64>) -> f64 {
         // One per answer left for next_guess itself being guessed
         let mut best_turns = within.count() as f64;
 
@@ -309,7 +474,7 @@ impl Clubs<'_> {
             if let Some(inner_turns) = cache.get(&subcluster) {
                 best_turns += inner_turns;
             } else {
-                let inner_turns = self.count_random_turns(subcluster);
+                let inner_turns = self.count_random_turns(&subcluster);
                 cache.insert(subcluster, inner_turns);
                 best_turns += inner_turns;
             }
@@ -318,7 +483,7 @@ impl Clubs<'_> {
         best_turns
     }
 
-    pub fn count_ideal_turns(&self, within: BitVectorSlice, guess: Word) -> usize {
+    pub fn count_ideal_turns(&self, within: &BitVector, guess: Word) -> usize {
         let mut turns_left = within.count() as usize;
 
         self.for_each_cluster(within, guess, &mut |_, cluster| {
@@ -329,7 +494,7 @@ impl Clubs<'_> {
         turns_left
     }
 
-    pub fn cluster_vector(&self, within: BitVectorSlice, guess: Word) -> ClusterVector {
+    pub fn cluster_vector(&self, within: &BitVector, guess: Word) -> ClusterVector {
         let mut cv = ClusterVector::new(Vec::new());
 
         self.for_each_cluster(within, guess, &mut |_, cluster| {
@@ -339,7 +504,7 @@ impl Clubs<'_> {
         cv
     }
 
-    fn for_each_cluster(&self, within: BitVectorSlice, guess: Word, action: &mut impl FnMut(Response, BitVectorSlice)) {
+    fn for_each_cluster(&self, within: &BitVector, guess: Word, action: &mut impl FnMut(Response, BitVector)) {
         if guess.has_repeat_letters() {
             self.for_each_cluster_repeats_recursive(guess, 0, within, 0u16, action);
         } else {
@@ -347,71 +512,65 @@ impl Clubs<'_> {
         }
     }
 
-    fn for_each_cluster_recursive(&self, guess: Word, next_letter_index: usize, matches: BitVectorSlice, tiles: u16, action: &mut impl FnMut(Response, BitVectorSlice)) {
+    fn for_each_cluster_recursive(&self, guess: Word, next_letter_index: usize, matches: &BitVector, tiles: u16, action: &mut impl FnMut(Response, BitVector)) {
         // If set is empty, stop
-        if matches.count() == 0 { return; }
+        if matches.is_empty() { return; }
 
         if next_letter_index >= 5 {
             // If we've intersected all letter-sets, call the action (except for the 'self' cluster)
             if tiles != response::ALL_GREEN {
-                action(Response::new(tiles), matches);
+                action(Response::new(tiles), matches.clone());
             }
         } else {
             let letter = guess.iter_index().nth(next_letter_index).unwrap() as usize;
 
             // Recurse for Green: Have this letter at this position
-            let mut green_matches = self.letters[letter].pos[next_letter_index];
-            green_matches.intersect_with(&matches);
-            self.for_each_cluster_recursive(guess, next_letter_index + 1, green_matches, (tiles << 2) + 2, action);
+            let green_matches = matches.intersection(&self.letters[letter].pos[next_letter_index]);
+            self.for_each_cluster_recursive(guess, next_letter_index + 1, &green_matches, (tiles << 2) + 2, action);
 
             // Recurse for Yellow: Have this letter, but not at this position
-            let mut yellow_matches = self.letters[letter].any;
+            let mut yellow_matches = matches.intersection(&self.letters[letter].any);
             yellow_matches.except_with(&green_matches);
-            yellow_matches.intersect_with(&matches);
-            self.for_each_cluster_recursive(guess, next_letter_index + 1, yellow_matches, (tiles << 2) + 1, action);
+            self.for_each_cluster_recursive(guess, next_letter_index + 1, &yellow_matches, (tiles << 2) + 1, action);
 
             // Recurse for Black: Words which don't have this letter at all
-            let mut black_matches = self.letters[letter].any;
-            black_matches.not(self.answer_count);
-            black_matches.intersect_with(&matches);
-            self.for_each_cluster_recursive(guess, next_letter_index + 1, black_matches, (tiles << 2) + 0, action);
+            let black_matches = matches.difference(&self.letters[letter].any);
+            self.for_each_cluster_recursive(guess, next_letter_index + 1, &black_matches, (tiles << 2) + 0, action);
         }
     }
 
-    fn for_each_cluster_repeats_recursive(&self, guess: Word, next_letter_index: usize, matches: BitVectorSlice, tiles: u16, action: &mut impl FnMut(Response, BitVectorSlice)) {
+    fn for_each_cluster_repeats_recursive(&self, guess: Word, next_letter_index: usize, matches: &BitVector, tiles: u16, action: &mut impl FnMut(Response, BitVector)) {
         // If set is empty, stop
-        if matches.count() == 0 { return; }
+        if matches.is_empty() { return; }
 
         if next_letter_index >= 5 {
             // If we've intersected all letter-sets, call the action (except for the 'self' cluster)
             if tiles != response::ALL_GREEN {
-                action(Response::new(tiles), matches);
+                action(Response::new(tiles), matches.clone());
             }
         } else {
             let letter = guess.iter_index().nth(next_letter_index).unwrap() as usize;
 
             // Find and Recurse for Green: Have this letter at this position
-            let mut green_matches = self.letters[letter].pos[next_letter_index];
-            green_matches.intersect_with(&matches);
-            self.for_each_cluster_repeats_recursive(guess, next_letter_index + 1, green_matches, (tiles << 2) + 2, action);
+            let green_matches = matches.intersection(&self.letters[letter].pos[next_letter_index]);
+            self.for_each_cluster_repeats_recursive(guess, next_letter_index + 1, &green_matches, (tiles << 2) + 2, action);
 
             // Find and Recurse for Yellow: Find answers with *remaining* *unmatched* copes of the letter
             let yellow_matches = self.yellows_for(guess, letter as u8, next_letter_index, matches);
             //yellow_matches.intersect_with(&matches); [already done inside]
-            self.for_each_cluster_repeats_recursive(guess, next_letter_index + 1, yellow_matches, (tiles << 2) + 1, action);
+            self.for_each_cluster_repeats_recursive(guess, next_letter_index + 1, &yellow_matches, (tiles << 2) + 1, action);
 
             // Find and Recurse for Blacks; all answers not green or yellow
-            let mut black_matches = matches.clone();
-            black_matches.except_with(&green_matches);
+            let mut black_matches = matches.difference(&green_matches);
             black_matches.except_with(&yellow_matches);
-            self.for_each_cluster_repeats_recursive(guess, next_letter_index + 1, black_matches, (tiles << 2) + 0, action);
+            self.for_each_cluster_repeats_recursive(guess, next_letter_index + 1, &black_matches, (tiles << 2) + 0, action);
         }
     }
 
     /// Correctly find which answers get a yellow tile for a given guess letter when the guess has repeated letters.
-    fn yellows_for(&self, guess: Word, letter: u8, letter_index: usize, within: BitVectorSlice) -> BitVectorSlice {
+    fn yellows_for(&self, guess: Word, letter: u8, letter_index: usize, within: &BitVector) -> BitVector {
         let mut occurrences_before = 0;
-        let mut have_any_unmatched = BitVectorSlice::new();
+        let mut have_any_unmatched = BitVector::new();
 
         for (index, letter_here) in guess.iter_index().enumerate() {
             if letter_here == letter {
@@ -429,7 +588,7 @@ impl Clubs<'_> {
         have_any_unmatched.except_with(&self.letters[letter as usize].pos[letter_index]);
 
         // Filter to 'within' also, to avoid extra per-word word
-        have_any_unmatched.intersect_with(&within);
+        have_any_unmatched.intersect_with(within);
 
         // If this is the first copy of 'letter' in the guess, all words with any unmatched copies get yellow.
         if occurrences_before == 0 {
@@ -438,7 +597,7 @@ impl Clubs<'_> {
 
         // If this is a later copy of 'letter' in the guess, we have to figure out which answers have 
         //  unmatched copies which weren't assigned to an earlier copy of 'letter' in the guess.
-        let mut had_enough_unmatched = BitVectorSlice::new();
+        let mut had_enough_unmatched = BitVector::new();
 
         for answer_index in have_any_unmatched.iter() {
             let answer = self.answers[answer_index];
@@ -489,24 +648,47 @@ mod tests {
         let clubs = Clubs::new(&words, &valid);
 
         // Verify 'within' respected, and the guess itself doesn't appear in a cluster
-        assert_eq!(split_to_string(&clubs, w("blush"), BitVectorSlice::from_vec(vec!(0, 1, 2))), ".L.SH: [slosh]; .LUSH: [slush]");
+        assert_eq!(split_to_string(&clubs, w("blush"), &BitVector::from_vec(vec!(0, 1, 2))), ".L.SH: [slosh]; .LUSH: [slush]");
 
         // Split all words with blush
-        assert_eq!(split_to_string(&clubs, w("blush"), clubs.all_vector()), ".L.S.: [gloss, floss]; .L.SH: [slosh]; .LUSH: [slush, flush]");
+        assert_eq!(split_to_string(&clubs, w("blush"), &clubs.all_vector()), ".L.S.: [gloss, floss]; .L.SH: [slosh]; .LUSH: [slush, flush]");
 
         // Verify repeat letter guesses FLOSS S5 should get yellow for S1s because S4 is green, so it didn't take S1.
-        assert_eq!(split_to_string(&clubs, w("floss"), clubs.all_vector()), ".L.S.: [blush]; .L.Ss: [slush]; .LOSs: [slosh]; .LOSS: [gloss]; FL.S.: [flush]");
+        assert_eq!(split_to_string(&clubs, w("floss"), &clubs.all_vector()), ".L.S.: [blush]; .L.Ss: [slush]; .LOSs: [slosh]; .LOSS: [gloss]; FL.S.: [flush]");
 
         // Floss S4 matches exact on each other word, so no yellos
         assert_eq!(try_yellows(&clubs, w("floss"), 3), "");
     }
 
+    #[test]
+    fn split_large() {
+        // Verify clusters over 64 answers split the same as scoring each answer individually
+        let valid = Vec::new();
+        let answers = Word::parse_file(std::path::Path::new("../data/2315/answers.txt"));
+        let clubs = Clubs::new(&answers, &valid);
+        let within = clubs.all_vector();
+        assert_eq!(within.count(), 2315);
+
+        for guess in wv("soare, clint, geese, puppy") {
+            let mut map = HashMap::new();
+            rank::split(&answers, guess, &mut map);
+
+            let mut expected = map.into_iter().collect::<Vec<_>>();
+            expected.sort();
+
+            let mut actual = clubs.split(guess, &within).into_iter().map(|(r, cluster)| (r, clubs.cluster_to_words(&cluster))).collect::<Vec<_>>();
+            actual.sort();
+
+            assert_eq!(actual, expected);
+        }
+    }
+
     fn try_yellows(clubs: &Clubs, guess: Word, letter_index: usize) -> String {
         let yellows = clubs.yellows_for(
             guess, 
             guess.iter_index().nth(letter_index).unwrap(), 
             letter_index, 
-            clubs.all_vector());
+            &clubs.all_vector());
 
         let mut result = String::new();
 
@@ -518,7 +700,7 @@ mod tests {
         result
     }
 
-    fn split_to_string(clubs: &Clubs, guess: Word, within: BitVectorSlice) -> String {
+    fn split_to_string(clubs: &Clubs, guess: Word, within: &BitVector) -> String {
         let mut result = String::new();
 
         // Find clusters, then sort by Response
@@ -531,7 +713,7 @@ mod tests {
             result += &format!("{}: [", response.to_knowns_string(&guess));
 
             // Write answers (in same order as they exist in club)
-            for (j, answer) in clubs.cluster_to_words(*cluster).iter().enumerate() {
+            for (j, answer) in clubs.cluster_to_words(cluster).iter().enumerate() {
                 if j > 0 { result += ", "; }
                 result += &answer.to_string();
             }
@@ -585,35 +767,35 @@ mod tests {
 
         // How many turns if we guess "begin" next? 
         //  CV = [2, 2] so 1 + 1 + (1 + 2) + (1 + 2) = 8 turns
-        assert_eq!(clubs.cluster_vector(within, w("begin")).to_string(), "[2, 2]");
-        assert_eq!(clubs.count_ideal_turns(within, w("begin")), 7 + 8);
+        assert_eq!(clubs.cluster_vector(&within, w("begin")).to_string(), "[2, 2]");
+        assert_eq!(clubs.count_ideal_turns(&within, w("begin")), 7 + 8);
 
         // How many turns if we guess "ennui" next?
         //  CV = [2, 0, 0, 1] so 1 + 1 + (1 + 2 + 2 + 2) = 9 turns
-        assert_eq!(clubs.cluster_vector(within, w("ennui")).to_string(), "[2, 0, 0, 1]");
-        assert_eq!(clubs.count_ideal_turns(within, w("ennui")), 7 + 9);
+        assert_eq!(clubs.cluster_vector(&within, w("ennui")).to_string(), "[2, 0, 0, 1]");
+        assert_eq!(clubs.count_ideal_turns(&within, w("ennui")), 7 + 9);
 
         // How many turns if we guess "vixen" next?
         //  CV = [4, 1] so 1 + 1 + 1 + 1 + (1 + 2) = 7 turns
-        assert_eq!(clubs.cluster_vector(within, w("vixen")).to_string(), "[4, 1]");
-        assert_eq!(clubs.count_ideal_turns(within, w("vixen")), 7 + 7);
+        assert_eq!(clubs.cluster_vector(&within, w("vixen")).to_string(), "[4, 1]");
+        assert_eq!(clubs.count_ideal_turns(&within, w("vixen")), 7 + 7);
 
         // How many turns if we guess "index" next?
         //  CV = [6] so 1 + 1 + 1 + 1 + 1 + 1 = 6 turns
-        assert_eq!(clubs.cluster_vector(within, w("index")).to_string(), "[6]");
-        assert_eq!(clubs.count_ideal_turns(within, w("index")), 7 + 6);
+        assert_eq!(clubs.cluster_vector(&within, w("index")).to_string(), "[6]");
+        assert_eq!(clubs.count_ideal_turns(&within, w("index")), 7 + 6);
 
         // How many turns if we guess "dumbo" next?
         //  CV = [3, 2] so 1 + 1 + 1 + (1 + 2) + (1 + 2) = 9 turns
-        assert_eq!(clubs.cluster_vector(within, w("dumbo")).to_string(), "[3, 2]");
-        assert_eq!(clubs.count_ideal_turns(within, w("dumbo")), 7 + 9);
+        assert_eq!(clubs.cluster_vector(&within, w("dumbo")).to_string(), "[3, 2]");
+        assert_eq!(clubs.count_ideal_turns(&within, w("dumbo")), 7 + 9);
 
         // Verify "best_next_guess" finds the option with the fewest ideal turns 
-        assert_eq!(clubs.best_next_guess(within), (Some(w("index")), 7 + 6));
+        assert_eq!(clubs.best_next_guess(&within), (Some(w("index")), 7 + 6));
 
         // parse, clint, index, * would be 27 turns (3*1 + 4*6)
         // There are 13 turns from the index guess (1*1 + 2*6)
-        assert_eq!(clubs.print_in_cluster(within),
+        assert_eq!(clubs.print_in_cluster(&within),
 "ennui  16  [2, 0, 0, 1]
 begin  15  [2, 2]
 denim  15  [2, 2]
@@ -624,18 +806,18 @@ index  13  [6]
 ");
 
         // Ask within a subset: [begin, denim, given]
-        let mut within = BitVectorSlice::new_all(4);
+        let mut within = BitVector::new_all(4);
         within.remove(0);
 
         // Ideal turns for "denim" is (1 + 2 + 2) = 5
-        assert_eq!(clubs.count_ideal_turns(within, w("denim")), 5);
+        assert_eq!(clubs.count_ideal_turns(&within, w("denim")), 5);
 
         // Ideal turns is 5 for each of them
-        assert_eq!(clubs.best_next_guess(within), (None, 5));
+        assert_eq!(clubs.best_next_guess(&within), (None, 5));
 
         // parse, clint, index, * would be 27 turns (3*1 + 4*6)
         // There are 13 turns from the index guess (1*1 + 2*6)
-        assert_eq!(clubs.print_in_cluster(within),
+        assert_eq!(clubs.print_in_cluster(&within),
 "begin  5  [2]
 denim  5  [2]
 given  5  [2]
@@ -649,29 +831,29 @@ given  5  [2]
         // Safe Triple: returns two turns after guess, and false (which guess doesn't matter)
         let words = wv("begin, denim, given");
         let clubs = Clubs::new(&words, &valid);
-        assert_eq!(clubs.best_next_guess(clubs.all_vector()), (None, 5));
+        assert_eq!(clubs.best_next_guess(&clubs.all_vector()), (None, 5));
 
         // Unsafe Triple: returns a safe option and true (which one matters) ["widen" can't tell between vixen and given because _i_en common and 'w', 'd' in neither]
         let words = wv("given, vixen, widen");
         let clubs = Clubs::new(&words, &valid);
-        assert_eq!(clubs.best_next_guess(clubs.all_vector()), (Some(w("given")), 5));
+        assert_eq!(clubs.best_next_guess(&clubs.all_vector()), (Some(w("given")), 5));
 
         // Terrible Triple: returns first option, 3, false; no better choice
         let words = wv("aaaaa, bbbbb, ccccc");
         let clubs = Clubs::new(&words, &valid);
-        assert_eq!(clubs.best_next_guess(clubs.all_vector()), (None, 6));
+        assert_eq!(clubs.best_next_guess(&clubs.all_vector()), (None, 6));
 
         let valid = wv("aaaaa, bbbbb, ccccc, ddddd, eeeee, abcde");
 
         // Terrible Triple: out of cluster can never be better
         let words = wv("aaaaa, bbbbb, ccccc");
         let clubs = Clubs::new(&words, &valid);
-        assert_eq!(clubs.best_next_guess(clubs.all_vector()), (None, 6));
+        assert_eq!(clubs.best_next_guess(&clubs.all_vector()), (None, 6));
 
         // Terrible Five; verify out-of-cluster best is found
         let words = wv("aaaaa, bbbbb, ccccc, ddddd, eeeee");
         let clubs = Clubs::new(&words, &valid);
-        assert_eq!(clubs.best_next_guess(clubs.all_vector()), (Some(w("abcde")), 10));
+        assert_eq!(clubs.best_next_guess(&clubs.all_vector()), (Some(w("abcde")), 10));
     }
 
     #[test]
@@ -681,22 +863,22 @@ given  5  [2]
         // Safe Triple: (1 + 2 + 2) turns for each case, so 5.0 random guessing total
         let words = wv("begin, denim, given");
         let clubs = Clubs::new(&words, &valid);
-        assert_float_absolute_eq!(clubs.count_random_turns(clubs.all_vector()), 5.0);
+        assert_float_absolute_eq!(clubs.count_random_turns(&clubs.all_vector()), 5.0);
 
         // Partial Triple: given = 5.0; vixen = 5.0; widen = 6.0, so average is 5.333
         let words = wv("given, vixen, widen");
         let clubs = Clubs::new(&words, &valid);
-        assert_float_absolute_eq!(clubs.count_random_turns(clubs.all_vector()), 16.0 / 3.0);
+        assert_float_absolute_eq!(clubs.count_random_turns(&clubs.all_vector()), 16.0 / 3.0);
 
         // Terrible Triple: (1 + 2 + 3) turns for each case, so 6.0 random guessing total
         let words = wv("aaaaa, bbbbb, ccccc");
         let clubs = Clubs::new(&words, &valid);
-        assert_float_absolute_eq!(clubs.count_random_turns(clubs.all_vector()), 6.0);
+        assert_float_absolute_eq!(clubs.count_random_turns(&clubs.all_vector()), 6.0);
 
         // Safe Quad: (1 + 2 + 2 + 2)
         let words = wv("gnome, nudge, undue, venue");
         let clubs = Clubs::new(&words, &valid);
-        assert_float_absolute_eq!(clubs.count_random_turns(clubs.all_vector()), 7.0);
+        assert_float_absolute_eq!(clubs.count_random_turns(&clubs.all_vector()), 7.0);
 
         // Four options.
         //   booze  6	[0, 0, 1]
@@ -709,7 +891,7 @@ given  5  [2]
         //  So 3 * 7 + 1 * 9 = 30 / 4 options = 7.5 total on average.
         let words = wv("booze, dodge, gouge, vogue");
         let clubs = Clubs::new(&words, &valid);
-        assert_float_absolute_eq!(clubs.count_random_turns(clubs.all_vector()), 7.5);
+        assert_float_absolute_eq!(clubs.count_random_turns(&clubs.all_vector()), 7.5);
     }
 
     #[test]
@@ -752,15 +934,128 @@ given  5  [2]
         //assert_eq!(tree.to_string(), "");//, 43, "[*] -> godly 43; [folly, holly, jolly, lowly, wooly] -> folly 10");
     }
 
+    #[test]
+    fn count_best_turns_hard_mode() {
+        let valid = wv("flams");
+
+        // FLAMS splits every ?IGHT word apart: 5 + (1 + 1 + 1 + 1 + 1) = 10
+        let tree = run_best_turns("fight, light, might, night, sight", &valid);
+        assert_eq!(tree.outer_total_turns, 10.0);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("flams")));
+
+        // With no guesses yet, nothing has been revealed, so hard mode may still guess FLAMS
+        let tree = run_best_turns_mode("fight, light, might, night, sight", &valid, true, &[]);
+        assert_eq!(tree.outer_total_turns, 10.0);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("flams")));
+
+        // After EIGHT revealed the ?IGHT hints, FLAMS doesn't use them, so hard mode must guess in-cluster
+        let tree = run_best_turns_mode("fight, light, might, night, sight", &valid, true, &wv("eight"));
+        assert_eq!(tree.outer_total_turns, 15.0);
+        assert_ne!(tree.next_guess, WordleGuess::Specific(w("flams")));
+
+        // FLAMS reveals nothing about {night, tight, wight}, so TWINS is still legal after it
+        let words = wv("fight, light, might, night, sight, tight, wight");
+        let valid = wv("flams, twins");
+        let mut clubs = Clubs::new(&words, &valid);
+        clubs.set_hard_mode(true, &[]);
+        clubs.set_objective(Objective::Failures, 3);
+        let tree = best_tree(&clubs);
+        assert_eq!(smart_trim(&tree.to_string()), "17 (fight, 7) -> flams {fight, light, might, night, sight, tight, wight}\n    6 (night, 3) -> twins {night, tight, wight}");
+    }
+
+    #[test]
+    fn count_best_turns_objectives() {
+        let words = "fight, light, might, night, sight, tight, wight";
+        let valid = wv("flams, twins");
+
+        // FLAMS leaves {night, tight, wight}; any guess there takes 6 turns, so none is recorded: 7 + (1 + 1 + 1 + 1 + 6) = 17
+        let tree = run_best_turns_objective(words, &valid, Objective::TotalTurns, 3);
+        assert_eq!(smart_trim(&tree.to_string()), "17 (fight, 7) -> flams {fight, light, might, night, sight, tight, wight}\n    {night, tight, wight}");
+
+        // Starting on turn 4, guessing NIGHT on turn 5 would solve WIGHT on turn 7; TWINS solves every one on turn 6.
+        let tree = run_best_turns_objective(words, &valid, Objective::Failures, 3);
+        assert_eq!(smart_trim(&tree.to_string()), "17 (fight, 7) -> flams {fight, light, might, night, sight, tight, wight}\n    6 (night, 3) -> twins {night, tight, wight}");
+
+        // With turns to spare, no choice can fail, so the first in-cluster word is kept
+        let tree = run_best_turns_objective(words, &valid, Objective::Failures, 0);
+        assert!(tree.to_string().contains("-> night"));
+
+        // Minimizing the worst case always prefers TWINS
+        let tree = run_best_turns_objective(words, &valid, Objective::MaxTurns, 0);
+        assert!(tree.to_string().contains("-> twins"));
+
+        // To solve the most within 5 turns starting on turn 5, guess an answer (costing 3 more turns overall)
+        let tree = run_best_turns_objective(words, &valid, Objective::SolvedWithin(5), 4);
+        assert_eq!(tree.outer_total_turns, 20.0);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("fight")));
+    }
+
+    #[test]
+    fn count_best_turns_weighted() {
+        let valid = Vec::new();
+
+        // Equal weights find the same strategy as none
+        let words = wv("folly, holly, jolly, lowly, wooly");
+        let weights = AnswerWeights::uniform(&words);
+        let tree = run_best_turns_weighted(&words, &valid, &weights);
+        assert_eq!(tree.outer_total_turns, 10.0);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("folly")));
+
+        // Unweighted, GIVEN is best (5 turns vs. 6 for WIDEN). If WIDEN is ten times likelier, guessing it first wins.
+        //  Weights are {given: 0.25, vixen: 0.25, widen: 2.5}; WIDEN: 2.5 + 0.25 * 2 + 0.25 * 3 = 3.75; GIVEN: 0.25 + 2.75 * 2 = 5.75
+        let words = wv("given, vixen, widen");
+        let weights = AnswerWeights::parse("widen\t10\ngiven\t1", &words).unwrap();
+        let tree = run_best_turns_weighted(&words, &valid, &weights);
+        assert_eq!(tree.outer_total_turns, 4.0);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("widen")));
+    }
+
+    fn run_best_turns_weighted(words: &Vec<Word>, valid: &Vec<Word>, weights: &AnswerWeights) -> WordleTree {
+        let mut clubs = Clubs::new(words, valid);
+        clubs.set_weights(weights);
+
+        let mut choices = HashMap::new();
+        let outer_turns = clubs.count_best_turns(&clubs.all_vector(), &mut choices);
+
+        let mut tree = WordleTree::new_sentinel();
+        clubs.best_strategy(&clubs.all_vector(), &choices, false, &mut tree);
+        let mut tree = tree.take_first_child().unwrap();
+        tree.outer_total_turns = outer_turns as f64;
+        tree
+    }
+
+    fn run_best_turns_objective(words: &str, valid: &Vec<Word>, objective: Objective, turns_before: usize) -> WordleTree {
+        let words = wv(words);
+        let mut clubs = Clubs::new(&words, valid);
+        clubs.set_objective(objective, turns_before);
+
+        let mut choices = HashMap::new();
+        let outer_turns = clubs.count_best_turns(&clubs.all_vector(), &mut choices);
+
+        let mut tree = WordleTree::new_sentinel();
+        clubs.best_strategy(&clubs.all_vector(), &choices, false, &mut tree);
+        let mut tree = tree.take_first_child().unwrap();
+        tree.outer_total_turns = outer_turns as f64;
+        tree
+    }
+
     fn run_best_turns(words: &str, valid: &Vec<Word>) -> WordleTree {
+        run_best_turns_mode(words, valid, false, &[])
+    }
+
+    fn run_best_turns_mode(words: &str, valid: &Vec<Word>, hard_mode: bool, guesses_before: &[Word]) -> WordleTree {
         let words = wv(words);
-        let clubs = Clubs::new(&words, valid);
+        let mut clubs = Clubs::new(&words, valid);
+        clubs.set_hard_mode(hard_mode, guesses_before);
+        best_tree(&clubs)
+    }
 
+    fn best_tree(clubs: &Clubs) -> WordleTree {
         let mut choices = HashMap::new();
-        let outer_turns = clubs.count_best_turns(clubs.all_vector(), &mut choices);
+        let outer_turns = clubs.count_best_turns(&clubs.all_vector(), &mut choices);
 
         let mut tree = WordleTree::new_sentinel();
-        clubs.best_strategy(clubs.all_vector(), &choices, false, &mut tree);
+        clubs.best_strategy(&clubs.all_vector(), &choices, false, &mut tree);
         
         let mut root = tree.take_first_child().unwrap();
         root.outer_total_turns = outer_turns as f64;
@@ -1,5 +1,5 @@
 use std::{collections::HashMap, fmt::{Display, Formatter}};
-use crate::{word::Word, parser::Parser, bit_vector_slice::BitVectorSlice};
+use crate::{word::Word, parser::Parser, bit_vector::BitVector, json::Json};
 
 const BIGGEST_CLUSTER_SHOWN: usize = 5;
 
@@ -36,7 +36,7 @@ impl ClusterVector {
         result
     }
 
-    pub fn from_bits<T>(vec: &Vec<(T, BitVectorSlice)>) -> ClusterVector {
+    pub fn from_bits<T>(vec: &Vec<(T, BitVector)>) -> ClusterVector {
         let mut result = ClusterVector::new(Vec::new());
 
         for (_, answers) in vec.iter() {
@@ -112,6 +112,17 @@ impl ClusterVector {
         result
     }
 
+    /// Every count, as a JSON array; unlike to_string, nothing is left out.
+    pub fn to_json(&self) -> Json {
+        Json::Array(self.value.iter().map(|count| Json::from(*count)).collect())
+    }
+
+    pub fn from_json(json: &Json) -> Result<ClusterVector, String> {
+        let values = json.as_array().ok_or("Cluster vector must be an array")?;
+        let value = values.iter().map(|v| v.as_usize().ok_or("Cluster vector counts must be whole numbers")).collect::<Result<Vec<usize>, &str>>()?;
+        Ok(ClusterVector::new(value))
+    }
+
     pub fn parse(parser: &mut Parser) -> Result<Option<ClusterVector>, String> {
         parser.require("[")?;
         let mut values = Vec::new();
@@ -194,6 +205,69 @@ impl ClusterVector {
 
         double_total / 2
     }
+
+    /// Expected information from the split, in bits: -∑ p log2(p) over clusters, where p is the share of answers in the cluster.
+    pub fn entropy(&self) -> f64 {
+        let word_count = self.word_count() as f64;
+        let mut bits = 0.0;
+
+        for (i, count) in self.value.iter().enumerate() {
+            let p = (i + 1) as f64 / word_count;
+            bits -= (*count as f64) * p * p.log2();
+        }
+
+        bits
+    }
+
+    /// Expected number of answers left after the split: each answer is in a cluster of n with odds n / total.
+    pub fn expected_remaining(&self) -> f64 {
+        let word_count = self.word_count() as f64;
+        let mut total = 0.0;
+
+        for (i, count) in self.value.iter().enumerate() {
+            let n = (i + 1) as f64;
+            total += (*count as f64) * n * n;
+        }
+
+        total / word_count
+    }
+
+    /// Rank by entropy (lower is better): thousandths of a bit short of fully identifying every answer (log2 of the answer count).
+    pub fn rank_entropy(&self) -> usize {
+        let word_count = self.word_count();
+        if word_count == 0 { return 0; }
+
+        (((word_count as f64).log2() - self.entropy()) * 1000.0).round() as usize
+    }
+
+    /// Rank by expected remaining answers (lower is better), in thousandths of an answer.
+    pub fn rank_expected_remaining(&self) -> usize {
+        if self.word_count() == 0 { return 0; }
+        (self.expected_remaining() * 1000.0).round() as usize
+    }
+
+    /// rank_entropy as a sum over clusters (n log2 n thousandths of a bit for each), so it can be weighted per cluster.
+    ///  Orders guesses for the same answers as rank_entropy does (it is rank_entropy times the answer count).
+    pub fn rank_entropy_total(&self) -> usize {
+        let mut bits = 0.0;
+
+        for (i, count) in self.value.iter().enumerate() {
+            let n = (i + 1) as f64;
+            bits += (*count as f64) * n * n.log2();
+        }
+
+        (bits * 1000.0).round() as usize
+    }
+
+    /// rank_expected_remaining as a sum over clusters (n² for each), so it can be weighted per cluster.
+    pub fn rank_expected_remaining_total(&self) -> usize {
+        self.value.iter().enumerate().map(|(i, count)| count * (i + 1) * (i + 1)).sum()
+    }
+
+    /// Rank by distinct responses (lower is better): the number of answers which don't get a response of their own.
+    pub fn rank_cluster_count(&self) -> usize {
+        self.word_count() - self.cluster_count()
+    }
 }
 
 impl Display for ClusterVector {
@@ -239,6 +313,42 @@ mod tests {
 
     }
 
+    #[test]
+    fn information() {
+        // Four answers split into singles: two bits, one left each, every answer identified
+        let cv = ClusterVector::new(vec![4]);
+        assert_eq!(cv.entropy(), 2.0);
+        assert_eq!(cv.expected_remaining(), 1.0);
+        assert_eq!(cv.rank_entropy(), 0);
+        assert_eq!(cv.rank_expected_remaining(), 1000);
+        assert_eq!(cv.rank_cluster_count(), 0);
+
+        // Two pairs: one bit, two left each, half a bit short
+        let cv = ClusterVector::new(vec![0, 2]);
+        assert_eq!(cv.entropy(), 1.0);
+        assert_eq!(cv.expected_remaining(), 2.0);
+        assert_eq!(cv.rank_entropy(), 1000);
+        assert_eq!(cv.rank_cluster_count(), 2);
+
+        // Two singles and a pair: 1.5 bits, (1 + 1 + 2 * 2) / 4 = 1.5 left
+        let cv = ClusterVector::new(vec![2, 1]);
+        assert_eq!(cv.entropy(), 1.5);
+        assert_eq!(cv.expected_remaining(), 1.5);
+        assert_eq!(cv.rank_entropy(), 500);
+        assert_eq!(cv.rank_expected_remaining(), 1500);
+        assert_eq!(cv.rank_cluster_count(), 1);
+
+        // The totals (for weighting per cluster) are the same ranks times the answer count
+        assert_eq!(cv.rank_entropy_total(), 2000);
+        assert_eq!(cv.rank_expected_remaining_total(), 6);
+
+        // Empty vectors rank as perfect rather than dividing by zero
+        assert_eq!(ClusterVector::new(Vec::new()).entropy(), 0.0);
+        assert_eq!(ClusterVector::new(Vec::new()).rank_entropy(), 0);
+        assert_eq!(ClusterVector::new(Vec::new()).rank_expected_remaining(), 0);
+        assert_eq!(ClusterVector::new(Vec::new()).rank_entropy_total(), 0);
+    }
+
     #[test]
     fn total_turns() {
         // Pessimistic
@@ -298,6 +408,17 @@ mod tests {
         assert_eq!(cv.to_string(), "[5, 4, 3, 2, 1 .. ^14]");
     }
 
+    #[test]
+    fn json_cluster_vector() {
+        // JSON keeps the clusters the text summarizes
+        let cv = ClusterVector::new(vec![632, 202, 105, 43, 30, 18, 18, 3, 7, 4, 4, 1]);
+        assert_eq!(cv.to_json().to_string(), "[632,202,105,43,30,18,18,3,7,4,4,1]");
+        assert_eq!(ClusterVector::from_json(&cv.to_json()).unwrap(), cv);
+
+        assert!(ClusterVector::from_json(&Json::parse("[1, 2.5]").unwrap()).is_err());
+        assert!(ClusterVector::from_json(&Json::parse("{}").unwrap()).is_err());
+    }
+
     #[test]
     fn parse_cluster_vector() {
         // All singles
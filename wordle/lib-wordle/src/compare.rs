@@ -0,0 +1,222 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}, ops::Range};
+use rand::rngs::StdRng;
+use crate::{check::{self, SimulationReport, SimulationRun}, weights::AnswerWeights, word::Word, wordle_tree::{WordleTree, tree_player::TreePlayer}};
+
+// Standard errors either side of the mean for a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// A head-to-head comparison of two strategy trees.
+///  Both trees play the same answer in each game, with the same random numbers, so the difference
+///  between them comes from the strategies and not from luck.
+pub struct Comparison {
+    pub left: SimulationReport,
+    pub right: SimulationReport,
+    total_difference: i64,                      // Right turns minus left turns, over every game
+    total_difference_squared: u64,
+    by_answer: HashMap<Word, (i64, usize)>,     // Turn difference and games for each answer
+}
+
+/// Play 'game_count' games with each tree, split across threads as in check::simulate_tree, so the same seed repeats a comparison exactly.
+#[allow(clippy::too_many_arguments)]
+pub fn compare(left: &WordleTree, right: &WordleTree, answers: &[Word], game_answer_pool: &[Word], game_count: usize, hard_mode: bool, weights: Option<&AnswerWeights>, run: &SimulationRun) -> Comparison {
+    let new_state = || {
+        let mut left_player = TreePlayer::new(left);
+        let mut right_player = TreePlayer::new(right);
+        left_player.set_hard_mode(hard_mode);
+        right_player.set_hard_mode(hard_mode);
+        (Comparison::new(), left_player, right_player)
+    };
+
+    let play_block = |(comparison, left_player, right_player): &mut (Comparison, TreePlayer, TreePlayer), games: Range<usize>, rng: &mut StdRng| {
+        for game in games {
+            let answer = check::choose_answer(game_answer_pool, game, game_count, weights, rng);
+
+            // Give each tree the same random numbers for its random guesses
+            let mut right_rng = rng.clone();
+            let left_turns = check::play_game(answers, answer, &Vec::new(), &mut |g, t, a| left_player.choose(g, t, a), hard_mode, rng, false);
+            let right_turns = check::play_game(answers, answer, &Vec::new(), &mut |g, t, a| right_player.choose(g, t, a), hard_mode, &mut right_rng, false);
+            comparison.add_game(answer, left_turns, right_turns);
+        }
+    };
+
+    let mut result = Comparison::new();
+    for (comparison, _, _) in check::run_blocks(game_count, run, new_state, play_block) {
+        result.add_comparison(&comparison);
+    }
+
+    result
+}
+
+impl Comparison {
+    fn new() -> Comparison {
+        Comparison { left: SimulationReport::new(), right: SimulationReport::new(), total_difference: 0, total_difference_squared: 0, by_answer: HashMap::new() }
+    }
+
+    fn add_game(&mut self, answer: Word, left_turns: usize, right_turns: usize) {
+        self.left.add_game(answer, left_turns);
+        self.right.add_game(answer, right_turns);
+
+        let difference = right_turns as i64 - left_turns as i64;
+        self.total_difference += difference;
+        self.total_difference_squared += (difference * difference) as u64;
+
+        let entry = self.by_answer.entry(answer).or_default();
+        entry.0 += difference;
+        entry.1 += 1;
+    }
+
+    fn add_comparison(&mut self, other: &Comparison) {
+        self.left.add_report(&other.left);
+        self.right.add_report(&other.right);
+        self.total_difference += other.total_difference;
+        self.total_difference_squared += other.total_difference_squared;
+
+        for (answer, (difference, games)) in other.by_answer.iter() {
+            let entry = self.by_answer.entry(*answer).or_default();
+            entry.0 += difference;
+            entry.1 += games;
+        }
+    }
+
+    pub fn game_count(&self) -> usize {
+        self.left.game_count
+    }
+
+    /// Average turns per game for the right tree minus the left; negative means the right tree is better.
+    pub fn mean_difference(&self) -> f64 {
+        if self.game_count() == 0 { return 0.0; }
+        self.total_difference as f64 / self.game_count() as f64
+    }
+
+    /// The standard error of mean_difference
+    pub fn standard_error(&self) -> f64 {
+        check::standard_error(self.game_count(), self.total_difference as f64, self.total_difference_squared as f64)
+    }
+
+    /// The 95% confidence interval for mean_difference
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let margin = CONFIDENCE_Z * self.standard_error();
+        (self.mean_difference() - margin, self.mean_difference() + margin)
+    }
+
+    /// The average difference (right minus left) for each answer which differed, biggest first (ties alphabetically)
+    pub fn answer_differences(&self) -> Vec<(Word, f64)> {
+        let mut answers = self.by_answer.iter()
+            .filter(|(_, (difference, _))| *difference != 0)
+            .map(|(answer, (difference, games))| (*answer, *difference as f64 / *games as f64))
+            .collect::<Vec<(Word, f64)>>();
+
+        answers.sort_by(|l, r| r.1.abs().total_cmp(&l.1.abs()).then(l.0.cmp(&r.0)));
+        answers
+    }
+
+    /// For the nodes of one of the compared trees, the total turn difference (right minus left) for the answers whose games
+    ///  reach each node, per pass through every answer. Each answer's difference is attributed only to the deepest node its game
+    ///  reaches before the two trees' guesses differ, showing which parts of the tree the strategies differ in most, biggest first.
+    pub fn node_differences(&self, tree: &WordleTree, other: &WordleTree, answers: &[Word]) -> Vec<(String, f64)> {
+        let tree_paths = paths_by_answer(tree, answers);
+        let other_paths = paths_by_answer(other, answers);
+
+        let mut by_node: HashMap<Vec<usize>, f64> = HashMap::new();
+        for (answer, (difference, games)) in self.by_answer.iter() {
+            let Some(paths) = tree_paths.get(answer) else { continue; };
+            let other_paths = other_paths.get(answer).map(|p| p.as_slice()).unwrap_or(&[]);
+
+            // The first node where the other tree guesses differently (or has no node), or the last node both reach
+            let divergence = paths.iter()
+                .position(|path| other_paths.get(path.len()).is_none_or(|other_path| other.node_at(other_path).next_guess != tree.node_at(path).next_guess))
+                .unwrap_or(paths.len() - 1);
+
+            *by_node.entry(paths[divergence].clone()).or_default() += *difference as f64 / *games as f64;
+        }
+
+        let mut nodes = by_node.into_iter()
+            .filter(|(_, difference)| difference.abs() > 0.0005)
+            .collect::<Vec<(Vec<usize>, f64)>>();
+
+        nodes.sort_by(|l, r| r.1.abs().total_cmp(&l.1.abs()).then(l.0.cmp(&r.0)));
+        nodes.into_iter().map(|(path, difference)| (tree.node_at(&path).describe(), difference)).collect()
+    }
+}
+
+/// The paths of the nodes each answer reaches in a tree, from the root down
+fn paths_by_answer(tree: &WordleTree, answers: &[Word]) -> HashMap<Word, Vec<Vec<usize>>> {
+    let mut result: HashMap<Word, Vec<Vec<usize>>> = HashMap::new();
+
+    for (path, clusters) in tree.clusters_by_node(answers) {
+        for answer in clusters.iter().flatten() {
+            result.entry(*answer).or_default().push(path.clone());
+        }
+    }
+
+    for paths in result.values_mut() {
+        paths.sort_by_key(|path| path.len());
+    }
+
+    result
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (low, high) = self.confidence_interval();
+        writeln!(f, "Left:  {:.4} ± {:.4} avg turns; {} lost", self.left.average_turns(), self.left.standard_error(), self.left.failures())?;
+        writeln!(f, "Right: {:.4} ± {:.4} avg turns; {} lost", self.right.average_turns(), self.right.standard_error(), self.right.failures())?;
+        write!(f, "Right - Left: {:+.4} avg turns over {} games (95% confidence: {low:+.4} to {high:+.4})", self.mean_difference(), self.game_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{w, wv};
+    use super::*;
+
+    #[test]
+    fn compare_paired() {
+        let answers = wv("fatal, tally");
+        let left = WordleTree::parse("(*, 2) -> fatal".lines()).unwrap();
+        let right = WordleTree::parse("(*, 2) -> tally".lines()).unwrap();
+
+        // Each tree solves its own guess in one turn and the other answer in two
+        let comparison = compare(&left, &right, &answers, &answers, 100, false, None, &SimulationRun::seeded(1));
+        assert_eq!(comparison.game_count(), 100);
+        assert_eq!(comparison.mean_difference(), 0.0);
+        assert_eq!(comparison.answer_differences(), vec![(w("fatal"), 1.0), (w("tally"), -1.0)]);
+
+        let (low, high) = comparison.confidence_interval();
+        assert!(low < 0.0 && high > 0.0);
+    }
+
+    #[test]
+    fn compare_same_random_numbers() {
+        let answers = wv("dowdy, fatal, tally, waltz, bobby, boxer, fever, wafer, waver");
+        let tree = WordleTree::parse("(*, 9) -> parse".lines()).unwrap();
+
+        // A tree against itself never differs, because random guesses are made the same way for both
+        let comparison = compare(&tree, &tree, &answers, &answers, 27_000, false, None, &SimulationRun::seeded(1));
+        assert_eq!(comparison.mean_difference(), 0.0);
+        assert_eq!(comparison.confidence_interval(), (0.0, 0.0));
+        assert!(comparison.answer_differences().is_empty());
+        assert!(comparison.left.average_turns() > 2.0);
+
+        // Guessing BOBBY after PARSE changes only the games reaching that node
+        let right = WordleTree::parse("(*, 9) -> parse\n    (*, 9) -> bobby".lines()).unwrap();
+        let comparison = compare(&tree, &right, &answers, &answers, 27_000, false, None, &SimulationRun::seeded(1));
+        let nodes = comparison.node_differences(&right, &tree, &answers);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].0, "(*, 9) -> bobby");
+        assert_eq!(format!("{:.3}", nodes[0].1), format!("{:.3}", comparison.mean_difference() * answers.len() as f64));
+
+        // Differences are attributed to the node where the guesses differ, not to every node above it
+        let deep_left = WordleTree::parse("(*, 9) -> parse\n    (*, 9) -> clint\n        (*, 9) -> bobby".lines()).unwrap();
+        let deep_right = WordleTree::parse("(*, 9) -> parse\n    (*, 9) -> clint\n        (*, 9) -> waver".lines()).unwrap();
+        let deeper = compare(&deep_left, &deep_right, &answers, &answers, 27_000, false, None, &SimulationRun::seeded(1));
+        assert_ne!(deeper.mean_difference(), 0.0);
+        let nodes = deeper.node_differences(&deep_right, &deep_left, &answers);
+        assert_eq!(nodes.iter().map(|(node, _)| node.as_str()).collect::<Vec<&str>>(), vec!["(*, 9) -> waver"]);
+        assert_eq!(format!("{:.3}", nodes[0].1), format!("{:.3}", deeper.mean_difference() * answers.len() as f64));
+
+        // The same seed with a different thread count gives the same comparison
+        let again = compare(&tree, &right, &answers, &answers, 27_000, false, None, &SimulationRun { threads: 3, ..SimulationRun::seeded(1) });
+        assert_eq!(again.to_string(), comparison.to_string());
+    }
+}
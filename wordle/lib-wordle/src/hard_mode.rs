@@ -0,0 +1,118 @@
+use crate::{word::Word, response::{Response, Tile}};
+
+/// Tracks the Wordle "hard mode" rules: revealed hints must be used in subsequent guesses.
+///  - Any green letter must be guessed again in the same position.
+///  - Any yellow (or green) letter must be included in the guess, as many times as copies were revealed.
+///
+///  Yellow letters may be moved to the same position again, and black letters may be guessed again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct HardMode {
+    greens: [Option<u8>; 5],
+    min_counts: [u8; 26]
+}
+
+impl HardMode {
+    pub fn new() -> HardMode {
+        HardMode { greens: [None; 5], min_counts: [0u8; 26] }
+    }
+
+    /// Add the rules revealed by a guess and its response.
+    pub fn add(&mut self, guess: Word, response: Response) {
+        let mut counts = [0u8; 26];
+
+        for (i, (tile, letter)) in response.iter().zip(guess.iter_index()).enumerate() {
+            match tile {
+                Tile::Green => {
+                    self.greens[i] = Some(letter);
+                    counts[letter as usize] += 1;
+                },
+                Tile::Yellow => counts[letter as usize] += 1,
+                Tile::Black => {}
+            }
+        }
+
+        for (min_count, count) in self.min_counts.iter_mut().zip(counts.iter()) {
+            *min_count = (*min_count).max(*count);
+        }
+    }
+
+    /// Build the rules revealed by a sequence of guesses when playing for a given answer.
+    pub fn from_guesses(guesses: &[Word], answer: Word) -> HardMode {
+        let mut result = HardMode::new();
+
+        for guess in guesses {
+            result.add(*guess, Response::score(*guess, answer));
+        }
+
+        result
+    }
+
+    /// Return whether a guess may be played under these rules.
+    pub fn allows(&self, guess: Word) -> bool {
+        for (green, letter) in self.greens.iter().zip(guess.iter_index()) {
+            if let Some(green) = green {
+                if *green != letter { return false; }
+            }
+        }
+
+        let counts = letter_counts(guess);
+        self.min_counts.iter().zip(counts.iter()).all(|(min_count, count)| count >= min_count)
+    }
+
+    /// Return whether any rules have been added yet.
+    pub fn is_empty(&self) -> bool {
+        self.greens.iter().all(|g| g.is_none()) && self.min_counts.iter().all(|c| *c == 0)
+    }
+}
+
+/// Count the copies of each letter in a word
+pub fn letter_counts(word: Word) -> [u8; 26] {
+    let mut counts = [0u8; 26];
+
+    for letter in word.iter_index() {
+        counts[letter as usize] += 1;
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn hard_mode_basics() {
+        // No rules allow everything
+        let mut rules = HardMode::new();
+        assert!(rules.is_empty());
+        assert!(rules.allows(w("fuzzy")));
+
+        // CRANE -> CR.n. for "crown": C1 and R2 required, N anywhere
+        rules.add(w("crane"), Response::score(w("crane"), w("crown")));
+        assert!(!rules.is_empty());
+        assert!(rules.allows(w("crown")));
+        assert!(rules.allows(w("crank")));          // Yellow letter may be reused in the same position
+        assert!(rules.allows(w("crane")));          // Black letters may be guessed again
+        assert!(!rules.allows(w("crust")));         // Missing N
+        assert!(!rules.allows(w("acorn")));         // C, R not in green positions
+
+        // Repeated letters: SPEED for "geese" reveals two Es; both must be used
+        let mut rules = HardMode::new();
+        rules.add(w("speed"), Response::score(w("speed"), w("geese")));
+        assert!(rules.allows(w("geese")));
+        assert!(rules.allows(w("sheep")));
+        assert!(!rules.allows(w("shone")));         // Only one E
+
+        // Black copies of a letter revealed alongside a yellow one only require one copy
+        let rules = HardMode::from_guesses(&wv("daddy"), w("bread"));
+        assert!(rules.allows(w("drama")));
+        assert!(!rules.allows(w("dowdy")));         // Missing A
+
+        // Rules accumulate across guesses
+        let rules = HardMode::from_guesses(&wv("parse, clint"), w("fatal"));
+        assert!(rules.allows(w("fatal")));
+        assert!(!rules.allows(w("basal")));         // Missing T
+    }
+
+}
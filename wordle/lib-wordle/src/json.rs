@@ -0,0 +1,404 @@
+use std::fmt::{Display, Formatter};
+
+/// A JSON value, for exchanging trees and reports with other tools and the web front ends.
+///  Objects keep their keys in the order written, so output is stable and diffable.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse JSON text. Errors are "@(line, char) "c": message", like the tree parser's.
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut reader = Reader { text, position: 0 };
+        let value = reader.value()?;
+
+        reader.skip_whitespace();
+        if reader.position < text.len() {
+            return Err(reader.error("Unexpected content after the JSON value"));
+        }
+
+        Ok(value)
+    }
+
+    /// An object from (key, value) pairs
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self { Json::Number(value) => Some(*value), _ => None }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_f64().filter(|v| *v >= 0.0 && v.fract() == 0.0).map(|v| v as usize)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { Json::Bool(value) => Some(*value), _ => None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Json::String(value) => Some(value), _ => None }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self { Json::Array(values) => Some(values), _ => None }
+    }
+
+    /// A required field, or an error naming it
+    pub fn field(&self, key: &str) -> Result<&Json, String> {
+        self.get(key).ok_or_else(|| format!("Missing '{key}'"))
+    }
+
+    pub fn usize_field(&self, key: &str) -> Result<usize, String> {
+        self.field(key)?.as_usize().ok_or_else(|| format!("'{key}' must be a whole number"))
+    }
+
+    pub fn f64_field(&self, key: &str) -> Result<f64, String> {
+        self.field(key)?.as_f64().ok_or_else(|| format!("'{key}' must be a number"))
+    }
+
+    pub fn str_field(&self, key: &str) -> Result<&str, String> {
+        self.field(key)?.as_str().ok_or_else(|| format!("'{key}' must be a string"))
+    }
+
+    pub fn array_field(&self, key: &str) -> Result<&Vec<Json>, String> {
+        self.field(key)?.as_array().ok_or_else(|| format!("'{key}' must be an array"))
+    }
+
+    /// Write with each array item and object field on its own line, indented by two spaces per level.
+    ///  Arrays of numbers or strings stay on one line.
+    pub fn to_pretty_string(&self) -> String {
+        let mut result = String::new();
+        self.write(Some(0), &mut result);
+        result
+    }
+
+    fn write(&self, indent: Option<usize>, result: &mut String) {
+        match self {
+            Json::Null => result.push_str("null"),
+            Json::Bool(value) => result.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => write_number(*value, result),
+            Json::String(value) => write_string(value, result),
+            Json::Array(values) => {
+                let is_flat = values.iter().all(|v| !matches!(v, Json::Array(_) | Json::Object(_)));
+                let inner = if is_flat { None } else { indent.map(|i| i + 1) };
+
+                result.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 { result.push(','); }
+                    new_line(inner, result);
+                    value.write(inner, result);
+                }
+                if !values.is_empty() && inner.is_some() { new_line(indent, result); }
+                result.push(']');
+            },
+            Json::Object(fields) => {
+                let inner = indent.map(|i| i + 1);
+
+                result.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 { result.push(','); }
+                    new_line(inner, result);
+                    write_string(key, result);
+                    result.push(':');
+                    if inner.is_some() { result.push(' '); }
+                    value.write(inner, result);
+                }
+                if !fields.is_empty() { new_line(indent, result); }
+                result.push('}');
+            }
+        }
+    }
+}
+
+impl Display for Json {
+    /// Write compactly, on one line
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut result = String::new();
+        self.write(None, &mut result);
+        f.write_str(&result)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Json { Json::Number(value as f64) }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Json { Json::Number(value) }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Json { Json::Bool(value) }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Json { Json::String(value.to_string()) }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Json { Json::String(value) }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json { value.map(|v| v.into()).unwrap_or(Json::Null) }
+}
+
+fn new_line(indent: Option<usize>, result: &mut String) {
+    if let Some(indent) = indent {
+        result.push('\n');
+        for _ in 0..indent { result.push_str("  "); }
+    }
+}
+
+fn write_number(value: f64, result: &mut String) {
+    if !value.is_finite() {
+        result.push_str("null");
+    } else if value.fract() == 0.0 && value.abs() < 1e15 {
+        result.push_str(&format!("{}", value as i64));
+    } else {
+        // Rust writes the shortest text which reads back as the same f64
+        result.push_str(&format!("{value}"));
+    }
+}
+
+fn write_string(value: &str, result: &mut String) {
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    position: usize,    // Byte offset of the next character
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        let before = &self.text[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let char_in_line = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        let current = self.peek().map(|c| c.to_string()).unwrap_or_default();
+        format!("@({line}, {char_in_line}) \"{current}\": {message}")
+    }
+
+    fn require(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{expected}'")))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) => {
+                for (literal, value) in [("null", Json::Null), ("true", Json::Bool(true)), ("false", Json::Bool(false))] {
+                    if self.text[self.position..].starts_with(literal) {
+                        self.position += literal.len();
+                        return Ok(value);
+                    }
+                }
+
+                Err(self.error("Expected a JSON value"))
+            },
+            None => Err(self.error("Out of content when more expected.")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.require('{')?;
+        let mut fields = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') { return Err(self.error("Expected a quoted key")); }
+            let key = self.string()?;
+
+            self.require(':')?;
+            fields.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => { self.position += 1; return Ok(Json::Object(fields)); },
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.require('[')?;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => { self.position += 1; return Ok(Json::Array(values)); },
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+            self.position += c.len_utf8();
+        }
+
+        match self.text[start..self.position].parse::<f64>() {
+            Ok(value) => Ok(Json::Number(value)),
+            Err(_) => {
+                self.position = start;
+                Err(self.error("Not a valid number"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.require('"')?;
+        let mut result = String::new();
+
+        loop {
+            let Some(c) = self.peek() else { return Err(self.error("Unterminated string")); };
+            self.position += c.len_utf8();
+
+            match c {
+                '"' => return Ok(result),
+                '\\' => {
+                    let Some(escaped) = self.peek() else { return Err(self.error("Unterminated string")); };
+                    self.position += escaped.len_utf8();
+
+                    match escaped {
+                        '"' | '\\' | '/' => result.push(escaped),
+                        'b' => result.push('\u{8}'),
+                        'f' => result.push('\u{c}'),
+                        'n' => result.push('\n'),
+                        'r' => result.push('\r'),
+                        't' => result.push('\t'),
+                        'u' => {
+                            let mut code = self.hex4()?;
+
+                            // Characters outside the basic plane are written as a surrogate pair
+                            if (0xd800..0xdc00).contains(&code) && self.text[self.position..].starts_with("\\u") {
+                                self.position += 2;
+                                let low = self.hex4()?;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+
+                            result.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        },
+                        _ => {
+                            self.position -= escaped.len_utf8();
+                            return Err(self.error("Not a valid escape"));
+                        }
+                    }
+                },
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.text.get(self.position..self.position + 4).and_then(|d| u32::from_str_radix(d, 16).ok());
+        match digits {
+            Some(code) => {
+                self.position += 4;
+                Ok(code)
+            },
+            None => Err(self.error("Expected four hex digits after '\\u'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let text = r#"{"name": "soare", "turns": 3.421, "count": 2315, "empty": [], "nested": {"ok": true, "none": null}, "escaped": "a\"b\\c\né😀"}"#;
+        let value = Json::parse(text).unwrap();
+
+        assert_eq!(value.str_field("name").unwrap(), "soare");
+        assert_eq!(value.f64_field("turns").unwrap(), 3.421);
+        assert_eq!(value.usize_field("count").unwrap(), 2315);
+        assert_eq!(value.field("nested").unwrap().get("ok"), Some(&Json::Bool(true)));
+        assert_eq!(value.str_field("escaped").unwrap(), "a\"b\\c\né😀");
+        assert!(value.usize_field("turns").is_err());
+        assert_eq!(value.field("missing").err().unwrap(), "Missing 'missing'");
+
+        // Compact and pretty output both read back the same
+        assert_eq!(Json::parse(&value.to_string()).unwrap(), value);
+        assert_eq!(Json::parse(&value.to_pretty_string()).unwrap(), value);
+        assert_eq!(value.to_string(), r#"{"name":"soare","turns":3.421,"count":2315,"empty":[],"nested":{"ok":true,"none":null},"escaped":"a\"b\\c\né😀"}"#);
+
+        let value = Json::object(vec![("list", Json::Array(vec![1usize.into(), 2usize.into()])), ("rows", Json::Array(vec![Json::object(vec![("a", 0.5.into())])]))]);
+        assert_eq!(value.to_pretty_string(), "{\n  \"list\": [1,2],\n  \"rows\": [\n    {\n      \"a\": 0.5\n    }\n  ]\n}");
+    }
+
+    #[test]
+    fn json_errors() {
+        assert_eq!(Json::parse("{\"a\": 1,\n  \"b\" 2}").err().unwrap(), "@(2, 7) \"2\": Expected ':'");
+        assert_eq!(Json::parse("[1, 2").err().unwrap(), "@(1, 6) \"\": Expected ',' or ']'");
+        assert_eq!(Json::parse("[1] x").err().unwrap(), "@(1, 5) \"x\": Unexpected content after the JSON value");
+        assert_eq!(Json::parse("nope").err().unwrap(), "@(1, 1) \"n\": Expected a JSON value");
+    }
+}
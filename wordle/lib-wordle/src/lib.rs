@@ -3,17 +3,24 @@ use word::Word;
 
 pub mod analyze;
 pub mod check;
-pub mod bit_vector_slice;
+pub mod bit_vector;
 pub mod clubs;
+pub mod compare;
 pub mod cluster_vector;
+pub mod hard_mode;
+pub mod json;
 pub mod letter_orders;
+pub mod objective;
 pub mod parser;
 pub mod rank;
 pub mod response;
+pub mod response_matrix;
 pub mod scrappy;
 pub mod search;
 pub mod single_use;
+pub mod solver;
 pub mod state;
+pub mod weights;
 pub mod word;
 pub mod wordle_tree;
 
@@ -0,0 +1,289 @@
+use std::{cmp::Ordering, collections::HashMap, fmt::{Display, Formatter}, hash::Hash};
+use crate::{word::Word, weights::AnswerWeights};
+
+/// Games not solved within this many turns are lost.
+pub const MAX_TURNS: usize = 6;
+
+// Differences smaller than this are rounding error from adding expected (fractional) counts in different orders
+const EPSILON: f64 = 0.000001;
+
+/// What a strategy search should minimize.
+///  Every objective breaks ties by total turns. Each is monotone (solving any answer sooner is never worse),
+///  so the ideal turns for a guess are always a lower bound and can be used to prune searches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Objective {
+    #[default]
+    TotalTurns,             // Fewest total turns (best average)
+    Failures,               // Fewest games taking more than MAX_TURNS, then fewest total turns
+    MaxTurns,               // Fewest turns for the worst-case answer, then fewest answers at each turn count from the worst up
+    SolvedWithin(usize),    // Most games solved within N turns, then fewest total turns
+}
+
+impl Objective {
+    /// Parse an objective name: 'turns', 'failures', 'max', or 'within:N'
+    pub fn parse(text: &str) -> Result<Objective, String> {
+        match text.to_ascii_lowercase().as_str() {
+            "turns" => Ok(Objective::TotalTurns),
+            "failures" => Ok(Objective::Failures),
+            "max" => Ok(Objective::MaxTurns),
+            other => {
+                if let Some(turns) = other.strip_prefix("within:") {
+                    match turns.parse::<usize>() {
+                        Ok(turns) if turns > 0 => Ok(Objective::SolvedWithin(turns)),
+                        _ => Err(format!("'{turns}' is not a valid turn count for objective 'within:N'."))
+                    }
+                } else {
+                    Err(format!("Unknown objective '{text}'. Use 'turns', 'failures', 'max', or 'within:N'."))
+                }
+            }
+        }
+    }
+
+    /// Compare two turn distributions for the same answers; Less means 'left' is better.
+    pub fn compare(&self, left: &TurnCounts, right: &TurnCounts) -> Ordering {
+        let primary = match self {
+            Objective::TotalTurns => Ordering::Equal,
+            Objective::Failures => compare_f64(left.unsolved_within(MAX_TURNS), right.unsolved_within(MAX_TURNS)),
+            Objective::SolvedWithin(turns) => compare_f64(left.unsolved_within(*turns), right.unsolved_within(*turns)),
+            Objective::MaxTurns => {
+                let length = left.counts.len().max(right.counts.len());
+                (0..length).rev()
+                    .map(|i| compare_f64(left.counts.get(i).copied().unwrap_or(0.0), right.counts.get(i).copied().unwrap_or(0.0)))
+                    .find(|o| *o != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            }
+        };
+
+        primary.then_with(|| compare_f64(left.total_turns(), right.total_turns()))
+    }
+
+    /// Rank a set of clusters reached after 'turns_before' guesses; lower is better.
+    ///  The objective is estimated from TurnCounts::predicted for each cluster, and ties are broken by 'ranker'.
+    ///  With weights, each cluster counts in proportion to how likely its answers are.
+    pub fn rank_map<T: Clone + Eq + Hash>(&self, map: &HashMap<T, Vec<Word>>, turns_before: usize, ranker: fn(&HashMap<T, Vec<Word>>) -> usize, weights: Option<&AnswerWeights>) -> u64 {
+        // Weight the objective so that it always outranks the tie-breaker (under a million total turns); u64 so this can't overflow on 32-bit targets
+        const WEIGHT: u64 = 1_000_000;
+
+        let predicted = || {
+            let mut counts = TurnCounts::new();
+            for (_, cluster) in map.iter() {
+                let predicted = TurnCounts::predicted(cluster.len(), turns_before);
+                match weights {
+                    Some(weights) => counts.add_counts(&weights.scale_counts(&predicted, cluster)),
+                    None => counts.add_counts(&predicted)
+                }
+            }
+            counts
+        };
+
+        let primary = match self {
+            Objective::TotalTurns => 0,
+            Objective::Failures => (predicted().unsolved_within(MAX_TURNS) * 1000.0).round() as u64,
+            Objective::SolvedWithin(turns) => (predicted().unsolved_within(*turns) * 1000.0).round() as u64,
+            Objective::MaxTurns => predicted().max_turns() as u64,
+        };
+
+        let secondary = match weights {
+            Some(weights) => weights.rank_map(map, ranker),
+            None => ranker(map)
+        };
+
+        primary * WEIGHT + secondary as u64
+    }
+}
+
+impl Display for Objective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Objective::TotalTurns => f.write_str("turns"),
+            Objective::Failures => f.write_str("failures"),
+            Objective::MaxTurns => f.write_str("max"),
+            Objective::SolvedWithin(turns) => write!(f, "within:{turns}"),
+        }
+    }
+}
+
+/// The (expected) number of answers solved on each turn.
+///  Counts are fractional so that random guessing can be described by its expected outcome.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct TurnCounts {
+    // counts[i] is the number of answers solved on turn i + 1
+    counts: Vec<f64>
+}
+
+impl TurnCounts {
+    pub fn new() -> TurnCounts {
+        TurnCounts { counts: Vec::new() }
+    }
+
+    /// A single answer solved on 'turn'
+    pub fn solved_on(turn: usize) -> TurnCounts {
+        let mut result = TurnCounts::new();
+        result.add(turn, 1.0);
+        result
+    }
+
+    /// The ideal outcome for a cluster after 'turns_before' guesses: one answer guessed next and every other answer the turn after.
+    ///  No strategy can do better for any objective.
+    pub fn ideal(cluster_size: usize, turns_before: usize) -> TurnCounts {
+        let mut result = TurnCounts::new();
+        if cluster_size > 0 {
+            result.add(turns_before + 1, 1.0);
+            result.add(turns_before + 2, (cluster_size - 1) as f64);
+        }
+        result
+    }
+
+    /// A rough estimate of the outcome for a cluster after 'turns_before' guesses, for ranking guesses before play is known.
+    ///  Pairs take one or two more turns. Bigger clusters solve one answer next, then half of the rest on each of the following two turns.
+    pub fn predicted(cluster_size: usize, turns_before: usize) -> TurnCounts {
+        let mut result = TurnCounts::new();
+
+        match cluster_size {
+            0 => {},
+            1 | 2 => result = TurnCounts::ideal(cluster_size, turns_before),
+            _ => {
+                let rest = (cluster_size - 1) as f64;
+                result.add(turns_before + 1, 1.0);
+                result.add(turns_before + 2, rest / 2.0);
+                result.add(turns_before + 3, rest / 2.0);
+            }
+        }
+
+        result
+    }
+
+    pub fn add(&mut self, turn: usize, count: f64) {
+        if turn == 0 || count == 0.0 { return; }
+        while self.counts.len() < turn { self.counts.push(0.0); }
+        self.counts[turn - 1] += count;
+    }
+
+    pub fn add_counts(&mut self, other: &TurnCounts) {
+        for (i, count) in other.counts.iter().enumerate() {
+            self.add(i + 1, *count);
+        }
+    }
+
+    /// Add another distribution, scaled (to weight an outcome by its probability)
+    pub fn add_scaled(&mut self, other: &TurnCounts, scale: f64) {
+        for (i, count) in other.counts.iter().enumerate() {
+            self.add(i + 1, *count * scale);
+        }
+    }
+
+    /// The same distribution with every answer solved 'turns' turns later
+    pub fn shifted(&self, turns: usize) -> TurnCounts {
+        let mut result = TurnCounts::new();
+        for (i, count) in self.counts.iter().enumerate() {
+            result.add(i + 1 + turns, *count);
+        }
+        result
+    }
+
+    /// Expected answers solved on 'turn'
+    pub fn get(&self, turn: usize) -> f64 {
+        if turn == 0 { return 0.0; }
+        self.counts.get(turn - 1).copied().unwrap_or(0.0)
+    }
+
+    pub fn answer_count(&self) -> f64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn total_turns(&self) -> f64 {
+        // Not .sum(): f64 sums start at -0.0, so nodes no games reach (in evaluate) would show "-0" turns
+        self.counts.iter().enumerate().fold(0.0, |total, (i, count)| total + (i + 1) as f64 * count)
+    }
+
+    /// Expected answers solved within 'turns' turns
+    pub fn solved_within(&self, turns: usize) -> f64 {
+        self.counts.iter().take(turns).sum()
+    }
+
+    /// Expected answers taking more than 'turns' turns
+    pub fn unsolved_within(&self, turns: usize) -> f64 {
+        self.counts.iter().skip(turns).sum()
+    }
+
+    /// Expected games lost (taking more than MAX_TURNS)
+    pub fn failures(&self) -> f64 {
+        self.unsolved_within(MAX_TURNS)
+    }
+
+    /// The most turns any answer might take
+    pub fn max_turns(&self) -> usize {
+        self.counts.iter().rposition(|count| *count > EPSILON).map(|i| i + 1).unwrap_or(0)
+    }
+}
+
+impl Display for TurnCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let counts = self.counts.iter().map(|c| crate::write_turns(*c, 1.0, false)).collect::<Vec<String>>();
+        write!(f, "[{}]", counts.join(", "))
+    }
+}
+
+fn compare_f64(left: f64, right: f64) -> Ordering {
+    if (left - right).abs() < EPSILON {
+        Ordering::Equal
+    } else if left < right {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turn_counts() {
+        let mut counts = TurnCounts::ideal(4, 2);
+        counts.add(7, 0.5);
+        assert_eq!(counts.to_string(), "[0, 0, 1, 3, 0, 0, 0.5]");
+        assert_eq!(counts.answer_count(), 4.5);
+        assert_eq!(counts.total_turns(), 3.0 + 12.0 + 3.5);
+        assert_eq!(counts.solved_within(3), 1.0);
+        assert_eq!(counts.failures(), 0.5);
+        assert_eq!(counts.max_turns(), 7);
+        assert_eq!(counts.shifted(1).to_string(), "[0, 0, 0, 1, 3, 0, 0, 0.5]");
+
+        // Predicted: pairs are ideal; bigger clusters spread over three turns
+        assert_eq!(TurnCounts::predicted(2, 1), TurnCounts::ideal(2, 1));
+        assert_eq!(TurnCounts::predicted(5, 0).to_string(), "[1, 2, 2]");
+    }
+
+    #[test]
+    fn objectives() {
+        // 'fast' has the best average but loses a game; 'safe' never fails
+        let mut fast = TurnCounts::new();
+        fast.add(2, 3.0);
+        fast.add(7, 1.0);
+
+        let mut safe = TurnCounts::new();
+        safe.add(3, 2.0);
+        safe.add(4, 2.0);
+
+        assert_eq!(Objective::TotalTurns.compare(&fast, &safe), Ordering::Less);
+        assert_eq!(Objective::Failures.compare(&fast, &safe), Ordering::Greater);
+        assert_eq!(Objective::MaxTurns.compare(&fast, &safe), Ordering::Greater);
+        assert_eq!(Objective::SolvedWithin(2).compare(&fast, &safe), Ordering::Less);
+        assert_eq!(Objective::SolvedWithin(4).compare(&fast, &safe), Ordering::Greater);
+
+        // Ties are broken by total turns
+        let mut slow = safe.clone();
+        slow.add(4, 1.0);
+        slow.add(3, -1.0);
+        assert_eq!(Objective::Failures.compare(&safe, &slow), Ordering::Less);
+
+        // Parse and Display round-trip
+        for text in ["turns", "failures", "max", "within:4"] {
+            assert_eq!(Objective::parse(text).unwrap().to_string(), text);
+        }
+
+        assert!(Objective::parse("within:0").is_err());
+        assert!(Objective::parse("fastest").is_err());
+    }
+}
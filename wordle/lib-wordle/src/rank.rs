@@ -1,5 +1,5 @@
 use std::{collections::{HashMap, HashSet}, mem};
-use crate::{word::{Word, self}, response::{Response, ResponseSet}, cluster_vector::ClusterVector};
+use crate::{word::{Word, self}, response::{Response, ResponseSet}, cluster_vector::ClusterVector, objective::TurnCounts};
 
 /// Split a cluster given a next guess - return a map of each distinct response and the answers which get that response.
 pub fn split(cluster: &Vec<Word>, guess: Word, map: &mut HashMap<Response, Vec<Word>>) {
@@ -127,6 +127,58 @@ pub fn total_turns_ideal_map<T>(map: &HashMap<T, Vec<Word>>) -> usize {
     ClusterVector::from_map(map).total_turns_ideal()
 }
 
+/// Rank by entropy. (Thousandths of a bit short of identifying every answer; see ClusterVector::entropy)
+pub fn entropy_map<T>(map: &HashMap<T, Vec<Word>>) -> usize {
+    ClusterVector::from_map(map).rank_entropy()
+}
+
+/// Rank by expected answers remaining, in thousandths.
+pub fn expected_remaining_map<T>(map: &HashMap<T, Vec<Word>>) -> usize {
+    ClusterVector::from_map(map).rank_expected_remaining()
+}
+
+/// Rank by entropy, summed over clusters so it can be weighted. (See ClusterVector::rank_entropy_total)
+pub fn entropy_total_map<T>(map: &HashMap<T, Vec<Word>>) -> usize {
+    ClusterVector::from_map(map).rank_entropy_total()
+}
+
+/// Rank by expected answers remaining, summed over clusters so it can be weighted.
+pub fn expected_remaining_total_map<T>(map: &HashMap<T, Vec<Word>>) -> usize {
+    ClusterVector::from_map(map).rank_expected_remaining_total()
+}
+
+/// Rank by distinct responses. (Answers not alone in their cluster)
+pub fn cluster_count_map<T>(map: &HashMap<T, Vec<Word>>) -> usize {
+    ClusterVector::from_map(map).rank_cluster_count()
+}
+
+/// A ranking function for the clusters after a guess; lower is better.
+pub type MapRanker<T> = fn(&HashMap<T, Vec<Word>>) -> usize;
+
+/// Find a cluster map ranker by name, for choosing one from the command line.
+pub fn map_ranker<T>(name: &str) -> Result<MapRanker<T>, String> {
+    match name {
+        "predicted" => Ok(total_turns_predicted_map),
+        "pessimistic" => Ok(total_turns_pessimistic_map),
+        "ideal" => Ok(total_turns_ideal_map),
+        "random" => Ok(total_turns_random_map),
+        "entropy" => Ok(entropy_map),
+        "remaining" => Ok(expected_remaining_map),
+        "clusters" => Ok(cluster_count_map),
+        _ => Err(format!("Unknown ranker '{name}'. Use 'predicted', 'pessimistic', 'ideal', 'random', 'entropy', 'remaining', or 'clusters'."))
+    }
+}
+
+/// Find a ranker by name for weighted answers (see AnswerWeights::rank_map), which needs rankers summing over clusters.
+///  The entropy and remaining rankers are swapped for their totals; the others already sum over clusters.
+pub fn weighted_map_ranker<T>(name: &str) -> Result<MapRanker<T>, String> {
+    match name {
+        "entropy" => Ok(entropy_total_map),
+        "remaining" => Ok(expected_remaining_total_map),
+        _ => map_ranker(name),
+    }
+}
+
 pub fn total_turns_random(cluster: &Vec<Word>) -> f64 {
     if cluster.len() == 1 {
         // Singles will be guessed the next turn
@@ -156,6 +208,82 @@ pub fn total_turns_random(cluster: &Vec<Word>) -> f64 {
     }
 }
 
+/// Compute total_turns_random, remembering the result for each cluster seen. Much faster for big clusters, which split into many of the same subclusters.
+pub fn total_turns_random_cached(cluster: &Vec<Word>, cache: &mut HashMap<Vec<Word>, f64>) -> f64 {
+    if cluster.len() <= 2 {
+        return total_turns_random(cluster);
+    }
+
+    if let Some(turns) = cache.get(cluster) {
+        return *turns;
+    }
+
+    let mut total = 0.0;
+    let mut map: HashMap<Response, Vec<Word>> = HashMap::new();
+
+    for guess in cluster.iter() {
+        split(cluster, *guess, &mut map);
+
+        for (_, subcluster) in map.iter() {
+            total += total_turns_random_cached(subcluster, cache);
+        }
+    }
+
+    let turns = (cluster.len() as f64) + (total / (cluster.len() as f64));
+    cache.insert(cluster.clone(), turns);
+    turns
+}
+
+/// Compute the expected number of answers solved on each turn with random in-cluster guessing, after 'turns_before' guesses.
+///  Matches total_turns_random, but keeps the distribution so that it can be ranked by any Objective.
+pub fn turn_counts_random(cluster: &Vec<Word>, turns_before: usize) -> TurnCounts {
+    if cluster.len() < 3 {
+        return TurnCounts::ideal(cluster.len(), turns_before);
+    }
+
+    // Each answer is guessed next with equal odds; the rest are split by that guess
+    let mut result = TurnCounts::solved_on(turns_before + 1);
+    let mut map: HashMap<Response, Vec<Word>> = HashMap::new();
+    let odds = 1.0 / (cluster.len() as f64);
+
+    for guess in cluster.iter() {
+        split(cluster, *guess, &mut map);
+
+        for (_, subcluster) in map.iter() {
+            result.add_scaled(&turn_counts_random(subcluster, turns_before + 1), odds);
+        }
+    }
+
+    result
+}
+
+/// Compute turn_counts_random, remembering the distribution (from turn zero) for each cluster seen.
+pub fn turn_counts_random_cached(cluster: &Vec<Word>, turns_before: usize, cache: &mut HashMap<Vec<Word>, TurnCounts>) -> TurnCounts {
+    if cluster.len() < 3 {
+        return TurnCounts::ideal(cluster.len(), turns_before);
+    }
+
+    if let Some(counts) = cache.get(cluster) {
+        return counts.shifted(turns_before);
+    }
+
+    let mut result = TurnCounts::solved_on(1);
+    let mut map: HashMap<Response, Vec<Word>> = HashMap::new();
+    let odds = 1.0 / (cluster.len() as f64);
+
+    for guess in cluster.iter() {
+        split(cluster, *guess, &mut map);
+
+        for (_, subcluster) in map.iter() {
+            result.add_scaled(&turn_counts_random_cached(subcluster, 1, cache), odds);
+        }
+    }
+
+    let counts = result.shifted(turns_before);
+    cache.insert(cluster.clone(), result);
+    counts
+}
+
 pub fn total_turns_random_map<T>(clusters: &HashMap<T, Vec<Word>>) -> usize {
     total_turns_random_map_exact(clusters) as usize
 }
@@ -339,6 +467,12 @@ mod tests {
         //   (1 + (3 * (1 + 5) / 3) = 9 turns;
         //  So 3 * 7 + 1 * 9 = 30 / 4 options = 7.5 total on average.
         assert_eq!(super::total_turns_random(&vec![w("booze"), w("dodge"), w("gouge"), w("vogue")]), 7.5);
+
+        // Distribution matches the total: booze first (1 + 2 + 3 + 3) or another (1 + 2 + 2 + 2), after two turns
+        let counts = super::turn_counts_random(&vec![w("booze"), w("dodge"), w("gouge"), w("vogue")], 2);
+        assert_eq!(counts.to_string(), "[0, 0, 1, 2.5, 0.5]");
+        assert_eq!(counts.total_turns(), 7.5 + 8.0);
+        assert_eq!(super::turn_counts_random_cached(&vec![w("booze"), w("dodge"), w("gouge"), w("vogue")], 2, &mut HashMap::new()), counts);
     }
 
     #[test]
@@ -407,6 +541,28 @@ mod tests {
 
     }
 
+    #[test]
+    fn information_rankers() {
+        let cluster = vec![w("scamp"), w("shack"), w("smack"), w("snack")];
+        let mut map = HashMap::new();
+
+        // DUMPY splits off SCAMP and SMACK, leaving SHACK and SNACK together; CHAMP identifies every answer
+        super::split(&cluster, w("dumpy"), &mut map);
+        assert_eq!(super::cluster_count_map(&map), 1);
+        assert_eq!(super::expected_remaining_map(&map), 1500);
+        assert_eq!(super::entropy_map(&map), 500);
+
+        super::split(&cluster, w("champ"), &mut map);
+        assert_eq!(super::cluster_count_map(&map), 0);
+        assert_eq!(super::expected_remaining_map(&map), 1000);
+        assert_eq!(super::entropy_map(&map), 0);
+
+        // Rankers are found by name
+        assert_eq!(super::map_ranker::<Response>("entropy").unwrap()(&map), 0);
+        assert_eq!(super::weighted_map_ranker::<Response>("remaining").unwrap()(&map), 4);
+        assert!(super::map_ranker::<Response>("fastest").is_err());
+    }
+
     #[test]
     fn split() {
         let cluster = vec![w("scamp"), w("shack"), w("smack")];
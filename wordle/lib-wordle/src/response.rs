@@ -79,6 +79,24 @@ impl Response {
         ResponseIterator { value: self.value, shift: 10u8 }
     }
 
+    /// Convert to a compact index (0..243), treating each tile as a base-three digit
+    pub fn to_index(&self) -> u8 {
+        self.iter().fold(0u8, |index, tile| index * 3 + tile as u8)
+    }
+
+    /// Convert a compact index (0..243) back to a Response
+    pub fn from_index(index: u8) -> Response {
+        let mut value = 0u16;
+        let mut divisor = 81u8;
+
+        for _ in 0..5 {
+            value = (value << 2) | ((index / divisor) % 3) as u16;
+            divisor /= 3;
+        }
+
+        Response { value }
+    }
+
     // Count the number of non-black tiles returned
     pub fn known_count(&self) -> u8 {
         let mut count = 0u8;
@@ -256,6 +274,7 @@ impl std::fmt::Debug for ResponseSet {
     }
 }
 
+/// Tracks only which letters must or must not appear in the answer. Use Knowledge to filter answers exactly.
 pub struct Constraint {
     must_have_letters: u32,
     must_not_have_letters: u32
@@ -298,9 +317,139 @@ impl Constraint {
     }
 }
 
+/// Tracks everything known about the answer from guesses and responses so far:
+///  green letters, letters known not to be in each position, and the minimum and maximum copies of each letter.
+///  Unlike Constraint, a word is possible exactly when it would have given every response seen.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Knowledge {
+    greens: [Option<u8>; 5],
+    not_here: [u32; 5],
+    min_counts: [u8; 26],
+    max_counts: [u8; 26]
+}
+
+impl Knowledge {
+    pub fn new() -> Knowledge {
+        Knowledge { greens: [None; 5], not_here: [0u32; 5], min_counts: [0u8; 26], max_counts: [5u8; 26] }
+    }
+
+    pub fn add(&mut self, guess: Word, response: Response) {
+        let mut known_counts = [0u8; 26];
+        let mut had_black = 0u32;
+
+        for (i, (tile, c)) in response.iter().zip(guess.iter_index()).enumerate() {
+            match tile {
+                Tile::Green => {
+                    self.greens[i] = Some(c);
+                    known_counts[c as usize] += 1;
+                },
+                Tile::Yellow => {
+                    self.not_here[i] |= 1u32 << c;
+                    known_counts[c as usize] += 1;
+                },
+                Tile::Black => {
+                    // A black tile means this letter isn't here (or it would be green), and there are no more copies than were marked
+                    self.not_here[i] |= 1u32 << c;
+                    had_black |= 1u32 << c;
+                }
+            }
+        }
+
+        for c in guess.iter_index() {
+            let c = c as usize;
+            self.min_counts[c] = self.min_counts[c].max(known_counts[c]);
+
+            if had_black & (1u32 << c) != 0 {
+                self.max_counts[c] = self.max_counts[c].min(known_counts[c]);
+            }
+        }
+    }
+
+    /// Return whether 'word' could still be the answer (whether it would have given every response added)
+    pub fn is_possible(&self, word: Word) -> bool {
+        let mut counts = [0u8; 26];
+
+        for (i, c) in word.iter_index().enumerate() {
+            if let Some(green) = self.greens[i] {
+                if green != c { return false; }
+            } else if self.not_here[i] & (1u32 << c) != 0 {
+                return false;
+            }
+
+            counts[c as usize] += 1;
+        }
+
+        counts.iter().zip(self.min_counts.iter().zip(self.max_counts.iter())).all(|(count, (min, max))| count >= min && count <= max)
+    }
+
+    /// Summarize what is known ("S.A.E, has r (not pos 4), exactly one e").
+    ///  Greens are shown in place; other required letters, known letter counts, and positions ruled out for them follow.
+    pub fn to_knowns_string(&self) -> String {
+        let mut result = String::new();
+        let mut green_counts = [0u8; 26];
+
+        for green in self.greens.iter() {
+            if let Some(c) = green {
+                result.push((b'A' + c) as char);
+                green_counts[*c as usize] += 1;
+            } else {
+                result.push('.');
+            }
+        }
+
+        for c in 0..26u8 {
+            let (min, max) = (self.min_counts[c as usize], self.max_counts[c as usize]);
+            if min == 0 { continue; }
+
+            let letter = (b'a' + c) as char;
+            let extra = min - green_counts[c as usize];
+
+            let mut part = if min == max {
+                format!("exactly {} {letter}", count_name(min))
+            } else if extra > 1 {
+                format!("has {} {letter}", count_name(extra))
+            } else if extra > 0 {
+                format!("has {letter}")
+            } else {
+                continue;
+            };
+
+            // Show the positions ruled out for letters which aren't all placed yet
+            if extra > 0 {
+                let positions = (0..5).filter(|i| self.greens[*i].is_none() && self.not_here[*i] & (1u32 << c) != 0).map(|i| (i + 1).to_string()).collect::<Vec<String>>();
+                if !positions.is_empty() {
+                    part += &format!(" (not pos {})", positions.join(", "));
+                }
+            }
+
+            result += ", ";
+            result += &part;
+        }
+
+        result
+    }
+}
+
+impl Default for Knowledge {
+    fn default() -> Self {
+        Knowledge::new()
+    }
+}
+
+fn count_name(count: u8) -> &'static str {
+    match count {
+        0 => "no",
+        1 => "one",
+        2 => "two",
+        3 => "three",
+        4 => "four",
+        _ => "five"
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{*, response::{ResponseSet, Constraint}};
+    use crate::{*, response::{ResponseSet, Constraint, Knowledge}};
     use super::{Response, Tile};
 
     #[test]
@@ -432,6 +581,79 @@ mod tests {
         assert_eq!(constraint.matches(w("abcdd")), true);
     }
 
+    #[test]
+    fn response_index() {
+        assert_eq!(r("bbbbb").to_index(), 0);
+        assert_eq!(r("bbbbg").to_index(), 2);
+        assert_eq!(r("ybbbb").to_index(), 81);
+        assert_eq!(r("ggggg").to_index(), 242);
+
+        // Every response round-trips through its index
+        for index in 0..243u8 {
+            assert_eq!(Response::from_index(index).to_index(), index);
+        }
+        assert_eq!(Response::from_index(242).value, super::ALL_GREEN);
+    }
+
+    #[test]
+    fn knowledge() {
+        let answer = w("abcde");
+        let mut knowledge = Knowledge::new();
+        assert!(knowledge.is_possible(w("zzzzz")));
+        assert_eq!(knowledge.to_knowns_string(), ".....");
+
+        // A green, b and c elsewhere, no x or y
+        knowledge.add(w("acbxy"), Response::score(w("acbxy"), answer));
+        assert!(knowledge.is_possible(answer));
+        assert!(!knowledge.is_possible(w("aaabb")));    // No c
+        assert!(!knowledge.is_possible(w("abcnx")));    // Has x
+        assert!(!knowledge.is_possible(w("bacde")));    // A not green
+        assert!(!knowledge.is_possible(w("acdeb")));    // C yellow in pos 2
+        assert_eq!(knowledge.to_knowns_string(), "A...., has b (not pos 3), has c (not pos 2)");
+
+        // "daddy": one d (green in pos 4); the other two are black
+        knowledge.add(w("daddy"), Response::score(w("daddy"), answer));
+        assert!(knowledge.is_possible(answer));
+        assert!(!knowledge.is_possible(w("daddy")));
+
+        // Unlike Constraint, Knowledge knows there is only one d
+        assert!(!knowledge.is_possible(w("abcdd")));
+        assert_eq!(knowledge.to_knowns_string(), "A..D., has b (not pos 3), has c (not pos 2), exactly one d");
+
+        // Repeat letter counts: "geese" for "sheep" has exactly two Es (one green, one yellow, one black), and no G
+        let mut knowledge = Knowledge::new();
+        knowledge.add(w("geese"), Response::score(w("geese"), w("sheep")));
+        assert!(knowledge.is_possible(w("sheep")));
+        assert!(!knowledge.is_possible(w("shelf")));    // Only one e
+        assert!(!knowledge.is_possible(w("emcee")));    // Three e
+        assert_eq!(knowledge.to_knowns_string(), "..E.., exactly two e (not pos 2, 5), has s (not pos 4)");
+
+        // Knowledge accumulates across guesses; GEESE shows there's only one e in "shale"
+        let mut knowledge = Knowledge::new();
+        knowledge.add(w("stare"), Response::score(w("stare"), w("shale")));
+        assert_eq!(knowledge.to_knowns_string(), "S.A.E");
+        knowledge.add(w("geese"), Response::score(w("geese"), w("shale")));
+        assert_eq!(knowledge.to_knowns_string(), "S.A.E, exactly one e");
+    }
+
+    #[test]
+    fn knowledge_matches_score() {
+        // Verify Knowledge.is_possible is exactly equivalent to re-scoring against every answer
+        let answers = Word::parse_file(std::path::Path::new("../data/2315/answers.txt"));
+
+        for (guess, answer) in [("soare", "geese"), ("daddy", "added"), ("clint", "pupil"), ("eerie", "tepee"), ("llama", "alarm")] {
+            let (guess, answer) = (w(guess), w(answer));
+            let response = Response::score(guess, answer);
+
+            let mut knowledge = Knowledge::new();
+            knowledge.add(guess, response);
+
+            for other in answers.iter() {
+                assert_eq!(knowledge.is_possible(*other), Response::score(guess, *other) == response, "{guess} {answer} {other}");
+            }
+        }
+    }
+
     #[test]
     fn response_set_basics() {
         let empty = ResponseSet::new();
@@ -0,0 +1,219 @@
+use std::{collections::HashMap, fs, path::Path};
+use crate::{word::Word, response::Response};
+
+const MAGIC: &[u8; 4] = b"WRM1";
+
+/// Contains the Response for every guess and answer pair in a word set, one byte each (Response::to_index).
+///  Build once per word set (or load from a cache file) so that splits and filters are table lookups instead of Response::score calls.
+pub struct ResponseMatrix {
+    guesses: Vec<Word>,
+    answers: Vec<Word>,
+    guess_indices: HashMap<Word, usize>,
+    answer_indices: HashMap<Word, usize>,
+
+    // Response index for each guess (row) and answer (column)
+    values: Vec<u8>
+}
+
+impl ResponseMatrix {
+    /// Score every guess against every answer
+    pub fn new(guesses: &[Word], answers: &[Word]) -> ResponseMatrix {
+        let mut values = Vec::with_capacity(guesses.len() * answers.len());
+
+        for guess in guesses.iter() {
+            for answer in answers.iter() {
+                values.push(Response::score(*guess, *answer).to_index());
+            }
+        }
+
+        ResponseMatrix::from_parts(guesses.to_vec(), answers.to_vec(), values)
+    }
+
+    fn from_parts(guesses: Vec<Word>, answers: Vec<Word>, values: Vec<u8>) -> ResponseMatrix {
+        let guess_indices = guesses.iter().enumerate().map(|(i, w)| (*w, i)).collect();
+        let answer_indices = answers.iter().enumerate().map(|(i, w)| (*w, i)).collect();
+        ResponseMatrix { guesses, answers, guess_indices, answer_indices, values }
+    }
+
+    /// Load the matrix from a cache file, or build it and try to save it for next time.
+    ///  The cache is rebuilt if it is missing, unreadable, or for different guesses or answers.
+    pub fn load_or_build(file_path: &Path, guesses: &[Word], answers: &[Word]) -> ResponseMatrix {
+        if let Ok(matrix) = ResponseMatrix::load(file_path) {
+            if matrix.guesses == guesses && matrix.answers == answers {
+                return matrix;
+            }
+        }
+
+        let matrix = ResponseMatrix::new(guesses, answers);
+        if let Err(e) = matrix.save(file_path) {
+            eprintln!("Unable to cache responses: {e}");
+        }
+
+        matrix
+    }
+
+    /// Read a matrix from a binary file written by save
+    pub fn load(file_path: &Path) -> Result<ResponseMatrix, String> {
+        let bytes = fs::read(file_path).map_err(|e| format!("Unable to read '{:?}': {e}", file_path))?;
+        ResponseMatrix::from_bytes(&bytes)
+    }
+
+    /// Write the matrix to a binary file: "WRM1", guess count and answer count (u32 LE), guess and answer text, then response indices by guess.
+    pub fn save(&self, file_path: &Path) -> Result<(), String> {
+        fs::write(file_path, self.to_bytes()).map_err(|e| format!("Unable to write '{:?}': {e}", file_path))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + 5 * (self.guesses.len() + self.answers.len()) + self.values.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(self.guesses.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.answers.len() as u32).to_le_bytes());
+
+        for word in self.guesses.iter().chain(self.answers.iter()) {
+            bytes.extend(word.iter());
+        }
+
+        bytes.extend_from_slice(&self.values);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ResponseMatrix, String> {
+        if bytes.len() < 12 || &bytes[0..4] != MAGIC {
+            return Err("Not a response matrix file".into());
+        }
+
+        let guess_count = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let answer_count = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let words_end = 12 + 5 * (guess_count + answer_count);
+
+        if bytes.len() != words_end + guess_count * answer_count {
+            return Err(format!("Response matrix file is the wrong length for {guess_count} guesses and {answer_count} answers"));
+        }
+
+        let mut words = Vec::with_capacity(guess_count + answer_count);
+        for chunk in bytes[12..words_end].chunks(5) {
+            let text = std::str::from_utf8(chunk).map_err(|_| "Invalid word in response matrix file".to_string())?;
+            words.push(Word::new(text).ok_or(format!("Invalid word '{text}' in response matrix file"))?);
+        }
+
+        let answers = words.split_off(guess_count);
+        Ok(ResponseMatrix::from_parts(words, answers, bytes[words_end..].to_vec()))
+    }
+
+    pub fn guesses(&self) -> &Vec<Word> {
+        &self.guesses
+    }
+
+    pub fn answers(&self) -> &Vec<Word> {
+        &self.answers
+    }
+
+    pub fn guess_index(&self, guess: Word) -> Option<usize> {
+        self.guess_indices.get(&guess).copied()
+    }
+
+    pub fn answer_index(&self, answer: Word) -> Option<usize> {
+        self.answer_indices.get(&answer).copied()
+    }
+
+    /// Return the Response index for a guess against each answer (in answers order), if the guess is in the matrix
+    pub fn row(&self, guess: Word) -> Option<&[u8]> {
+        self.guess_index(guess).map(|i| self.row_at(i))
+    }
+
+    /// Return the Response index for a guess (by index) against each answer
+    pub fn row_at(&self, guess_index: usize) -> &[u8] {
+        let answer_count = self.answers.len();
+        &self.values[guess_index * answer_count..(guess_index + 1) * answer_count]
+    }
+
+    /// Look up the Response for a guess and answer by index
+    pub fn response(&self, guess_index: usize, answer_index: usize) -> Response {
+        Response::from_index(self.values[guess_index * self.answers.len() + answer_index])
+    }
+
+    /// Return the Response for a guess and answer, computing it if either isn't in the matrix
+    pub fn score(&self, guess: Word, answer: Word) -> Response {
+        match (self.guess_index(guess), self.answer_index(answer)) {
+            (Some(g), Some(a)) => self.response(g, a),
+            _ => Response::score(guess, answer)
+        }
+    }
+
+    /// Split a cluster given a next guess, like rank::split, using lookups.
+    pub fn split(&self, cluster: &[Word], guess: Word, map: &mut HashMap<Response, Vec<Word>>) {
+        map.clear();
+        let row = self.row(guess);
+
+        for answer in cluster.iter() {
+            if guess == *answer { continue; }
+
+            let response = match (row, self.answer_index(*answer)) {
+                (Some(row), Some(a)) => Response::from_index(row[a]),
+                _ => Response::score(guess, *answer)
+            };
+
+            map.entry(response).or_default().push(*answer);
+        }
+    }
+
+    /// Keep only the answers which would give 'response' for 'guess'
+    pub fn filter(&self, guess: Word, response: Response, answers: &mut Vec<Word>) {
+        let index = response.to_index();
+
+        if let Some(row) = self.row(guess) {
+            answers.retain(|a| match self.answer_index(*a) {
+                Some(a) => row[a] == index,
+                None => Response::score(guess, *a) == response
+            });
+        } else {
+            answers.retain(|a| Response::score(guess, *a) == response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn response_matrix_basics() {
+        let guesses = wv("soare, clint, geese");
+        let answers = wv("geese, sheep, shale, bread");
+        let matrix = ResponseMatrix::new(&guesses, &answers);
+
+        // Every lookup matches scoring
+        for guess in guesses.iter() {
+            for answer in answers.iter() {
+                assert_eq!(matrix.score(*guess, *answer), Response::score(*guess, *answer));
+            }
+        }
+
+        // Unknown words are scored directly
+        assert_eq!(matrix.score(w("fuzzy"), w("geese")), Response::score(w("fuzzy"), w("geese")));
+        assert_eq!(matrix.row(w("fuzzy")), None);
+
+        // Split matches rank::split
+        let mut expected = HashMap::new();
+        let mut actual = HashMap::new();
+        rank::split(&answers, w("geese"), &mut expected);
+        matrix.split(&answers, w("geese"), &mut actual);
+        assert_eq!(actual, expected);
+
+        // Filter keeps only matching answers
+        let mut left = answers.clone();
+        matrix.filter(w("soare"), Response::score(w("soare"), w("shale")), &mut left);
+        assert_eq!(left, wv("shale"));
+
+        // Round-trip through bytes
+        let copy = ResponseMatrix::from_bytes(&matrix.to_bytes()).unwrap();
+        assert_eq!(copy.guesses(), matrix.guesses());
+        assert_eq!(copy.answers(), matrix.answers());
+        assert_eq!(copy.values, matrix.values);
+
+        // Invalid files are rejected
+        assert!(ResponseMatrix::from_bytes(b"WRM1").is_err());
+        assert!(ResponseMatrix::from_bytes(&matrix.to_bytes()[0..20]).is_err());
+    }
+}
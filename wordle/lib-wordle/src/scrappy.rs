@@ -25,11 +25,6 @@ pub fn recommended_strategy(guesses: &Vec<Word>, next_guess: Word, answers: &Vec
         let first = cluster_words[0];
         let count = cluster_words.len();
 
-        if count > 63 { 
-            result += &format!("SKIP {first}, {count}\n");
-            continue;
-        }
-
         let next = Some(next_guess); // if responses.known_count() < 3 { Some(next_guess) } else { None };
 
         if let Some(choice) = run_best_turns(cluster_words, next, &valid) {
@@ -52,26 +47,26 @@ fn run_best_turns(words: &Vec<Word>, next_guess: Option<Word>, valid: &Vec<Word>
 
     // Search for the best strategy (issue: best all the way down)
     let mut choices = HashMap::new();
-    clubs.count_best_turns(clubs.all_vector(), &mut choices);
+    clubs.count_best_turns(&within, &mut choices);
     let mut tree = WordleTree::new_sentinel();
-    clubs.best_strategy(within, &choices, false, &mut tree);
+    clubs.best_strategy(&within, &choices, false, &mut tree);
 
     // If there was a best option, compare to the planned next option
     let next = tree.take_first_child();
     if let Some(next) = next {
         if let WordleGuess::Specific(guess) = next.next_guess {
-            let cv = ClusterVector::from_bits(&clubs.split(guess, within));
-            let outer_turns = clubs.count_random_turns_after(within, guess);
+            let cv = ClusterVector::from_bits(&clubs.split(guess, &within));
+            let outer_turns = clubs.count_random_turns_after(&within, guess);
 
             if let Some(next_guess) = next_guess {
-                let standard_outer_turns = clubs.count_random_turns_after(within, next_guess);
-                let std_cv = ClusterVector::from_bits(&clubs.split(next_guess, within));
+                let standard_outer_turns = clubs.count_random_turns_after(&within, next_guess);
+                let std_cv = ClusterVector::from_bits(&clubs.split(next_guess, &within));
 
                 if outer_turns <= standard_outer_turns - MIN_TOTAL_TURNS_IMPROVEMENT {
                     return Some(format!("{guess}  {outer_turns:2.1}  {cv}\n    vs {next_guess}  {standard_outer_turns:2.1}  {std_cv}"));
                 } 
             } else {
-                let standard_outer_turns = clubs.count_random_turns(within);
+                let standard_outer_turns = clubs.count_random_turns(&within);
                 
                 if outer_turns <= standard_outer_turns - MIN_TOTAL_TURNS_IMPROVEMENT {
                     return Some(format!("{guess}  {outer_turns:2.1}  {cv}\n    vs *      {standard_outer_turns:2.1}"));
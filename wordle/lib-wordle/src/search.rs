@@ -1,36 +1,82 @@
-use crate::{cluster_vector::ClusterVector, response::{Response, ResponseSet}, word::Word};
-use std::collections::{HashMap, BinaryHeap};
+use crate::{cluster_vector::ClusterVector, objective::Objective, rank::{self, MapRanker}, response::{Response, ResponseSet}, response_matrix::ResponseMatrix, weights::AnswerWeights, word::Word, wordle_tree::{self, WordleGuess, WordleTree, WordleTreeIdentifier}, wv_safe};
+use std::{collections::{HashMap, BinaryHeap}, fs, path::Path, sync::{Mutex, atomic::{AtomicU64, AtomicUsize, Ordering}}, thread, time::{Duration, Instant}};
 
 const BEST_COUNT: usize = 20;
 
+// Checkpoints are written at most this often (and when the search finishes)
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
 struct SearchState<'a> {
     answers: &'a Vec<Word>,                                     // In: Wordle answers for which to rank the guesses
+    matrix: Option<&'a ResponseMatrix>,                         // In: Precomputed responses for the answers, if available
     ranker: fn(&HashMap<ResponseSet, Vec<Word>>) -> usize,      // In: Ranking function for guesses; lower is better
+    objective: Objective,                                       // In: What to optimize; objectives other than total turns rank first and use 'ranker' to break ties
+    weights: Option<&'a AnswerWeights>,                         // In: How likely each answer is, if not all equally likely
 
-    cluster_cutoff: f64,                                        // State: Only call ranking function for guesses creating at least this many distinct clusters of answers
+    cluster_cutoff: &'a AtomicU64,                              // State: Only call ranking function for guesses creating at least this many distinct clusters of answers (f64 bits, shared by every thread)
     cluster_cutoff_ratio: f64,                                  // In: Raise cluster_cutoff to this percentage of the cluster count, if higher, as the search progresses
-    
+
     count_ranked: usize,                                        // Out: How many guesses had the ranking function called?
 
     guesses: Vec<Word>,                                         // State: Current Set of guesses, initial plus current ones being considered
     responses: Vec<ResponseSet>,                                // State: The ResponseSet for each answer for the guesses so far, to avoid re-computing Response::score
     used_letters: u32,                                          // State: Bitmask of letters used in the guesses so far
     count_left: usize,                                          // State: Number of guesses left to add
-    original_count: usize,                                      // In: Number of guesses to find
 
-    best: BinaryHeap<(usize, Vec<Word>, ClusterVector)>,        // Out: Top N guess groups found so far; (score; guesses; cluster vector)
+    best: BinaryHeap<(u64, Vec<Word>, ClusterVector)>,        // Out: Top N guess groups found so far; (score; guesses; cluster vector)
+}
+
+/// How to run a search: how many threads to use, where to report progress, and where to save it.
+pub struct SearchRun<'a> {
+    pub threads: usize,                                             // Worker threads; each searches different first guess options
+    pub checkpoint: Option<&'a Path>,                               // File to save progress to, and to resume from if it exists
+    pub progress: Option<&'a (dyn Fn(&SearchProgress) + Sync)>,     // Called after each first guess option is searched
+}
+
+impl Default for SearchRun<'_> {
+    fn default() -> Self {
+        SearchRun {
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            checkpoint: None,
+            progress: None
+        }
+    }
+}
+
+/// Progress so far in a search, reported after each first guess option is searched
+pub struct SearchProgress {
+    pub options_done: usize,                    // First guess options searched, including those from a resumed checkpoint
+    pub option_count: usize,                    // First guess options in total
+    pub count_ranked: usize,                    // Guess combinations ranked in this run
+    pub cluster_cutoff: f64,                    // The cluster cutoff reached
+    pub elapsed: Duration,                      // Time spent in this run
+    pub eta: Option<Duration>,                  // Estimated time left
+    pub best: Option<(u64, Vec<Word>)>,       // Best score and guesses so far
 }
 
 /// Search for the best guess(es) for a given set of answers, guess options, and initial guesses, according to a specific ranking function.
+///  Pass a ResponseMatrix for the same answers to look up responses instead of scoring each guess.
+///  Pass an Objective other than TotalTurns to rank guesses by estimated failures or worst case first.
+///  Pass AnswerWeights to rank clusters of likely answers above clusters of rare ones.
+///  The ranker is named as for rank::map_ranker (rank::weighted_map_ranker with weights).
+///  The first guess options are split across threads; with a checkpoint file, an interrupted search resumes where it stopped.
+///  Threads share the cluster cutoff. With a cutoff ratio above zero, it rises in the order options finish, so which low-cluster guesses
+///  are skipped (and so the results near the cutoff) can vary with the thread count; with a ratio of zero the results never do.
+#[allow(clippy::too_many_arguments)]
 pub fn find_best(
     answers: &Vec<Word>,
+    matrix: Option<&ResponseMatrix>,
     valid: &Vec<Word>,
     initial_guesses: Vec<Word>,
     count: usize,
-    ranker: fn(&HashMap<ResponseSet, Vec<Word>>) -> usize,
+    ranker_name: &str,
+    objective: Objective,
+    weights: Option<&AnswerWeights>,
     cluster_cutoff: f64,
     cluster_cutoff_ratio: f64,
-) -> BinaryHeap<(usize, Vec<Word>, ClusterVector)> {
+    run: &SearchRun,
+) -> Result<BinaryHeap<(u64, Vec<Word>, ClusterVector)>, String> {
+    let ranker = if weights.is_some() { rank::weighted_map_ranker(ranker_name)? } else { rank::map_ranker(ranker_name)? };
 
     // Exclude rare letters and those already guessed
     let mut used_letters = 0u32;
@@ -61,61 +107,139 @@ pub fn find_best(
         options = valid.clone();
     }
 
-    // Build containers for a ResponseSet per answer
-    let mut responses = Vec::new();
-    for _ in 0..answers.len() {
-        responses.push(ResponseSet::new());
-    }
+    // The matrix can only be used if columns are these answers, in order
+    let matrix = matrix.filter(|m| m.answers() == answers);
 
     // Add scores for initial guesses
-    for guess in initial_guesses.iter() {
-        for (i, answer) in answers.iter().enumerate() {
-            let response = Response::score(*guess, *answer);
-            responses[i].push(response);
-        }
-    }
+    let responses = responses_for(answers, matrix, &initial_guesses);
+
+    // Everything else the results depend on, to only resume checkpoints for the same search
+    let weights_text = weights.map(|w| fingerprint(answers.iter().map(|a| format!("{a}:{}", w.weight(*a)))).to_string()).unwrap_or("none".to_string());
+    let settings = format!("ranker={ranker_name} objective={objective} weights={weights_text} answers={} options={} cutoff={cluster_cutoff} ratio={cluster_cutoff_ratio}",
+        fingerprint(answers.iter().map(|a| a.to_string())), fingerprint(options.iter().map(|o| o.to_string())));
+
+    // Resume from the checkpoint, if there is one
+    let mut checkpoint = match run.checkpoint.filter(|path| path.exists()) {
+        Some(path) => {
+            let checkpoint = SearchCheckpoint::load(path)?;
+            if checkpoint.count != count || checkpoint.initial_guesses != initial_guesses || checkpoint.done.len() != options.len() || checkpoint.settings != settings {
+                return Err(format!("Checkpoint '{:?}' is for a different search ({} {:?} {}; this is {} {:?} {}). Delete it or choose another file.",
+                    path, checkpoint.count, checkpoint.initial_guesses, checkpoint.settings, count, initial_guesses, settings));
+            }
+            checkpoint
+        },
+        None => SearchCheckpoint::new(count, &initial_guesses, &settings, options.len())
+    };
 
-    println!("Finding best {} guesses after {:?} having at least {:.0} clusters within {} / {} words with distinct letters...", count, initial_guesses, cluster_cutoff, options.len(), valid.len());
+    checkpoint.cluster_cutoff = checkpoint.cluster_cutoff.max(cluster_cutoff);
+    let shared_cutoff = AtomicU64::new(checkpoint.cluster_cutoff.to_bits());
+    let todo = (0..options.len()).filter(|i| !checkpoint.done[*i]).collect::<Vec<usize>>();
 
-    // Build the state to search
-    let mut state = SearchState {
-        answers: answers,
-        ranker: ranker,
+    println!("Finding best {} guesses after {:?} having at least {:.0} clusters within {} / {} words with distinct letters ({} left, {} threads)...", count, initial_guesses, checkpoint.cluster_cutoff, options.len(), valid.len(), todo.len(), run.threads);
 
-        cluster_cutoff: cluster_cutoff,
-        cluster_cutoff_ratio: cluster_cutoff_ratio,
+    // Work for a first guess is roughly the number of options after it to pair with
+    let work = |i: usize| if count > 1 { options.len() - i } else { 1 };
+    let work_left = todo.iter().map(|i| work(*i)).sum::<usize>();
+
+    let threads = run.threads.max(1);
+    let resumed = checkpoint.best.iter().map(|(score, guesses)| (*score, guesses.clone(), cluster_vector_for(answers, matrix, guesses))).collect::<Vec<_>>();
+    let shared = Mutex::new(SharedProgress {
+        checkpoint,
+        worker_best: vec![Vec::new(); threads],
         count_ranked: 0,
+        work_done: 0,
+        start: Instant::now(),
+        last_saved: Instant::now(),
+    });
+    let next = AtomicUsize::new(0);
+
+    // Search the first guess options on each thread, taking the next one not yet started each time
+    let worker_results = thread::scope(|scope| {
+        let handles = (0..threads).map(|worker| {
+            let (options, todo, shared, next, responses, initial_guesses, resumed, work, shared_cutoff) = (&options, &todo, &shared, &next, &responses, &initial_guesses, &resumed, &work, &shared_cutoff);
+
+            scope.spawn(move || {
+                let mut state = SearchState {
+                    answers,
+                    matrix,
+                    ranker,
+                    objective,
+                    weights,
+
+                    cluster_cutoff: shared_cutoff,
+                    cluster_cutoff_ratio,
+                    count_ranked: 0,
+
+                    guesses: initial_guesses.clone(),
+                    responses: responses.clone(),
+                    used_letters,
+                    count_left: count,
+
+                    best: BinaryHeap::new(),
+                };
+
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(option_index) = todo.get(index) else { break; };
+
+                    let ranked_before = state.count_ranked;
+                    search_option(&mut state, options, *option_index);
+
+                    let mut shared = shared.lock().unwrap();
+                    shared.finish_option(worker, *option_index, &state, state.count_ranked - ranked_before, work(*option_index));
+                    shared.report(run, resumed, work_left);
+                }
 
-        guesses: initial_guesses,
-        responses: responses,
-        used_letters: used_letters,
-        count_left: count,
-        original_count: count,
+                state.best
+            })
+        }).collect::<Vec<_>>();
 
-        best: BinaryHeap::new(),
-    };
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+    });
 
-    // Look recursively for the remaining guesses
-    find_best_recurse(&mut state, &options);
+    // Merge the best from each worker and any resumed checkpoint
+    let mut best = BinaryHeap::new();
+    for entry in worker_results.into_iter().flatten().chain(resumed) {
+        best.push(entry);
+        if best.len() > BEST_COUNT { best.pop(); }
+    }
 
-    println!("Done. {} combinations scored.", state.count_ranked);
-    state.best
+    let mut shared = shared.into_inner().unwrap();
+    shared.checkpoint.best = best.iter().map(|(score, guesses, _)| (*score, guesses.clone())).collect();
+    if let Some(path) = run.checkpoint {
+        shared.checkpoint.save(path)?;
+    }
+
+    println!("Done. {} combinations scored.", shared.count_ranked);
+    Ok(best)
 }
 
-fn find_best_recurse(state: &mut SearchState, guess_options: &Vec<Word>) {
+/// Search every combination starting with one first guess option (or just score it, if finding one guess)
+fn search_option(state: &mut SearchState, options: &[Word], index: usize) {
+    if state.count_left <= 1 {
+        find_best_recurse(state, &options[index..index + 1]);
+    } else {
+        state.count_left -= 1;
+        search_after(state, options, index);
+        state.count_left += 1;
+    }
+}
+
+fn find_best_recurse(state: &mut SearchState, guess_options: &[Word]) {
     if state.count_left <= 1 {
         let mut cluster_sizes = HashMap::new();
         let mut clusters = HashMap::new();
         let mut cv = ClusterVector::new(Vec::new());
 
-        for (_i, guess) in guess_options.iter().enumerate() {
+        for guess in guess_options.iter() {
             state.guesses.push(*guess);
 
             // Score guess against answers and determine how many distinct clusters there are
             cluster_sizes.clear();
+            let row = state.matrix.and_then(|m| m.row(*guess));
             for (i, answer) in state.answers.iter().enumerate() {
                 let responses = &mut state.responses[i];
-                let response = Response::score(*guess, *answer);
+                let response = match row { Some(row) => Response::from_index(row[i]), None => Response::score(*guess, *answer) };
                 responses.push(response);
 
                 *cluster_sizes.entry(*responses).or_insert(0usize) += 1;
@@ -123,13 +247,10 @@ fn find_best_recurse(state: &mut SearchState, guess_options: &Vec<Word>) {
 
             // If the number of clusters is high enough, ...
             let cluster_count = cluster_sizes.len() as f64;
-            if cluster_count >= state.cluster_cutoff {
-                // Increase the cutoff, if this count is high enough
+            if cluster_count >= f64::from_bits(state.cluster_cutoff.load(Ordering::Relaxed)) {
+                // Increase the cutoff for every thread, if this count is high enough (non-negative f64s order the same as their bits)
                 let new_cutoff = cluster_count * state.cluster_cutoff_ratio;
-                if new_cutoff > state.cluster_cutoff {
-                    state.cluster_cutoff = new_cutoff;
-                    println!("  CUTOFF -> {:.0}  ({:.0} x {:.2})", new_cutoff, cluster_count, state.cluster_cutoff_ratio);
-                }
+                state.cluster_cutoff.fetch_max(new_cutoff.max(0.0).to_bits(), Ordering::Relaxed);
 
                 // Build a map of the answers themselves
                 clusters.clear();
@@ -140,7 +261,7 @@ fn find_best_recurse(state: &mut SearchState, guess_options: &Vec<Word>) {
 
                 // Score the specific clusters
                 state.count_ranked += 1;
-                let score = (state.ranker)(&clusters);
+                let score = state.objective.rank_map(&clusters, state.guesses.len(), state.ranker, state.weights);
 
                 // If this ties or beats the current best, track it
                 cv.clear();
@@ -148,8 +269,6 @@ fn find_best_recurse(state: &mut SearchState, guess_options: &Vec<Word>) {
 
                 if state.best.len() < BEST_COUNT || state.best.peek().unwrap().0 > score {
                     if state.best.len() >= BEST_COUNT { state.best.pop(); }
-
-                    println!("{}: {:?} {}", score, state.guesses, cv.to_string());
                     state.best.push((score, state.guesses.clone(), cv.clone()));
                 }
             }
@@ -160,46 +279,337 @@ fn find_best_recurse(state: &mut SearchState, guess_options: &Vec<Word>) {
             }
         }
     } else {
-        let letters_before = state.used_letters;
         state.count_left -= 1;
 
-        let mut inner_options = Vec::new();
+        for i in 0..guess_options.len() {
+            search_after(state, guess_options, i);
+        }
+
+        state.count_left += 1;
+    }
+}
+
+/// Add guess_options[index] and recurse to consider the options after it which share none of the letters guessed
+fn search_after(state: &mut SearchState, guess_options: &[Word], index: usize) {
+    let guess = guess_options[index];
+    let letters_before = state.used_letters;
+
+    // Score this guess against all answers
+    let row = state.matrix.and_then(|m| m.row(guess));
+    for (j, answer) in state.answers.iter().enumerate() {
+        let response = match row { Some(row) => Response::from_index(row[j]), None => Response::score(guess, *answer) };
+        state.responses[j].push(response);
+    }
+
+    state.guesses.push(guess);
+    state.used_letters = letters_before | guess.letters_in_word();
+
+    // Filter remaining guess options
+    let mut inner_options = Vec::new();
+    for other in guess_options[index+1..].iter() {
+        let used_letters = other.letters_in_word();
+        if used_letters & state.used_letters != 0 { continue; }
+
+        inner_options.push(*other);
+    }
+
+    // Recurse to consider remaining options
+    find_best_recurse(state, &inner_options);
 
-        for (i, guess) in guess_options.iter().enumerate() {
-            // Score this guess against all answers
-            for (j, answer) in state.answers.iter().enumerate() {
-                let response = Response::score(*guess, *answer);
-                state.responses[j].push(response);
+    state.guesses.pop();
+    for response in state.responses.iter_mut() {
+        response.pop();
+    }
+
+    state.used_letters = letters_before;
+}
+
+/// The ResponseSet for each answer to a set of guesses
+fn responses_for(answers: &[Word], matrix: Option<&ResponseMatrix>, guesses: &[Word]) -> Vec<ResponseSet> {
+    let mut responses = vec![ResponseSet::new(); answers.len()];
+
+    for guess in guesses.iter() {
+        let row = matrix.and_then(|m| m.row(*guess));
+        for (i, answer) in answers.iter().enumerate() {
+            let response = match row { Some(row) => Response::from_index(row[i]), None => Response::score(*guess, *answer) };
+            responses[i].push(response);
+        }
+    }
+
+    responses
+}
+
+fn cluster_vector_for(answers: &[Word], matrix: Option<&ResponseMatrix>, guesses: &[Word]) -> ClusterVector {
+    let mut counts = HashMap::new();
+    for responses in responses_for(answers, matrix, guesses) {
+        *counts.entry(responses).or_insert(0usize) += 1;
+    }
+
+    ClusterVector::from_counts(&counts)
+}
+
+/// Progress shared between search threads
+struct SharedProgress {
+    checkpoint: SearchCheckpoint,
+    worker_best: Vec<Vec<(u64, Vec<Word>)>>,      // Each worker's best as of its last finished option
+    count_ranked: usize,
+    work_done: usize,
+    start: Instant,
+    last_saved: Instant,
+}
+
+impl SharedProgress {
+    fn finish_option(&mut self, worker: usize, option_index: usize, state: &SearchState, count_ranked: usize, work: usize) {
+        self.checkpoint.done[option_index] = true;
+        self.checkpoint.cluster_cutoff = f64::from_bits(state.cluster_cutoff.load(Ordering::Relaxed));
+        self.worker_best[worker] = state.best.iter().map(|(score, guesses, _)| (*score, guesses.clone())).collect();
+        self.count_ranked += count_ranked;
+        self.work_done += work;
+    }
+
+    /// Call the progress callback and save a checkpoint, if it's time to
+    fn report(&mut self, run: &SearchRun, resumed: &[(u64, Vec<Word>, ClusterVector)], work_left: usize) {
+        let save = run.checkpoint.is_some() && self.last_saved.elapsed() >= CHECKPOINT_INTERVAL;
+        if !save && run.progress.is_none() { return; }
+
+        // The best so far across workers and the resumed checkpoint
+        let mut best = resumed.iter().map(|(score, guesses, _)| (*score, guesses.clone())).chain(self.worker_best.iter().flatten().cloned()).collect::<Vec<_>>();
+        best.sort();
+        best.truncate(BEST_COUNT);
+
+        if let Some(progress) = run.progress {
+            let elapsed = self.start.elapsed();
+            let eta = if self.work_done > 0 { Some(elapsed.mul_f64((work_left - self.work_done) as f64 / self.work_done as f64)) } else { None };
+
+            progress(&SearchProgress {
+                options_done: self.checkpoint.done.iter().filter(|d| **d).count(),
+                option_count: self.checkpoint.done.len(),
+                count_ranked: self.count_ranked,
+                cluster_cutoff: self.checkpoint.cluster_cutoff,
+                elapsed,
+                eta,
+                best: best.first().cloned(),
+            });
+        }
+
+        if save {
+            self.checkpoint.best = best;
+            if let Some(Err(e)) = run.checkpoint.map(|path| self.checkpoint.save(path)) {
+                eprintln!("Unable to save checkpoint: {e}");
             }
+            self.last_saved = Instant::now();
+        }
+    }
+}
 
-            state.guesses.push(*guess);
-            state.used_letters = letters_before | guess.letters_in_word();
+/// The state of a search saved to resume it later: which first guess options are done, the cluster cutoff reached, and the best found.
+///  Written as text, one setting per line:
+///   count 2
+///   initial soare
+///   settings ranker=predicted objective=turns weights=none answers=... options=... cutoff=0 ratio=0.9
+///   cutoff 120
+///   options 4031
+///   done 0-137,140
+///   best 3922 clint,parse
+#[derive(Debug, PartialEq)]
+struct SearchCheckpoint {
+    count: usize,
+    initial_guesses: Vec<Word>,
+    settings: String,
+    cluster_cutoff: f64,
+    done: Vec<bool>,
+    best: Vec<(u64, Vec<Word>)>,
+}
+
+impl SearchCheckpoint {
+    fn new(count: usize, initial_guesses: &[Word], settings: &str, option_count: usize) -> SearchCheckpoint {
+        SearchCheckpoint { count, initial_guesses: initial_guesses.to_vec(), settings: settings.to_string(), cluster_cutoff: 0.0, done: vec![false; option_count], best: Vec::new() }
+    }
+
+    fn load(file_path: &Path) -> Result<SearchCheckpoint, String> {
+        let text = fs::read_to_string(file_path).map_err(|e| format!("Unable to read '{:?}': {e}", file_path))?;
+        SearchCheckpoint::parse(&text)
+    }
 
-            // Filter remaining guess options
-            inner_options.clear();
-            for other in guess_options[i+1..].iter() {
-                let used_letters = other.letters_in_word();
-                if used_letters & state.used_letters != 0 { continue; }
+    fn save(&self, file_path: &Path) -> Result<(), String> {
+        fs::write(file_path, self.to_string()).map_err(|e| format!("Unable to write '{:?}': {e}", file_path))
+    }
 
-                inner_options.push(*other);
+    fn parse(text: &str) -> Result<SearchCheckpoint, String> {
+        let mut result = SearchCheckpoint::new(0, &[], "", 0);
+
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let invalid = || format!("Invalid checkpoint line '{line}'.");
+
+            match key {
+                "count" => result.count = value.parse().map_err(|_| invalid())?,
+                "initial" => result.initial_guesses = parse_words(value)?,
+                "settings" => result.settings = value.to_string(),
+                "cutoff" => result.cluster_cutoff = value.parse().map_err(|_| invalid())?,
+                "options" => result.done = vec![false; value.parse().map_err(|_| invalid())?],
+                "done" => {
+                    for range in value.split(',').filter(|r| !r.is_empty()) {
+                        let (start, end) = range.split_once('-').unwrap_or((range, range));
+                        let start = start.parse::<usize>().map_err(|_| invalid())?;
+                        let end = end.parse::<usize>().map_err(|_| invalid())?;
+                        if end >= result.done.len() || start > end { return Err(invalid()); }
+
+                        result.done[start..=end].fill(true);
+                    }
+                },
+                "best" => {
+                    let (score, guesses) = value.split_once(' ').ok_or_else(invalid)?;
+                    result.best.push((score.parse().map_err(|_| invalid())?, parse_words(guesses)?));
+                },
+                "" => {},
+                _ => return Err(invalid())
             }
+        }
 
-            // Recurse to consider remaining options
-            find_best_recurse(state, &inner_options);
+        Ok(result)
+    }
+}
 
-            state.guesses.pop();
-            for response in state.responses.iter_mut() {
-                response.pop();
+impl std::fmt::Display for SearchCheckpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "count {}", self.count)?;
+        writeln!(f, "initial {}", write_words(&self.initial_guesses))?;
+        writeln!(f, "settings {}", self.settings)?;
+        writeln!(f, "cutoff {}", self.cluster_cutoff)?;
+        writeln!(f, "options {}", self.done.len())?;
+
+        // Write done options as ranges of indices
+        let mut ranges = Vec::new();
+        let mut start = None;
+        for i in 0..=self.done.len() {
+            match (start, self.done.get(i).copied().unwrap_or(false)) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    ranges.push(if s + 1 == i { format!("{s}") } else { format!("{s}-{}", i - 1) });
+                    start = None;
+                },
+                _ => {}
+            }
+        }
+        writeln!(f, "done {}", ranges.join(","))?;
+
+        for (score, guesses) in self.best.iter() {
+            writeln!(f, "best {score} {}", write_words(guesses))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_words(text: &str) -> Result<Vec<Word>, String> {
+    if text.trim().is_empty() { Ok(Vec::new()) } else { wv_safe(text) }
+}
+
+fn write_words(words: &[Word]) -> String {
+    words.iter().map(|w| w.to_string()).collect::<Vec<String>>().join(",")
+}
+
+/// A hash of a list of values (FNV-1a), the same on every run and platform, to tell word lists apart in checkpoints
+fn fingerprint(values: impl Iterator<Item = String>) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for value in values {
+        for byte in value.bytes().chain([b'\n']) {
+            hash = (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Find the best second guess for each response to an opening guess, rather than one second guess for every response.
+///  Returns a tree with the opener and a Response child per first response (> .o.r., 13) with the best next guess for it.
+///  Answers left after the second guess are guessed randomly, so the tree total turns compare opening guesses on adaptive play.
+///  Pass a ResponseMatrix for the same answers to look up responses instead of scoring each guess.
+pub fn find_best_adaptive(
+    answers: &Vec<Word>,
+    matrix: Option<&ResponseMatrix>,
+    valid: &[Word],
+    opener: Word,
+    ranker: MapRanker<Response>,
+    objective: Objective,
+    weights: Option<&AnswerWeights>,
+) -> WordleTree {
+    let matrix = matrix.filter(|m| m.answers() == answers);
+    let weight = |cluster: &[Word]| weights.map(|w| w.total(cluster) / cluster.len() as f64).unwrap_or(1.0);
+
+    // Split answers by the response to the opener
+    let mut first_map = HashMap::new();
+    let mut first_indices = HashMap::new();
+    for (i, answer) in answers.iter().enumerate() {
+        if *answer == opener { continue; }
+        let response = response_at(matrix, opener, i, *answer);
+        (*first_map.entry(response).or_insert(Vec::new())).push(*answer);
+        (*first_indices.entry(response).or_insert(Vec::new())).push(i);
+    }
+
+    let mut root = WordleTree::new(WordleTreeIdentifier::Any, WordleGuess::Specific(opener));
+    root.cluster_vector = Some(ClusterVector::from_map(&first_map));
+
+    if answers.contains(&opener) {
+        let mut single = WordleTree::new_single_leaf(opener, 0);
+        single.outer_total_turns = weight(&[opener]);
+        root.add_child(single);
+    }
+
+    let mut map = HashMap::new();
+    for (response, cluster) in first_map.into_iter() {
+        let indices = &first_indices[&response];
+
+        // Pairs and singles are best guessed in-cluster
+        if cluster.len() <= 2 {
+            let mut leaf = WordleTree::new_leaf(cluster.clone(), (cluster.len() as f64 + rank::total_turns_random(&cluster)) * weight(&cluster));
+            leaf.identifier = WordleTreeIdentifier::Response(opener, response);
+            root.add_child(leaf);
+            continue;
+        }
+
+        // Rank every valid guess for this cluster; on ties, prefer guesses which might be the answer
+        let mut best: Option<((u64, bool), Word)> = None;
+        for guess in valid.iter() {
+            map.clear();
+            for (i, answer) in indices.iter().zip(cluster.iter()) {
+                if answer == guess { continue; }
+                (*map.entry(response_at(matrix, *guess, *i, *answer)).or_insert(Vec::new())).push(*answer);
             }
 
-            // Print progress and arguments to resume search here
-            if state.count_left == state.original_count - 1 && i % 10 == 0 {
-                println!(" --after {guess}  --cutoff {:.0}", state.cluster_cutoff);
+            let score = (objective.rank_map(&map, 2, ranker, weights), !cluster.contains(guess));
+            if best.is_none_or(|(b, _)| score < b) {
+                best = Some((score, *guess));
             }
         }
 
-        state.used_letters = letters_before;
-        state.count_left += 1;
+        let guess = best.unwrap().1;
+        let mut node = WordleTree::new(WordleTreeIdentifier::Response(opener, response), WordleGuess::Specific(guess));
+
+        // Count two turns to reach each answer, plus random guessing after the second guess
+        rank::split(&cluster, guess, &mut map);
+        let mut total_turns = if cluster.contains(&guess) { 2.0 * weight(&[guess]) } else { 0.0 };
+        for (_, subcluster) in map.iter() {
+            total_turns += (2.0 * subcluster.len() as f64 + rank::total_turns_random(subcluster)) * weight(subcluster);
+        }
+
+        node.outer_total_turns = total_turns;
+        node.answer_count = cluster.len();
+        node.cluster_vector = Some(ClusterVector::from_map(&map));
+        if cluster.len() <= wordle_tree::LIST_ANSWERS_MAX_COUNT {
+            node.answers = Some(cluster);
+        }
+
+        root.add_child(node);
+    }
+
+    root
+}
+
+fn response_at(matrix: Option<&ResponseMatrix>, guess: Word, answer_index: usize, answer: Word) -> Response {
+    match matrix.and_then(|m| m.row(guess)) {
+        Some(row) => Response::from_index(row[answer_index]),
+        None => Response::score(guess, answer)
     }
 }
 
@@ -209,21 +619,126 @@ pub fn score_cluster_count(cv: &Vec<usize>) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
-    use crate::{word::Word, rank};
+    use std::{path::Path, sync::Mutex};
+    use crate::{word::Word, rank, response_matrix::ResponseMatrix, objective::Objective, weights::AnswerWeights, wordle_tree::{WordleTree, WordleGuess, WordleTreeIdentifier}};
+    use super::{SearchRun, SearchProgress, SearchCheckpoint};
 
     #[test]
     fn search_scoring() {
         let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
         let guesses = vec![w("clint"), w("parse"), w("soare"), w("primy")];
+        let run = SearchRun::default();
 
-        let results = super::find_best(&answers, &guesses, Vec::new(), 2, rank::total_turns_random_map, 0.0, 0.0);
+        let results = super::find_best(&answers, None, &guesses, Vec::new(), 2, "random", Objective::TotalTurns, None, 0.0, 0.0, &run).unwrap();
         let best = results.iter().last().unwrap();
         assert_eq!(best.0, 3922);
         assert_eq!(best.1, vec![w("clint"), w("parse")]);
+
+        // Same result with a response matrix
+        let matrix = ResponseMatrix::new(&guesses, &answers);
+        let results = super::find_best(&answers, Some(&matrix), &guesses, Vec::new(), 2, "random", Objective::TotalTurns, None, 0.0, 0.0, &run).unwrap();
+        assert_eq!(results.iter().last().unwrap(), best);
+
+        // Minimizing the worst case, no pair is predicted to finish within 4 turns; ties fall back to the ranker
+        let results = super::find_best(&answers, None, &guesses, Vec::new(), 2, "random", Objective::MaxTurns, None, 0.0, 0.0, &run).unwrap();
+        let best = results.iter().last().unwrap();
+        assert_eq!(best.0, 5_000_000 + 3922);
+
+        // Equal weights rank the same as none
+        let weights = AnswerWeights::uniform(&answers);
+        let results = super::find_best(&answers, None, &guesses, Vec::new(), 2, "random", Objective::TotalTurns, Some(&weights), 0.0, 0.0, &run).unwrap();
+        let best = results.iter().last().unwrap();
+        assert_eq!(best.1, vec![w("clint"), w("parse")]);
+    }
+
+    #[test]
+    fn search_threads_and_checkpoints() {
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let guesses = crate::wv("clint, parse, soare, primy, dumbo, ghoul, fjord, wacky");
+        let search = |run: &SearchRun| {
+            super::find_best(&answers, None, &guesses, Vec::new(), 2, "predicted", Objective::TotalTurns, None, 0.0, 0.0, run).unwrap().into_sorted_vec()
+        };
+
+        // One thread and many find the same results, and progress is reported for every first guess option
+        let reports = Mutex::new(Vec::new());
+        let progress = |p: &SearchProgress| reports.lock().unwrap().push((p.options_done, p.option_count));
+        let single = search(&SearchRun { threads: 1, checkpoint: None, progress: Some(&progress) });
+        let option_count = reports.lock().unwrap().len();
+        assert_eq!(reports.lock().unwrap().last(), Some(&(option_count, option_count)));
+
+        let multiple = search(&SearchRun { threads: 4, checkpoint: None, progress: None });
+        assert_eq!(single.iter().map(|b| b.0).collect::<Vec<_>>(), multiple.iter().map(|b| b.0).collect::<Vec<_>>());
+
+        // Resuming a checkpoint with every option done returns the saved best without searching
+        let path = std::env::temp_dir().join(format!("search_checkpoint_{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let first = search(&SearchRun { threads: 2, checkpoint: Some(&path), progress: None });
+
+        let saved = SearchCheckpoint::load(&path).unwrap();
+        assert!(saved.done.iter().all(|d| *d));
+        assert_eq!(saved.best.len(), first.len());
+
+        let resumed = search(&SearchRun { threads: 2, checkpoint: Some(&path), progress: None });
+        assert_eq!(resumed, first);
+
+        // A checkpoint for a different search is an error
+        let other = |count: usize, ranker: &str, objective: Objective, ratio: f64| super::find_best(&answers, None, &guesses, Vec::new(), count, ranker, objective, None, 0.0, ratio, &SearchRun { threads: 1, checkpoint: Some(&path), progress: None });
+        assert!(other(1, "predicted", Objective::TotalTurns, 0.0).is_err());
+        assert!(other(2, "entropy", Objective::TotalTurns, 0.0).is_err());
+        assert!(other(2, "predicted", Objective::MaxTurns, 0.0).is_err());
+        assert!(other(2, "predicted", Objective::TotalTurns, 0.5).is_err());
+        assert!(super::find_best(&answers, None, &guesses[1..].to_vec(), Vec::new(), 2, "predicted", Objective::TotalTurns, None, 0.0, 0.0, &SearchRun { threads: 1, checkpoint: Some(&path), progress: None }).is_err());
+        assert!(super::find_best(&answers[1..].to_vec(), None, &guesses, Vec::new(), 2, "predicted", Objective::TotalTurns, None, 0.0, 0.0, &SearchRun { threads: 1, checkpoint: Some(&path), progress: None }).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn search_adaptive() {
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let valid = answers.iter().step_by(5).copied().chain([w("clint")]).collect::<Vec<Word>>();
+        let matrix = ResponseMatrix::new(&valid, &answers);
+        let tree = super::find_best_adaptive(&answers, Some(&matrix), &valid, w("soare"), rank::total_turns_predicted_map, Objective::TotalTurns, None);
+
+        // Every answer is covered, with a child per response to SOARE rolling up to the total
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("soare")));
+        assert_eq!(tree.answer_count, answers.len());
+        let children = tree.subtree.as_ref().unwrap();
+        assert!(children.iter().all(|c| matches!(c.identifier, WordleTreeIdentifier::Response(g, _) if g == w("soare")) || c.answers == Some(vec![w("soare")])));
+        assert_eq!(tree.outer_total_turns, children.iter().map(|c| c.outer_total_turns).sum::<f64>());
+
+        // Choosing the second guess per response beats always playing CLINT second, and the matrix doesn't change the result
+        let fixed = crate::wordle_tree::builders::build("standard", &answers, &[], &crate::wv("soare, clint"), false, Objective::TotalTurns, None).unwrap();
+        assert!(tree.outer_total_turns < fixed.outer_total_turns);
+
+        let without_matrix = super::find_best_adaptive(&answers, None, &valid, w("soare"), rank::total_turns_predicted_map, Objective::TotalTurns, None);
+        assert_eq!(without_matrix.to_string(), tree.to_string());
+
+        // The tree can be read back as a strategy
+        let parsed = WordleTree::parse(tree.to_string().lines()).unwrap();
+        assert_eq!(parsed.answer_count, tree.answer_count);
+        assert_eq!(parsed.subtree.unwrap().len(), children.len());
+    }
+
+    #[test]
+    fn checkpoint_text() {
+        let mut checkpoint = SearchCheckpoint::new(2, &[w("soare")], "ranker=predicted ratio=0.9", 8);
+        checkpoint.cluster_cutoff = 120.5;
+        for i in [0, 1, 2, 4, 6, 7] { checkpoint.done[i] = true; }
+        checkpoint.best.push((3922, vec![w("clint"), w("parse")]));
+
+        let text = checkpoint.to_string();
+        assert_eq!(text, "count 2\ninitial soare\nsettings ranker=predicted ratio=0.9\ncutoff 120.5\noptions 8\ndone 0-2,4,6-7\nbest 3922 clint,parse\n");
+        assert_eq!(SearchCheckpoint::parse(&text).unwrap(), checkpoint);
+
+        // No initial guesses or options done
+        let checkpoint = SearchCheckpoint::new(1, &[], "", 3);
+        assert_eq!(SearchCheckpoint::parse(&checkpoint.to_string()).unwrap(), checkpoint);
+
+        assert!(SearchCheckpoint::parse("options 3\ndone 2-5").is_err());
+        assert!(SearchCheckpoint::parse("colour blue").is_err());
     }
 
     fn w(text: &str) -> Word {
         Word::new(text).unwrap()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use crate::{bit_vector::BitVector, cluster_vector::ClusterVector, response::{self, Response}, response_matrix::ResponseMatrix, weights::AnswerWeights, word::Word, wordle_tree::{self, WordleGuess, WordleTree, WordleTreeIdentifier}};
+
+const RESPONSE_COUNT: usize = 243;
+
+// Weighted answers count in thousandths, so that the search can stay in whole numbers
+const WEIGHT_UNIT: usize = 1000;
+
+/// Finds the strategy with the fewest total turns to solve every answer, considering every guess in every situation.
+///  Results are memoized per cluster. Guesses are tried in order of an admissible lower bound (after the guess, one answer per
+///  sub-cluster could be guessed next and the rest need at least two more turns), and the search for a cluster stops as soon as
+///  no remaining guess could beat the best found so far. Sub-clusters are searched with the turns left under the best as a limit.
+///  With weights, turns are weighted by answer (the likeliest answer in each sub-cluster could be guessed next).
+pub struct Solver<'a> {
+    matrix: &'a ResponseMatrix,
+    all_green: u8,
+    memo: HashMap<BitVector, Solved>,
+
+    // The weight of each answer (by index), in units; every answer weighs one unit when unweighted
+    weights: Vec<usize>,
+    unit: usize,
+
+    // How many clusters (of three or more answers) had every guess ranked
+    pub clusters_searched: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Solved {
+    Exact(usize, usize),        // Fewest total turns, and the guess index to get them
+    AtLeast(usize),             // The search was cut off; total turns are at least this many
+}
+
+impl Solver<'_> {
+    /// Solve for the answers in the matrix, using the matrix guesses. Every answer must also be a guess.
+    pub fn new(matrix: &ResponseMatrix) -> Result<Solver<'_>, String> {
+        if let Some(missing) = matrix.answers().iter().find(|a| matrix.guess_index(**a).is_none()) {
+            return Err(format!("Answer '{missing}' is not one of the guesses."));
+        }
+
+        Ok(Solver {
+            matrix,
+            all_green: Response::new(response::ALL_GREEN).to_index(),
+            memo: HashMap::new(),
+            weights: vec![1; matrix.answers().len()],
+            unit: 1,
+            clusters_searched: 0
+        })
+    }
+
+    /// Weight answers by how likely they are rather than equally. Turn totals become weighted totals.
+    pub fn set_weights(&mut self, weights: &AnswerWeights) {
+        self.weights = self.matrix.answers().iter().map(|a| (weights.weight(*a) * WEIGHT_UNIT as f64).round() as usize).collect();
+        self.unit = WEIGHT_UNIT;
+        self.memo.clear();
+    }
+
+    /// Return the fewest total turns to solve every answer in the cluster, counting one turn per answer for each guess.
+    ///  Words which aren't answers in the matrix are ignored.
+    pub fn best_turns(&mut self, cluster: &[Word]) -> f64 {
+        let within = self.to_indices(cluster);
+        let turns = self.turns(&within, usize::MAX);
+        self.to_turns(turns)
+    }
+
+    /// Return the best guess for the cluster and the total turns with it, or None for clusters where any in-cluster guess is as good.
+    pub fn best_next_guess(&mut self, cluster: &[Word]) -> (Option<Word>, f64) {
+        let within = self.to_indices(cluster);
+        let turns = self.turns(&within, usize::MAX);
+        let turns = self.to_turns(turns);
+
+        match self.memo.get(&BitVector::from_vec(within)) {
+            Some(Solved::Exact(_, guess)) => (Some(self.matrix.guesses()[*guess]), turns),
+            _ => (None, turns)
+        }
+    }
+
+    /// Build the optimal strategy for all answers as a WordleTree, optionally with a required first guess.
+    ///  Clusters of one or two answers are left out (any guess in them is optimal); their counts are in each cluster vector.
+    pub fn solve(&mut self, first_guess: Option<Word>) -> Result<WordleTree, String> {
+        let within = (0..self.matrix.answers().len()).collect::<Vec<usize>>();
+
+        let (guess, turns) = match first_guess {
+            Some(word) => {
+                let guess = self.matrix.guess_index(word).ok_or(format!("First guess '{word}' is not one of the guesses."))?;
+                (guess, self.turns_after(&within, guess, usize::MAX))
+            },
+            None => {
+                let turns = self.turns(&within, usize::MAX);
+                match self.memo.get(&BitVector::from_vec(within.clone())) {
+                    Some(Solved::Exact(_, guess)) => (*guess, turns),
+                    _ => return Err("Too few answers to need a strategy.".into())
+                }
+            }
+        };
+
+        let mut sentinel = WordleTree::new_sentinel();
+        self.add_to_tree(&within, guess, turns, WordleTreeIdentifier::Any, 0, &mut sentinel);
+        Ok(sentinel.take_first_child().unwrap())
+    }
+
+    fn to_indices(&self, cluster: &[Word]) -> Vec<usize> {
+        let mut within = cluster.iter().filter_map(|w| self.matrix.answer_index(*w)).collect::<Vec<usize>>();
+        within.sort();
+        within.dedup();
+        within
+    }
+
+    fn to_turns(&self, units: usize) -> f64 {
+        units as f64 / self.unit as f64
+    }
+
+    /// The total weight of the answers (the count, unweighted)
+    fn weight(&self, within: &[usize]) -> usize {
+        within.iter().map(|a| self.weights[*a]).sum()
+    }
+
+    /// The best possible turns for a cluster: the likeliest answer guessed next, and every other answer the turn after
+    fn ideal_turns(&self, within: &[usize]) -> usize {
+        let heaviest = within.iter().map(|a| self.weights[*a]).max().unwrap_or(0);
+        2 * self.weight(within) - heaviest
+    }
+
+    /// Return the exact fewest total turns for the cluster if less than limit, or otherwise a lower bound which is at least limit.
+    fn turns(&mut self, within: &[usize], limit: usize) -> usize {
+        let count = within.len();
+        if count < 3 {
+            // For one or two words, guessing the likelier first is the best outcome
+            return self.ideal_turns(within);
+        }
+
+        let key = BitVector::from_vec(within.to_vec());
+        match self.memo.get(&key) {
+            Some(Solved::Exact(turns, _)) => return *turns,
+            Some(Solved::AtLeast(turns)) if *turns >= limit => return *turns,
+            _ => {}
+        }
+
+        // Nothing can beat guessing one answer and then every other answer next
+        let ideal_turns = self.ideal_turns(within);
+        if ideal_turns >= limit { return ideal_turns; }
+
+        // Rank every guess by lower bound, in-cluster before out-of-cluster, then guess order
+        self.clusters_searched += 1;
+        let total_weight = self.weight(within);
+        let mut options = Vec::new();
+        let mut counts = [0usize; RESPONSE_COUNT];
+        let mut weights = [0usize; RESPONSE_COUNT];
+        let mut heaviest = [0usize; RESPONSE_COUNT];
+
+        for guess in 0..self.matrix.guesses().len() {
+            let row = self.row(guess);
+            for answer in within.iter() {
+                let response = row[*answer] as usize;
+                let weight = self.weights[*answer];
+                counts[response] += 1;
+                weights[response] += weight;
+                heaviest[response] = heaviest[response].max(weight);
+            }
+
+            let solved = counts[self.all_green as usize];
+            let solved_weight = weights[self.all_green as usize];
+            let mut cluster_count = 0;
+            let mut heaviest_total = 0;
+            for answer in within.iter() {
+                let response = row[*answer] as usize;
+                if counts[response] > 0 {
+                    cluster_count += 1;
+                    if response != self.all_green as usize { heaviest_total += heaviest[response]; }
+                    counts[response] = 0;
+                    weights[response] = 0;
+                    heaviest[response] = 0;
+                }
+            }
+
+            // Skip guesses which leave every answer in the same cluster
+            if solved == 0 && cluster_count == 1 { continue; }
+
+            // This guess for every answer, then the ideal for each cluster left
+            let lower_bound = total_weight + 2 * (total_weight - solved_weight) - heaviest_total;
+            options.push((lower_bound, solved == 0, guess));
+        }
+
+        options.sort();
+
+        let mut best = (limit, None);
+        for (lower_bound, _, guess) in options.iter() {
+            if *lower_bound >= best.0 { break; }
+
+            let turns = self.turns_after(within, *guess, best.0);
+            if turns < best.0 {
+                best = (turns, Some(*guess));
+            }
+        }
+
+        let solved = match best {
+            (turns, Some(guess)) => Solved::Exact(turns, guess),
+            (turns, None) => Solved::AtLeast(options.first().map(|o| o.0.max(turns)).unwrap_or(turns))
+        };
+
+        self.memo.insert(key, solved);
+        match solved {
+            Solved::Exact(turns, _) => turns,
+            Solved::AtLeast(turns) => turns
+        }
+    }
+
+    /// Return the total turns for the cluster with a specific next guess, or a lower bound of at least limit if it isn't under limit.
+    fn turns_after(&mut self, within: &[usize], guess: usize, limit: usize) -> usize {
+        let clusters = self.split(within, guess);
+
+        // One turn for each answer for this guess, plus an ideal case for each cluster after it
+        let mut total = self.weight(within) + clusters.iter().map(|(_, c)| self.ideal_turns(c)).sum::<usize>();
+
+        // Replace ideal turns with actual turns, biggest clusters first, until over the limit
+        for (_, cluster) in clusters.iter() {
+            if total >= limit { break; }
+            if cluster.len() < 3 { continue; }
+
+            let ideal_turns = self.ideal_turns(cluster);
+            let turns = self.turns(cluster, limit - (total - ideal_turns));
+            total += turns - ideal_turns;
+        }
+
+        total
+    }
+
+    /// Split the cluster by response to the guess, excluding the answer guessed, biggest clusters first.
+    fn split(&self, within: &[usize], guess: usize) -> Vec<(u8, Vec<usize>)> {
+        let row = self.row(guess);
+        let mut map: HashMap<u8, Vec<usize>> = HashMap::new();
+
+        for answer in within.iter() {
+            let response = row[*answer];
+            if response != self.all_green {
+                map.entry(response).or_default().push(*answer);
+            }
+        }
+
+        let mut clusters = map.into_iter().collect::<Vec<_>>();
+        clusters.sort_by(|l, r| r.1.len().cmp(&l.1.len()).then(l.0.cmp(&r.0)));
+        clusters
+    }
+
+    fn row(&self, guess: usize) -> &[u8] {
+        self.matrix.row_at(guess)
+    }
+
+    fn add_to_tree(&mut self, within: &[usize], guess: usize, turns: usize, identifier: WordleTreeIdentifier, turns_before: usize, parent: &mut WordleTree) {
+        let guess_word = self.matrix.guesses()[guess];
+        let clusters = self.split(within, guess);
+
+        let mut node = WordleTree::new(identifier, WordleGuess::Specific(guess_word));
+        node.outer_total_turns = self.to_turns(turns_before * self.weight(within) + turns);
+        node.answer_count = within.len();
+
+        if within.len() <= wordle_tree::LIST_ANSWERS_MAX_COUNT {
+            node.answers = Some(within.iter().map(|a| self.matrix.answers()[*a]).collect());
+        }
+
+        let mut cv = ClusterVector::new(Vec::new());
+        for (_, cluster) in clusters.iter() {
+            cv.add(cluster.len());
+        }
+        node.cluster_vector = Some(cv);
+
+        for (response, cluster) in clusters.iter() {
+            if cluster.len() < 3 { continue; }
+
+            let turns = self.turns(cluster, usize::MAX);
+            if let Some(Solved::Exact(_, next)) = self.memo.get(&BitVector::from_vec(cluster.clone())).copied() {
+                let identifier = WordleTreeIdentifier::Response(guess_word, Response::from_index(*response));
+                self.add_to_tree(cluster, next, turns, identifier, turns_before + 1, &mut node);
+            }
+        }
+
+        parent.add_child_without_rollup(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{*, clubs::Clubs};
+    use std::path::Path;
+
+    #[test]
+    fn solver_basics() {
+        // Base cases: 1 for 1-cluster, 3 for 2-cluster
+        let answers = wv("clash, clasp, class, ddddd, uuuuu, mmmmm, ppppp");
+        let guesses = [answers.clone(), wv("dumpy")].concat();
+        let matrix = ResponseMatrix::new(&guesses, &answers);
+        let mut solver = Solver::new(&matrix).unwrap();
+
+        assert_eq!(solver.best_turns(&wv("clash")), 1.0);
+        assert_eq!(solver.best_turns(&wv("clash, clasp")), 3.0);
+
+        // Three cluster where no guess distinguishes the others: 1 + 2 + 3 turns
+        assert_eq!(solver.best_turns(&wv("clash, clasp, class")), 6.0);
+
+        // Four options where an out-of-cluster choice is best: dumpy -> 2 + 2 + 2 + 2 (vs 1 + 2 + 3 + 4)
+        assert_eq!(solver.best_next_guess(&wv("ddddd, uuuuu, mmmmm, ppppp")), (Some(w("dumpy")), 8.0));
+
+        // If DDDDD is much likelier, guessing it first is better: {ddddd: 3.25, others: 0.25} -> 3.25 + 0.25 * (2 + 3 + 4) = 5.5 (vs 8 for DUMPY)
+        let answers = wv("ddddd, uuuuu, mmmmm, ppppp");
+        let matrix = ResponseMatrix::new(&[answers.clone(), wv("dumpy")].concat(), &answers);
+        let mut solver = Solver::new(&matrix).unwrap();
+        solver.set_weights(&AnswerWeights::parse("ddddd\t13\nuuuuu\t1", &answers).unwrap());
+        assert_eq!(solver.best_next_guess(&wv("ddddd, uuuuu, mmmmm, ppppp")), (Some(w("ddddd")), 5.5));
+
+        // Every answer must be a guess
+        let answers = wv("clash, clasp, class");
+        let matrix = ResponseMatrix::new(&wv("dumpy"), &answers);
+        assert!(Solver::new(&matrix).is_err());
+    }
+
+    #[test]
+    fn solver_matches_clubs() {
+        // Optimal turns match Clubs::count_best_turns for clusters from a real first guess
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let valid = wv("clint, dumpy, flams, wordy, ghoul, befit");
+        let mut clusters = HashMap::new();
+        rank::split(&answers, w("soare"), &mut clusters);
+
+        let mut compared = 0;
+        for (_, cluster) in clusters.iter().filter(|(_, c)| c.len() >= 3 && c.len() <= 12) {
+            let guesses = [cluster.clone(), valid.clone()].concat();
+            let matrix = ResponseMatrix::new(&guesses, cluster);
+            let mut solver = Solver::new(&matrix).unwrap();
+
+            let clubs = Clubs::new(cluster, &valid);
+            let mut choices = HashMap::new();
+            let expected = clubs.count_best_turns(&clubs.all_vector(), &mut choices);
+
+            assert_eq!(solver.best_turns(cluster), expected as f64, "{:?}", cluster);
+            compared += 1;
+        }
+
+        assert!(compared > 20);
+    }
+
+    #[test]
+    fn solver_tree() {
+        // fight, light, might, night, sight: flams separates all of them (5 + 1 + 1 + 1 + 1 + 1 = 10)
+        let answers = wv("fight, light, might, night, sight");
+        let guesses = [answers.clone(), wv("flams")].concat();
+        let matrix = ResponseMatrix::new(&guesses, &answers);
+        let mut solver = Solver::new(&matrix).unwrap();
+
+        let tree = solver.solve(None).unwrap();
+        assert_eq!(smart_trim(&tree.to_string()), "10 (*, 5) -> flams [5] {fight, light, might, night, sight}");
+
+        // With a required first guess, sub-clusters are solved optimally after it
+        let tree = solver.solve(Some(w("fight"))).unwrap();
+        assert_eq!(smart_trim(&tree.to_string()),
+"13 (*, 5) -> fight [0, 0, 0, 1] {fight, light, might, night, sight}
+    12 (> .IGHT, 4) -> flams [4] {light, might, night, sight}");
+
+        // The first guess must be a known guess
+        assert!(solver.solve(Some(w("dumpy"))).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use std::{collections::HashMap, cmp::Ordering};
-use crate::{rank, response::Response, word::Word, cluster_vector::ClusterVector};
+use crate::{rank, response::Response, response_matrix::ResponseMatrix, word::Word, cluster_vector::ClusterVector, json::Json, wordle_tree::json::words_to_json};
 
 /// Represents a Wordle game state with any number of guesses.
 pub struct State<'a> {
@@ -11,6 +11,16 @@ pub struct State<'a> {
 
     // Words which may be used for remaining guesses
     _valid: &'a Vec<Word>,
+
+    // Precomputed responses, if available
+    matrix: Option<&'a ResponseMatrix>,
+}
+
+impl<'a> State<'a> {
+    /// Look up responses from a ResponseMatrix instead of scoring each guess and answer
+    pub fn set_matrix(&mut self, matrix: &'a ResponseMatrix) {
+        self.matrix = Some(matrix);
+    }
 }
 
 impl State<'_> {
@@ -23,6 +33,7 @@ impl State<'_> {
             guesses: Vec::new(),
             remaining: remaining,
             _valid: valid,
+            matrix: None,
         }
     }
 
@@ -32,7 +43,10 @@ impl State<'_> {
         let mut new_remaining: HashMap<Vec<Response>, Vec<Word>> = HashMap::new();
         for (responses, answers) in self.remaining.iter() {
             for answer in answers {
-                let new_response = Response::score(guess, *answer);
+                let new_response = match self.matrix {
+                    Some(matrix) => matrix.score(guess, *answer),
+                    None => Response::score(guess, *answer)
+                };
 
                 if let Some(expected) = response {
                     if new_response != expected {
@@ -74,6 +88,9 @@ impl State<'_> {
         //     used_letters = used_letters | guess.letters_in_word();
         // }
 
+        // Look up answer indices in the matrix once, so each guess only needs table lookups
+        let answer_indices = map.values().map(|answers| answers.iter().map(|a| self.matrix.and_then(|m| m.answer_index(*a))).collect::<Vec<_>>()).collect::<Vec<_>>();
+
         // For each allowed word ...
         for word in self._valid.iter() {
             // Skip words which contain letters already used
@@ -81,14 +98,19 @@ impl State<'_> {
 
             let mut cv = Vec::new();
 
+            let row = self.matrix.and_then(|m| m.row(*word));
+
             // For each existing bucket ...
-            for (_, answers) in map.iter() {
+            for ((_, answers), indices) in map.iter().zip(answer_indices.iter()) {
                 let mut inner_map = HashMap::new();
 
                 // Score each answer
-                for answer in answers {
+                for (answer, index) in answers.iter().zip(indices.iter()) {
                     if answer == word { continue; }
-                    let response = Response::score(*word, *answer);
+                    let response = match (row, index) {
+                        (Some(row), Some(index)) => Response::from_index(row[*index]),
+                        _ => Response::score(*word, *answer)
+                    };
                     let entry = inner_map.entry(response);
                     *entry.or_insert(0) += 1;
                 }
@@ -116,22 +138,44 @@ impl State<'_> {
         ClusterVector::from_map(&self.remaining)
     }
 
+    /// The guesses, knowns, and remaining clusters 'print' starts with, as JSON:
+    ///  {"guesses": ["soare"], "knowns": [".O.re"], "cluster_vector": [...], "clusters": [{"responses": [".O.re"], "answers": [...]}]}.
+    ///  Unconstrained guesses have "*****" knowns, as printed. Clusters are biggest first, as printed.
+    pub fn to_json(&self) -> Json {
+        let mut clusters: Vec<(&Vec<Response>, &Vec<Word>)> = self.remaining.iter().collect();
+        clusters.sort_by(|l, r| r.1.len().cmp(&l.1.len()).then(order_by_responses(l.0, r.0)));
+
+        let clusters = clusters.into_iter().map(|(responses, answers)| Json::object(vec![
+            ("responses", Json::Array(responses.iter().zip(self.guesses.iter()).map(|(response, (guess, _))| Json::from(response.to_knowns_string(guess))).collect())),
+            ("answers", words_to_json(answers)),
+        ])).collect();
+
+        Json::object(vec![
+            ("guesses", words_to_json(&self.guesses.iter().map(|(guess, _)| *guess).collect::<Vec<Word>>())),
+            ("knowns", Json::Array(self.knowns().into_iter().map(Json::from).collect())),
+            ("cluster_vector", self.to_cluster_vector().to_json()),
+            ("clusters", Json::Array(clusters)),
+        ])
+    }
+
     pub fn print_guesses(&self) {
         let guesses = self.guesses.iter().map(|(guess, _)| guess.to_string()).collect::<Vec<String>>();
         println!("{}", guesses.join(" "));
     }
 
     pub fn print_knowns(&self) {
-        let knowns = self.guesses.iter().map(|(guess, response)| 
+        println!("{}", self.knowns().join(" "));
+    }
+
+    fn knowns(&self) -> Vec<String> {
+        self.guesses.iter().map(|(guess, response)| 
         { 
             if let Some(response) = response {
                 response.to_knowns_string(&guess)
             } else {
                 "*****".to_string()
             }
-        }).collect::<Vec<String>>();
-
-        println!("{}", knowns.join(" "));
+        }).collect::<Vec<String>>()
     }
 
     // Desired Output Options:
@@ -221,7 +265,8 @@ impl State<'_> {
                 total_turns += if answers.len() == 1 { 1 } else { 3 };
             } else {
                 // Rank options by cluster vectors and pessimistic turns
-                let inner = State::new(&answers, &answers);
+                let mut inner = State::new(&answers, &answers);
+                if let Some(matrix) = self.matrix { inner.set_matrix(matrix); }
                 let in_cluster_options = inner.best_next(ClusterVector::total_turns_pessimistic);
                 let mut best = in_cluster_options.iter().min_by(|l, r| l.0.cmp(&r.0)).unwrap().clone();
 
@@ -236,7 +281,8 @@ impl State<'_> {
                 } else {
                     // Otherwise, if no in-cluster guess was ideal, look for out-of-cluster options
                     if best.0 > answers.len() - 1 {
-                        let inner = State::new(&answers, &self._valid);
+                        let mut inner = State::new(&answers, &self._valid);
+                        if let Some(matrix) = self.matrix { inner.set_matrix(matrix); }
                         let all_options = inner.best_next(ClusterVector::total_turns_pessimistic);
                         let all_best = all_options.iter().min_by(|l, r| l.0.cmp(&r.0)).unwrap();
 
@@ -356,7 +402,7 @@ pub fn order_by_responses(left: &Vec<Response>, right: &Vec<Response>) -> Orderi
 mod tests {
     use std::{path::Path, cmp::Ordering};
     use super::State;
-    use crate::{word::Word, response::Response};
+    use crate::{word::Word, response::Response, response_matrix::ResponseMatrix, cluster_vector::ClusterVector};
 
     #[test]
     fn state_basics() {
@@ -376,6 +422,37 @@ mod tests {
         let cv = state.to_cluster_vector();
         assert_eq!(cv.value, vec![2, 0, 0, 0, 1, 0, 1, 0, 0, 0, 1]);
         assert_eq!(cv.total_turns_pessimistic(), 111);
+
+        // Verify the same results when looking up responses
+        let matrix = ResponseMatrix::new(&guesses, &answers);
+        let mut with_matrix = State::new(&answers, &guesses);
+        with_matrix.set_matrix(&matrix);
+        with_matrix.filter(Word::new(&"vower").unwrap(), None);
+        assert_eq!(with_matrix.to_cluster_vector().value, cv.value);
+
+        let mut expected = state.best_next(ClusterVector::total_turns_pessimistic).into_iter().map(|(score, word, _)| (score, word)).collect::<Vec<_>>();
+        let mut actual = with_matrix.best_next(ClusterVector::total_turns_pessimistic).into_iter().map(|(score, word, _)| (score, word)).collect::<Vec<_>>();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+
+        // Information rankers work on the same cluster vectors
+        let mut expected = state.best_next(ClusterVector::rank_entropy).into_iter().map(|(score, word, _)| (score, word)).collect::<Vec<_>>();
+        let mut actual = with_matrix.best_next(ClusterVector::rank_entropy).into_iter().map(|(score, word, _)| (score, word)).collect::<Vec<_>>();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn state_json() {
+        let answers = ["fatal", "tally", "waltz", "dowdy"].iter().map(|w| Word::new(w).unwrap()).collect::<Vec<Word>>();
+        let mut state = State::new(&answers, &answers);
+        state.filter(Word::new("parse").unwrap(), None);
+        state.filter(Word::new("clint").unwrap(), Response::from_knowns_str(".l..t"));
+
+        // Only answers with ".l..t" for CLINT are left, in the cluster PARSE split them into
+        assert_eq!(state.to_json().to_string(), r#"{"guesses":["parse","clint"],"knowns":["*****",".l..t"],"cluster_vector":[0,0,1],"clusters":[{"responses":[".A...",".l..t"],"answers":["fatal","tally","waltz"]}]}"#);
     }
 
     #[test]
@@ -0,0 +1,158 @@
+use std::{collections::HashMap, fs, hash::Hash, path::Path};
+use rand::{Rng, seq::SliceRandom};
+use crate::{word::Word, rank::MapRanker, objective::TurnCounts};
+
+/// How likely each answer is, relative to the others (ex: from word frequency).
+///  Weights are scaled so the average answer weighs one, so weighted turn totals stay comparable to unweighted ones.
+///  Everything which takes weights treats None as every answer being equally likely.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnswerWeights {
+    weights: HashMap<Word, f64>
+}
+
+impl AnswerWeights {
+    /// Every answer equally likely
+    pub fn uniform(answers: &[Word]) -> AnswerWeights {
+        AnswerWeights { weights: answers.iter().map(|a| (*a, 1.0)).collect() }
+    }
+
+    /// Read weights for the answers from a file of 'word<TAB>weight' lines.
+    pub fn load(file_path: &Path, answers: &[Word]) -> Result<AnswerWeights, String> {
+        let text = fs::read_to_string(file_path).map_err(|e| format!("Unable to read '{:?}': {e}", file_path))?;
+        AnswerWeights::parse(&text, answers)
+    }
+
+    /// Parse 'word<TAB>weight' lines (blank lines are skipped).
+    ///  Words which aren't answers are ignored. Answers which aren't listed get the smallest listed weight.
+    pub fn parse(text: &str, answers: &[Word]) -> Result<AnswerWeights, String> {
+        let mut listed = HashMap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() { continue; }
+
+            let (word, weight) = line.split_once('\t').ok_or(format!("Line {}: expected 'word<TAB>weight', found '{line}'.", i + 1))?;
+            let word = Word::new(word.trim()).ok_or(format!("Line {}: '{word}' is not a valid Wordle word.", i + 1))?;
+            let weight = weight.trim().parse::<f64>().map_err(|_| format!("Line {}: '{weight}' is not a number.", i + 1))?;
+
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("Line {}: weights must be zero or more, found '{weight}'.", i + 1));
+            }
+
+            listed.insert(word, weight);
+        }
+
+        let default = listed.values().copied().reduce(f64::min).unwrap_or(1.0);
+        let mut weights = answers.iter().map(|a| (*a, listed.get(a).copied().unwrap_or(default))).collect::<HashMap<Word, f64>>();
+
+        let total: f64 = weights.values().sum();
+        if total <= 0.0 {
+            return Err("At least one answer must have a weight above zero.".to_string());
+        }
+
+        let scale = answers.len() as f64 / total;
+        for weight in weights.values_mut() {
+            *weight *= scale;
+        }
+
+        Ok(AnswerWeights { weights })
+    }
+
+    /// The weight for an answer; words which weren't answers weigh nothing.
+    pub fn weight(&self, word: Word) -> f64 {
+        self.weights.get(&word).copied().unwrap_or(0.0)
+    }
+
+    pub fn total(&self, cluster: &[Word]) -> f64 {
+        cluster.iter().map(|a| self.weight(*a)).sum()
+    }
+
+    /// The weight of the most likely answer in a cluster
+    pub fn heaviest(&self, cluster: &[Word]) -> f64 {
+        cluster.iter().map(|a| self.weight(*a)).fold(0.0, f64::max)
+    }
+
+    /// Choose an answer from a cluster with odds proportional to weight.
+    pub fn choose(&self, cluster: &[Word], rng: &mut impl Rng) -> Word {
+        match cluster.choose_weighted(rng, |a| self.weight(*a)) {
+            Ok(answer) => *answer,
+            Err(_) => *cluster.choose(rng).unwrap()
+        }
+    }
+
+    /// Scale a cluster's turn counts (for equally likely answers) by the average weight of its answers.
+    pub fn scale_counts(&self, counts: &TurnCounts, cluster: &[Word]) -> TurnCounts {
+        let mut result = TurnCounts::new();
+        if !cluster.is_empty() {
+            result.add_scaled(counts, self.total(cluster) / cluster.len() as f64);
+        }
+        result
+    }
+
+    /// Rank clusters with a ranker which sums over clusters (see rank::weighted_map_ranker), weighting each cluster by the average weight of its answers.
+    pub fn rank_map<T: Clone + Eq + Hash>(&self, map: &HashMap<T, Vec<Word>>, ranker: MapRanker<T>) -> usize {
+        let mut total = 0.0;
+        let mut single = HashMap::new();
+
+        for (key, cluster) in map.iter() {
+            single.clear();
+            single.insert(key.clone(), cluster.clone());
+            total += ranker(&single) as f64 * self.total(cluster) / cluster.len() as f64;
+        }
+
+        total.round() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{wv, w, rank, objective::TurnCounts};
+    use super::AnswerWeights;
+
+    #[test]
+    fn weights_parse() {
+        let answers = wv("booze, dodge, gouge, vogue");
+
+        // Weights scale to average one; unlisted answers get the smallest weight; other words are ignored
+        let weights = AnswerWeights::parse("booze\t1\ndodge\t5\n\ngouge\t1\ncrane\t100\n", &answers).unwrap();
+        assert_eq!(weights.weight(w("dodge")), 2.5);
+        assert_eq!(weights.weight(w("vogue")), 0.5);
+        assert_eq!(weights.weight(w("crane")), 0.0);
+        assert_eq!(weights.total(&answers), 4.0);
+        assert_eq!(weights.heaviest(&answers), 2.5);
+
+        assert!(AnswerWeights::parse("booze 1", &answers).is_err());
+        assert!(AnswerWeights::parse("booze\tlots", &answers).is_err());
+        assert!(AnswerWeights::parse("booze\t-1", &answers).is_err());
+        assert!(AnswerWeights::parse("booze\t0\ndodge\t0", &answers).is_err());
+    }
+
+    #[test]
+    fn weights_choose_and_rank() {
+        let answers = wv("booze, dodge, gouge, vogue");
+        let weights = AnswerWeights::parse("booze\t0\ndodge\t1", &answers).unwrap();
+
+        // Zero weight answers are never chosen
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            assert_eq!(weights.choose(&answers, &mut rng), w("dodge"));
+        }
+
+        // Cluster counts scale by average weight
+        let counts = TurnCounts::ideal(2, 0);
+        assert_eq!(weights.scale_counts(&counts, &wv("booze, dodge")).to_string(), "[2, 2]");
+
+        // Uniform weights rank the same as unweighted
+        let mut map = HashMap::new();
+        rank::split(&answers, w("vogue"), &mut map);
+        let uniform = AnswerWeights::uniform(&answers);
+        assert_eq!(uniform.rank_map(&map, rank::total_turns_pessimistic_map), rank::total_turns_pessimistic_map(&map));
+        assert_eq!(uniform.rank_map(&map, rank::expected_remaining_total_map), rank::expected_remaining_total_map(&map));
+
+        // Only DODGE has weight; clusters without it rank as free
+        let weighted = weights.rank_map(&map, rank::expected_remaining_total_map);
+        let dodge_cluster = map.values().find(|c| c.contains(&w("dodge"))).unwrap();
+        assert_eq!(weighted, dodge_cluster.len() * 4);
+    }
+}
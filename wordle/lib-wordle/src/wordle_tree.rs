@@ -1,7 +1,15 @@
-use std::{collections::BinaryHeap, str::Lines};
+use std::{collections::{BinaryHeap, HashMap}, str::Lines};
 use crate::{cluster_vector::ClusterVector, parser::Parser, word::Word, *};
 
 pub mod builders;
+pub mod complexity;
+pub mod diff;
+pub mod evaluate;
+pub mod graph;
+pub mod human;
+pub mod json;
+pub mod lint;
+pub mod retarget;
 pub mod tree_player;
 
 pub const LIST_ANSWERS_MAX_COUNT: usize = 16;
@@ -14,6 +22,7 @@ pub const LIST_ANSWERS_MAX_COUNT: usize = 16;
     WordleTree has a text syntax designed to be human readable and easy to parse,
     so that *both* humans and computers can easily play the strategy described.
  */
+#[derive(Clone)]
 pub struct WordleTree {
     // The total number of turns estimated to solve every answer in this subtree, including initial guesses to get to this subtree.
     pub outer_total_turns: f64,
@@ -27,27 +36,95 @@ pub struct WordleTree {
     // The next guess to use in this situation.
     pub next_guess: WordleGuess,
 
+    // True if next_guess can't be the answer; it's only guessed to split the remaining answers. Written as "(probe)" after the guess.
+    pub probe: bool,
+
     // The specific answers in this subtree. Included so that the specific words here can be output in the text form.
     pub answers: Option<Vec<Word>>,
 
     // The cluster vector after next_guess, for large trees.
     pub cluster_vector: Option<ClusterVector>,
 
+    // A note for people playing the strategy (from the human cheat sheet form); not written in the full text form.
+    pub comment: Option<String>,
+
     // Further strategy to handle situations after next_guess.
     // Never populated if this subtree is two answers or less. Not needed if all subtrees will be guessed randomly.
     pub subtree: Option<Vec<WordleTree>>
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum WordleGuess {
     Specific(Word),
-    Random
+    OneOf(Vec<Word>),       // The first listed word which is still a possible answer, or the first word if none are. Written "dumpy|mumpy".
+    MostCommonLetters,      // The possible answer whose letters are in the most possible answers (alphabetically first on ties). Written "*common".
+    FirstAnswer,            // The alphabetically first possible answer. Written "*first".
+    Random                  // A possible answer chosen uniformly at random. Written "*".
+}
+
+impl WordleGuess {
+    /// The word to guess with 'answers_left' still possible, or None to guess randomly.
+    pub fn choose(&self, answers_left: &[Word]) -> Option<Word> {
+        match self {
+            WordleGuess::Specific(word) => Some(*word),
+            WordleGuess::OneOf(words) => words.iter().find(|w| answers_left.contains(w)).or(words.first()).copied(),
+            WordleGuess::MostCommonLetters => {
+                let mut counts = [0usize; 26];
+                for answer in answers_left.iter() {
+                    let letters = answer.letters_in_word();
+                    for (letter, count) in counts.iter_mut().enumerate() {
+                        if letters & (1 << letter) != 0 { *count += 1; }
+                    }
+                }
+
+                let score = |word: &Word| (0..26).filter(|l| word.letters_in_word() & (1 << l) != 0).map(|l| counts[l]).sum::<usize>();
+                answers_left.iter().max_by(|l, r| score(l).cmp(&score(r)).then(r.cmp(l))).copied()
+            },
+            WordleGuess::FirstAnswer => answers_left.iter().min().copied(),
+            WordleGuess::Random => None,
+        }
+    }
+
+    /// The word guessed, if it is the same whatever answers are left.
+    pub fn specific(&self) -> Option<Word> {
+        match self {
+            WordleGuess::Specific(word) => Some(*word),
+            _ => None
+        }
+    }
+
+    /// Parse a guess in the tree text: a word, "*", "*first", "*common", or words separated by '|'.
+    pub fn parse(text: &str) -> Option<WordleGuess> {
+        match text {
+            "*" => Some(WordleGuess::Random),
+            "*first" => Some(WordleGuess::FirstAnswer),
+            "*common" => Some(WordleGuess::MostCommonLetters),
+            _ if text.contains('|') => text.split('|').map(Word::new).collect::<Option<Vec<Word>>>().map(WordleGuess::OneOf),
+            _ => Word::new(text).map(WordleGuess::Specific),
+        }
+    }
+}
+
+impl std::fmt::Display for WordleGuess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WordleGuess::Specific(word) => write!(f, "{word}"),
+            WordleGuess::OneOf(words) => f.write_str(&words.iter().map(|w| w.to_string()).collect::<Vec<String>>().join("|")),
+            WordleGuess::MostCommonLetters => f.write_str("*common"),
+            WordleGuess::FirstAnswer => f.write_str("*first"),
+            WordleGuess::Random => f.write_str("*"),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum WordleTreeIdentifier {
     Any,                            // Applies to all games at the given turn
+    KnownLettersUnder(usize),       // Applies when fewer than (usize) letters are known (green or yellow tiles across every guess so far; see known_letters)
+    KnownLettersAtLeast(usize),     // Applies when at least (usize) letters are known (see known_letters)
+    UnderLength(usize),             // Applies when fewer than (usize) possible answers remain
     EqualsLength(usize),            // Applies when the number of remaining possible answers matches (usize)
+    Pattern([Option<u8>; 5]),       // Applies when every remaining possible answer has the given letters (0-25) in the given positions
     Response(Word, Response),       // Applies when the last guess has the given response    
     Cluster(Word),                  // Applies when the alphabetically first remaining possible answer is (Word)
 }
@@ -61,27 +138,62 @@ impl WordleTreeIdentifier {
         }
     }
 
-    /// Return whether this identifier is a match for the given cluster.
-    pub fn matches(&self, last_guess: Option<Word>, last_response: Option<Response>, cluster: &Vec<Word>) -> bool {
+    /// Return whether this identifier is a match for the given cluster, with 'known_letters' green or yellow tiles shown so far in the game.
+    pub fn matches(&self, last_guess: Option<Word>, last_response: Option<Response>, known_letters: usize, cluster: &Vec<Word>) -> bool {
         match self {
             WordleTreeIdentifier::Any => true,
+            WordleTreeIdentifier::KnownLettersUnder(count) => known_letters < *count,
+            WordleTreeIdentifier::KnownLettersAtLeast(count) => known_letters >= *count,
+            WordleTreeIdentifier::UnderLength(length) => cluster.len() < *length,
             WordleTreeIdentifier::EqualsLength(length) => cluster.len() == *length,
+            WordleTreeIdentifier::Pattern(pattern) => !cluster.is_empty() && cluster.iter().all(|answer| answer.iter_index().zip(pattern.iter()).all(|(letter, p)| p.is_none_or(|p| p == letter))),
             WordleTreeIdentifier::Cluster(cluster_word) => cluster.first().map(|w| w == cluster_word).unwrap_or(false),
             WordleTreeIdentifier::Response(guess, response) => last_guess.is_some_and(|g| g == *guess) && last_response.is_some_and(|r| r == *response),
         }
     }
 
-    /// Response > Cluster > EqualsLength > Any; same as sort order.
+    /// Cluster > Response > Pattern > EqualsLength > UnderLength > KnownLettersAtLeast > KnownLettersUnder > Any; same as sort order.
     pub fn is_more_specific(&self, other: &WordleTreeIdentifier) -> bool {
         self > other
     }
 }
 
+/// The number of green or yellow tiles shown for 'answer' across a sequence of guesses. A letter is counted again each time it is shown.
+pub fn known_letters(guesses: &[Word], answer: Word) -> usize {
+    guesses.iter().map(|guess| Response::score(*guess, answer).known_count() as usize).sum()
+}
+
+/// Parse a letter pattern, like "?O?RE": five letters or '?'s, with at least one '?' (so it isn't a word).
+pub fn parse_pattern(text: &str) -> Option<[Option<u8>; 5]> {
+    if text.len() != 5 || !text.contains('?') { return None; }
+
+    let mut pattern = [None; 5];
+    for (p, c) in pattern.iter_mut().zip(text.bytes()) {
+        match c {
+            b'?' => {},
+            b'a'..=b'z' => *p = Some(c - b'a'),
+            b'A'..=b'Z' => *p = Some(c - b'A'),
+            _ => return None,
+        }
+    }
+
+    Some(pattern)
+}
+
+/// Write a letter pattern with uppercase letters and '?'s.
+pub fn pattern_string(pattern: &[Option<u8>; 5]) -> String {
+    pattern.iter().map(|p| p.map(|l| (b'A' + l) as char).unwrap_or('?')).collect()
+}
+
 
 impl std::fmt::Display for WordleTreeIdentifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             WordleTreeIdentifier::Any => { return f.write_str("*"); },
+            WordleTreeIdentifier::KnownLettersUnder(count) => write!(f, "known < {count}"),
+            WordleTreeIdentifier::KnownLettersAtLeast(count) => write!(f, "{count}+"),
+            WordleTreeIdentifier::UnderLength(l) => write!(f, "< {l}"),
+            WordleTreeIdentifier::Pattern(pattern) => f.write_str(&pattern_string(pattern)),
             WordleTreeIdentifier::EqualsLength(l) => { return f.write_str(&format!("= {l}")); },
             WordleTreeIdentifier::Cluster(word) => { return f.write_str(&word.to_string()); },
             WordleTreeIdentifier::Response(guess, response) => { return f.write_str(&response.to_knowns_string(guess)); }
@@ -98,6 +210,7 @@ pub struct WordleTreeToStringOptions {
     pub always_show_identifiers: bool,      // True to show identifier even if there are few enough answers to show all of them
 
     pub show_zero_turn_paths: bool,         // False to suppress paths which weren't encountered at all in the simulation
+    pub show_turn_histogram: bool,          // True to show games solved on each turn (and lost) through each node in the simulation
 }
 
 impl WordleTreeToStringOptions {
@@ -108,7 +221,8 @@ impl WordleTreeToStringOptions {
             show_cluster_vectors: true, 
             show_answers: true, 
             always_show_identifiers: false,
-            show_zero_turn_paths: false
+            show_zero_turn_paths: false,
+            show_turn_histogram: false
         }
     }
 }
@@ -120,8 +234,10 @@ impl WordleTree {
             identifier: identifier, 
             answer_count: 0, 
             next_guess: next_guess, 
+            probe: false,
             answers: None, 
             cluster_vector: None,
+            comment: None,
             subtree: None
         }
     }
@@ -132,8 +248,10 @@ impl WordleTree {
             identifier: WordleTreeIdentifier::Cluster(word),
             answer_count: 1,
             next_guess: WordleGuess::Random,
+            probe: false,
             answers: Some(vec![word]),
             cluster_vector: None,
+            comment: None,
             subtree: None
         }
     }
@@ -144,8 +262,10 @@ impl WordleTree {
             identifier: WordleTreeIdentifier::Cluster(*answers.first().unwrap()),
             answer_count: answers.len(),
             next_guess: WordleGuess::Random,
+            probe: false,
             answers: Some(answers),
             cluster_vector: None,
+            comment: None,
             subtree: None
         }
     }
@@ -156,8 +276,10 @@ impl WordleTree {
             identifier: WordleTreeIdentifier::Any,
             answer_count: 0,
             next_guess: WordleGuess::Random,
+            probe: false,
             answers: None,
             cluster_vector: None,
+            comment: None,
             subtree: None
         }
     }
@@ -199,6 +321,86 @@ impl WordleTree {
         }
     }
 
+    /// Find the child which applies after this node's guess for a cluster: the most specific match (see is_more_specific), and its index.
+    ///  'known_letters' is the number of green or yellow tiles shown so far in the game, including for the last guess.
+    pub fn matching_child(&self, last_guess: Option<Word>, last_response: Option<Response>, known_letters: usize, cluster: &Vec<Word>) -> Option<(usize, &WordleTree)> {
+        let mut best: Option<(usize, &WordleTree)> = None;
+
+        if let Some(subtree) = &self.subtree {
+            for (i, child) in subtree.iter().enumerate() {
+                if child.identifier.matches(last_guess, last_response, known_letters, cluster) {
+                    if best.is_none_or(|b| child.identifier.is_more_specific(&b.1.identifier)) {
+                        best = Some((i, child));
+                    }
+
+                    if child.identifier.is_cluster() { break; }
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The node at 'path' (child indices from this node)
+    pub fn node_at(&self, path: &[usize]) -> &WordleTree {
+        path.iter().fold(self, |node, i| &node.subtree.as_ref().unwrap()[*i])
+    }
+
+    /// Find the clusters of answers reaching each node, by the path of child indices to it, following the same matching rules as TreePlayer.
+    pub fn clusters_by_node(&self, answers: &[Word]) -> HashMap<Vec<usize>, Vec<Vec<Word>>> {
+        self.known_clusters_by_node(answers).into_iter()
+            .map(|(path, clusters)| (path, clusters.into_iter().map(|(cluster, _)| cluster).collect()))
+            .collect()
+    }
+
+    /// Find the clusters reaching each node as clusters_by_node does, with the green or yellow tiles shown for each so far in the game.
+    pub fn known_clusters_by_node(&self, answers: &[Word]) -> HashMap<Vec<usize>, Vec<(Vec<Word>, usize)>> {
+        let mut result = HashMap::new();
+        self.assign_clusters(vec![(answers.to_vec(), 0)], &mut Vec::new(), &mut result);
+        result
+    }
+
+    fn assign_clusters(&self, node_clusters: Vec<(Vec<Word>, usize)>, path: &mut Vec<usize>, result: &mut HashMap<Vec<usize>, Vec<(Vec<Word>, usize)>>) {
+        let mut by_child: HashMap<usize, Vec<(Vec<Word>, usize)>> = HashMap::new();
+        let mut map = HashMap::new();
+
+        // Guessing policies may choose a different word for each cluster
+        for (cluster, known_letters) in node_clusters.iter() {
+            let Some(guess) = self.next_guess.choose(cluster) else { continue; };
+
+            rank::split(cluster, guess, &mut map);
+            for (response, subcluster) in map.drain() {
+                let known_letters = known_letters + response.known_count() as usize;
+                if let Some((i, _)) = self.matching_child(Some(guess), Some(response), known_letters, &subcluster) {
+                    by_child.entry(i).or_default().push((subcluster, known_letters));
+                }
+            }
+        }
+
+        for (i, child_clusters) in by_child {
+            path.push(i);
+            self.subtree.as_ref().unwrap()[i].assign_clusters(child_clusters, path, result);
+            path.pop();
+        }
+
+        result.insert(path.clone(), node_clusters);
+    }
+
+    /// A one-line description of this node, as "(identifier, answer count) -> guess"
+    pub fn describe(&self) -> String {
+        let guess = &self.next_guess;
+        match self.identifier {
+            WordleTreeIdentifier::Any => format!("(*, {}) -> {guess}", self.answer_count),
+            WordleTreeIdentifier::KnownLettersUnder(count) => format!("(known < {count}, {}) -> {guess}", self.answer_count),
+            WordleTreeIdentifier::KnownLettersAtLeast(count) => format!("({count}+, {}) -> {guess}", self.answer_count),
+            WordleTreeIdentifier::UnderLength(length) => format!("(< {length}, {}) -> {guess}", self.answer_count),
+            WordleTreeIdentifier::Pattern(pattern) => format!("({}, {}) -> {guess}", pattern_string(&pattern), self.answer_count),
+            WordleTreeIdentifier::EqualsLength(length) => format!("(= {length}, {}) -> {guess}", self.answer_count),
+            WordleTreeIdentifier::Cluster(word) => format!("({word}, {}) -> {guess}", self.answer_count),
+            WordleTreeIdentifier::Response(last, response) => format!("(> {}, {}) -> {guess}", Response::to_knowns_string(&response, &last), self.answer_count),
+        }
+    }
+
     pub fn take_first_child(&mut self) -> Option<WordleTree> {
         if let Some(subtree) = &mut self.subtree {
             return subtree.pop();
@@ -217,16 +419,24 @@ impl WordleTree {
         // Add this node to the output string
         self.add_self_to_string(options, indent, result);
 
-        // Further indented subtree, if included, 
-        // *ordered by* : answer count desc, then identifier asc, then next_guess asc
-        if let Some(subtree) = &self.subtree {
-            let mut ordered = subtree.iter().collect::<BinaryHeap<_>>();
-            while let Some(child) = ordered.pop() {
-                child.add_to_string(options, indent + 1, result);
-            }
+        // Further indented subtree, if included
+        for child in self.ordered_children() {
+            child.add_to_string(options, indent + 1, result);
         }
     }
 
+    /// The children in the order the text form writes them: answer count desc, then specific guesses before random, then identifier.
+    pub fn ordered_children(&self) -> Vec<&WordleTree> {
+        let mut ordered = self.subtree.iter().flatten().collect::<BinaryHeap<_>>().into_sorted_vec();
+        ordered.reverse();
+        ordered
+    }
+
+    /// The number of nodes in this subtree, including this one.
+    pub fn node_count(&self) -> usize {
+        1 + self.subtree.iter().flatten().map(|child| child.node_count()).sum::<usize>()
+    }
+
     pub fn add_self_to_string(&self, options: &WordleTreeToStringOptions, indent: usize, result: &mut String) {
         // Indent
         let line_start = result.len();
@@ -254,6 +464,18 @@ impl WordleTree {
                 WordleTreeIdentifier::Cluster(ref word) => {
                     result.push_str(&format!("({}, {})", word.to_string(), self.answer_count));
                 },
+                WordleTreeIdentifier::KnownLettersUnder(count) => {
+                    result.push_str(&format!("(known < {}, {})", count, self.answer_count));
+                },
+                WordleTreeIdentifier::KnownLettersAtLeast(count) => {
+                    result.push_str(&format!("({}+, {})", count, self.answer_count));
+                },
+                WordleTreeIdentifier::UnderLength(length) => {
+                    result.push_str(&format!("(< {}, {})", length, self.answer_count));
+                },
+                WordleTreeIdentifier::Pattern(ref pattern) => {
+                    result.push_str(&format!("({}, {})", pattern_string(pattern), self.answer_count));
+                },
                 WordleTreeIdentifier::EqualsLength(length) => {
                     result.push_str(&format!("(= {}, {})", length, self.answer_count));
                 },
@@ -267,13 +489,10 @@ impl WordleTree {
 
             // -> next_guess
             result.push_str(&" -> ");
-            match self.next_guess {
-                WordleGuess::Random => {
-                    result.push_str("*");
-                },
-                WordleGuess::Specific(ref word) => {
-                    result.push_str(&format!("{}", word.to_string()));
-                }
+            result.push_str(&self.next_guess.to_string());
+
+            if self.probe {
+                result.push_str(" (probe)");
             }
         }
 
@@ -315,9 +534,20 @@ impl WordleTree {
     }
 
 
-    /// Parse a full WordleTree from text
+    /// Parse a full WordleTree from text, in either the full form or the human cheat sheet form
     pub fn parse(text: Lines) -> Result<WordleTree, String> {
+        WordleTree::parse_with_line_numbers(text).map(|(tree, _)| tree).map_err(|(_, message)| message)
+    }
+
+    /// Parse a full WordleTree from text, also returning the line number of each node in file order (a pre-order walk of the tree).
+    ///  Errors include the line number where parsing failed.
+    pub fn parse_with_line_numbers(text: Lines) -> Result<(WordleTree, Vec<usize>), (usize, String)> {
+        if WordleTree::is_human_format(text.clone()) {
+            return WordleTree::parse_human(text);
+        }
+
         let mut parser = Parser::new(text);
+        let mut line_numbers = Vec::new();
         let mut parent_stack: Vec<(usize, WordleTree)> = Vec::new();
         
         loop {
@@ -337,16 +567,15 @@ impl WordleTree {
             let mut last_guess = None;
             if let Some((p_indent, leaf)) = last {
                 // Retrieve the specific guess just before the new node
-                if let WordleGuess::Specific(word) = leaf.next_guess {
-                    last_guess = Some(word);
-                }
+                last_guess = leaf.next_guess.specific();
 
                 // Put the last removed node back (the parent of the current line node)
                 parent_stack.push((p_indent, leaf));
             }
 
             // Parse the new node
-            let tree = WordleTree::parse_single(&mut parser, last_guess)?;
+            line_numbers.push(parser.line_number);
+            let tree = WordleTree::parse_single(&mut parser, last_guess).map_err(|message| (parser.line_number, message))?;
             
             // Add the newly parsed node
             parent_stack.push((indent, tree));
@@ -365,7 +594,7 @@ impl WordleTree {
             last = Some(parent);
         }
 
-        Ok(last.unwrap())
+        Ok((last.unwrap(), line_numbers))
     }
 
     /// Parse a single WordleTree node from the current line of text being parsed
@@ -389,6 +618,15 @@ impl WordleTree {
             if parser.current == "=" {
                 parser.next()?;
                 result.identifier = WordleTreeIdentifier::EqualsLength(parser.as_usize()?);
+            } else if parser.current == "<" {
+                parser.next()?;
+                result.identifier = WordleTreeIdentifier::UnderLength(parser.as_usize()?);
+            } else if parser.current == "known" {
+                parser.next()?;
+                parser.require("<")?;
+                result.identifier = WordleTreeIdentifier::KnownLettersUnder(parser.as_usize()?);
+            } else if let Some(pattern) = parse_pattern(&parser.current) {
+                result.identifier = WordleTreeIdentifier::Pattern(pattern);
             } else if parser.current == ">" {
                 parser.next()?;
                 if let Some(last_guess) = last_guess {
@@ -397,6 +635,8 @@ impl WordleTree {
                 } else {
                     return Err(parser.error("Response node found without known specific previous guess."));
                 }
+            } else if let Some(count) = parser.current.strip_suffix('+').and_then(|c| c.parse::<usize>().ok()) {
+                result.identifier = WordleTreeIdentifier::KnownLettersAtLeast(count);
             } else if let Some(word) = parser.as_word()? {
                 result.identifier = WordleTreeIdentifier::Cluster(word);
             } else {
@@ -414,13 +654,20 @@ impl WordleTree {
             // Next Guess
             parser.require("->")?;
 
-            if let Some(word) = parser.as_word()? { 
-                result.next_guess = WordleGuess::Specific(word);
-            } else {
-                result.next_guess = WordleGuess::Random;
+            match WordleGuess::parse(&parser.current) {
+                Some(guess) => result.next_guess = guess,
+                None => return Err(parser.error("Not a valid word, '*', '*first', '*common', or 'word|word'")),
             }
-            
+
             parser.next()?;
+
+            // (probe)?
+            if parser.current == "(" {
+                parser.next()?;
+                parser.require("probe")?;
+                parser.require(")")?;
+                result.probe = true;
+            }
         }
 
         // Cluster Vector?
@@ -621,24 +868,113 @@ mod tests {
         let cluster = vec![w("fatal"), w("tally"), w("waltz")];
 
         // Any matches any cluster
-        assert!(WordleTreeIdentifier::Any.matches(guess, response, &cluster));
+        assert!(WordleTreeIdentifier::Any.matches(guess, response, 0, &cluster));
 
         // EqualsLength matches clusters of the same length
-        assert!(WordleTreeIdentifier::EqualsLength(3).matches(guess, response, &cluster));
-        assert!(!WordleTreeIdentifier::EqualsLength(2).matches(guess, response, &cluster));
+        assert!(WordleTreeIdentifier::EqualsLength(3).matches(guess, response, 0, &cluster));
+        assert!(!WordleTreeIdentifier::EqualsLength(2).matches(guess, response, 0, &cluster));
 
         // Cluster matches only the cluster with the same first word
-        assert!(WordleTreeIdentifier::Cluster(w("fatal")).matches(guess, response, &cluster));
-        assert!(!WordleTreeIdentifier::Cluster(w("tally")).matches(guess, response, &cluster));
+        assert!(WordleTreeIdentifier::Cluster(w("fatal")).matches(guess, response, 0, &cluster));
+        assert!(!WordleTreeIdentifier::Cluster(w("tally")).matches(guess, response, 0, &cluster));
 
         let guess = w("soare");
         let response = Response::score(guess, cluster[0]);
 
-        assert!(WordleTreeIdentifier::Response(guess, response).matches(Some(guess), Some(response), &cluster));        // Both Match
-        assert!(!WordleTreeIdentifier::Response(guess, response).matches(Some(guess), None, &cluster));                 // No response
-        assert!(!WordleTreeIdentifier::Response(guess, response).matches(None, Some(response), &cluster));              // No guess
-        assert!(!WordleTreeIdentifier::Response(guess, response).matches(Some(w("waltz")), Some(response), &cluster));  // Different guess, same response
-        assert!(!WordleTreeIdentifier::Response(guess, response).matches(Some(guess), Some(Response::new(0)), &cluster));  // Same guess, different response
+        assert!(WordleTreeIdentifier::Response(guess, response).matches(Some(guess), Some(response), 0, &cluster));        // Both Match
+        assert!(!WordleTreeIdentifier::Response(guess, response).matches(Some(guess), None, 0, &cluster));                 // No response
+        assert!(!WordleTreeIdentifier::Response(guess, response).matches(None, Some(response), 0, &cluster));              // No guess
+        assert!(!WordleTreeIdentifier::Response(guess, response).matches(Some(w("waltz")), Some(response), 0, &cluster));  // Different guess, same response
+        assert!(!WordleTreeIdentifier::Response(guess, response).matches(Some(guess), Some(Response::new(0)), 0, &cluster));  // Same guess, different response
+    }
+
+    #[test]
+    fn test_identifier_conditions() {
+        let cluster = vec![w("fatal"), w("tally"), w("waltz")];
+
+        // Specificity: Cluster > Response > Pattern > EqualsLength > UnderLength > KnownLettersAtLeast > KnownLettersUnder > Any
+        let pattern = WordleTreeIdentifier::Pattern(parse_pattern("?A???").unwrap());
+        assert!(WordleTreeIdentifier::Any < WordleTreeIdentifier::KnownLettersUnder(3));
+        assert!(WordleTreeIdentifier::KnownLettersUnder(3) < WordleTreeIdentifier::KnownLettersAtLeast(3));
+        assert!(WordleTreeIdentifier::KnownLettersAtLeast(3) < WordleTreeIdentifier::UnderLength(3));
+        assert!(WordleTreeIdentifier::UnderLength(3) < WordleTreeIdentifier::EqualsLength(3));
+        assert!(WordleTreeIdentifier::EqualsLength(3) < pattern);
+        assert!(pattern < WordleTreeIdentifier::Response(w("soare"), Response::new(0)));
+
+        // UnderLength matches smaller clusters only
+        assert!(WordleTreeIdentifier::UnderLength(4).matches(None, None, 0, &cluster));
+        assert!(!WordleTreeIdentifier::UnderLength(3).matches(None, None, 0, &cluster));
+
+        // Known letter counts compare with the tiles shown so far in the game
+        assert!(WordleTreeIdentifier::KnownLettersUnder(3).matches(None, None, 2, &cluster));
+        assert!(!WordleTreeIdentifier::KnownLettersUnder(3).matches(None, None, 3, &cluster));
+        assert!(WordleTreeIdentifier::KnownLettersAtLeast(3).matches(None, None, 3, &cluster));
+        assert!(!WordleTreeIdentifier::KnownLettersAtLeast(3).matches(None, None, 2, &cluster));
+
+        // PARSE shows TALLY's 'a', then CLINT shows its 'l' and 't'
+        assert_eq!(known_letters(&[w("parse"), w("clint")], w("tally")), 3);
+
+        // FATAL, TALLY, and WALTZ all have an 'a' second; only TALLY and WALTZ have an 'l' third
+        assert!(pattern.matches(None, None, 0, &cluster));
+        assert!(!WordleTreeIdentifier::Pattern(parse_pattern("?al??").unwrap()).matches(None, None, 0, &cluster));
+        assert!(WordleTreeIdentifier::Pattern(parse_pattern("?al??").unwrap()).matches(None, None, 0, &cluster[1..].to_vec()));
+
+        // Patterns need a '?', so words are still Cluster identifiers
+        assert_eq!(parse_pattern("fatal"), None);
+        assert_eq!(parse_pattern("?a?"), None);
+        assert_eq!(pattern_string(&parse_pattern("?o?re").unwrap()), "?O?RE");
+
+        // Each parses and writes back the same way
+        let text =
+r#"100   (*, 2315)  -> soare
+    60    (< 40, 30) -> *
+    50    (known < 2, 20) -> clint
+    40    (?O?RE, 10) -> *
+"#;
+        let tree = WordleTree::parse(text.lines()).unwrap();
+        let children = tree.subtree.as_ref().unwrap();
+        assert_eq!(children[0].identifier, WordleTreeIdentifier::UnderLength(40));
+        assert_eq!(children[1].identifier, WordleTreeIdentifier::KnownLettersUnder(2));
+        assert_eq!(children[2].identifier, WordleTreeIdentifier::Pattern([None, Some(14), None, Some(17), Some(4)]));
+        assert_eq!(children[1].describe(), "(known < 2, 20) -> clint");
+        assert_eq!(smart_trim(&tree.to_string()), smart_trim(text));
+    }
+
+    #[test]
+    fn test_guess_policies() {
+        let cluster = wv("dowdy, fatal, tally, waltz");
+
+        // WALTZ shares the most letters with the others; DOWDY is first
+        assert_eq!(WordleGuess::MostCommonLetters.choose(&cluster), Some(w("waltz")));
+        assert_eq!(WordleGuess::FirstAnswer.choose(&cluster[1..]), Some(w("fatal")));
+        assert_eq!(WordleGuess::Random.choose(&cluster), None);
+
+        // OneOf guesses the first word still possible, or the first word
+        let one_of = WordleGuess::parse("clint|tally|dowdy").unwrap();
+        assert_eq!(one_of, WordleGuess::OneOf(wv("clint, tally, dowdy")));
+        assert_eq!(one_of.choose(&cluster), Some(w("tally")));
+        assert_eq!(one_of.choose(&wv("fatal")), Some(w("clint")));
+        assert_eq!(one_of.specific(), None);
+
+        assert_eq!(WordleGuess::parse("first"), Some(WordleGuess::Specific(w("first"))));
+        assert_eq!(WordleGuess::parse("clint|tal"), None);
+        assert_eq!(WordleGuess::parse("*last"), None);
+
+        // Each parses and writes back the same way
+        let text =
+r#"100   (*, 2315)  -> soare
+    60    (= 3, 30)  -> *common
+    50    (< 3, 20)  -> *first
+    40    (*, 10)    -> clint|dumpy
+"#;
+        let tree = WordleTree::parse(text.lines()).unwrap();
+        let children = tree.subtree.as_ref().unwrap();
+        assert_eq!(children[0].next_guess, WordleGuess::MostCommonLetters);
+        assert_eq!(children[1].next_guess, WordleGuess::FirstAnswer);
+        assert_eq!(children[2].describe(), "(*, 10) -> clint|dumpy");
+        assert_eq!(smart_trim(&tree.to_string()), smart_trim(text));
+
+        assert_eq!(WordleTree::parse("(*, 10) -> soare|".lines()).err().unwrap(), "@(1, 12) \"soare|\": Not a valid word, '*', '*first', '*common', or 'word|word'");
     }
 
     #[test]
@@ -681,6 +1017,14 @@ mod tests {
         assert_eq!(tree.identifier, WordleTreeIdentifier::Response(w("soare"), Response::from_str("bGbyy").unwrap()));
         assert_eq!(tree.answer_count, 12);
         assert_eq!(tree.next_guess, WordleGuess::Specific(w("mawky")));
+        assert!(!tree.probe);
+
+        // Probe guess (can't be the answer)
+        let mut parser = Parser::new("    14 (batch, 6) -> clamp (probe)  [6]  {batch, catch, hatch, latch, match, watch}".lines());
+        let tree = WordleTree::parse_single(&mut parser, None).unwrap();
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("clamp")));
+        assert!(tree.probe);
+        assert_eq!(tree.answer_count, 6);
 
         // Equals Length cluster, no CV, no answers
         let mut parser = Parser::new("        48 (= 1, 12) -> *".lines());
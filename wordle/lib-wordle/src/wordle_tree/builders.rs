@@ -1,10 +1,13 @@
-use std::{collections::{HashMap, VecDeque}, mem};
-use crate::{word::Word, wordle_tree::{*, self}, rank, response::ResponseSet};
+use std::{cmp::Ordering, collections::{HashMap, VecDeque}, mem};
+use crate::{word::Word, wordle_tree::{*, self}, rank, response::ResponseSet, hard_mode::HardMode, objective::{Objective, TurnCounts}, weights::AnswerWeights};
+use strategy::Strategy;
 
-// FUTURE:
-//  Currently, builders which split the tree (make different specific choices per situation) can't recurse by using the overall construction strategy; they don't get a handle to it.
-//  The strategy would have to be a member in BuilderState to allow it to be passed itself.
-//  I *think* Rust may be unwilling to do this because a closure that changes state is an FnMut, and must be mutable, and can't be borrowed mutably while it's being borrowed already (which would be the case recursively).
+pub mod strategy;
+
+// NOTE:
+//  Builders which split the tree (make different specific choices per situation) can't recurse by using the overall construction closure; they don't get a handle to it.
+//  A closure that changes state is an FnMut, and can't be borrowed mutably while it's being borrowed already (which would be the case recursively).
+//  Strategy (strategy.rs) describes chains as data instead, so 'each' can give every cluster its own copy of a sub-strategy to recurse with.
 
 // Strategy Situations:
 //  - Pass:       Take nothing off the map, don't create any new nodes, and return None to tell the next strategy to try.
@@ -13,14 +16,6 @@ use crate::{word::Word, wordle_tree::{*, self}, rank, response::ResponseSet};
 //  - Branch:     Create multiple nodes and potentially recurse to finish the game. 
 
 // TODO:
-//  - guess_best_until_done should consider out-of-cluster options when appropriate.
-//     - Only for 4+ clusters. Only when best in-cluster is more than two additional turns on average.
-//     - May need to set maximum cluster size. Hundreds of cluster words would leave too much recursive rework.
-//     - Sort valid by ideal turns; stop when remaining option ideal turns are worse than current best.
-//     - Stop early when any valid option has fully split the remaining answers.
-//     - DO NOT recurse when option only produces one cluster (infinite recursion).
-//     - Put 'valid' on BuilderState to get it through.
-
 //  - Print options on WordleTree? (Hide answers. Hide CVs. Total/Avg Turns)
 //  - Triples: guess_safe_under_length only? Interesting but not playable unless the other clusters are all handled individually, which is guess_best.
 
@@ -32,28 +27,87 @@ use crate::{word::Word, wordle_tree::{*, self}, rank, response::ResponseSet};
 
 /// Build constructs a WordleTree for a given strategy and set of answers and guesses.
 ///  It uses composable strategy parts to choose the next guess for each situation.
-pub fn build(strategy: &str, answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
+///  In hard mode, specific guesses are only used for clusters where they use all known hints.
+///  Strategies which choose between guesses ('best') choose the one best for the objective, including valid guesses which can't be the answer when they split a cluster better.
+///  With weights, they count each answer in proportion to how likely it is.
+///  Strategies other than the named ones are parsed as a Strategy chain (ex: "random<=3 | standard(soare, clint) | best"), which must not be passed guesses.
+pub fn build(strategy: &str, answers: &Vec<Word>, valid: &[Word], guesses: &Vec<Word>, hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> Result<WordleTree, String> {
     match strategy {
-        "standard" => build_standard(answers, guesses),
-        "hybrid"   => build_hybrid(answers, guesses),
-        "best"     => build_best(answers, guesses),
-        "first"    => build_first(answers, guesses),
-        "v11"      => build_v11(answers, guesses),
-        _          => panic!("Unknown strategy: {}", strategy)
+        "standard" => Ok(build_standard(answers, valid, guesses, hard_mode, objective, weights)),
+        "hybrid"   => Ok(build_hybrid(answers, valid, guesses, hard_mode, objective, weights)),
+        "best"     => Ok(build_best(answers, valid, guesses, hard_mode, objective, weights)),
+        "first"    => Ok(build_first(answers, valid, guesses, hard_mode, objective, weights)),
+        "v11"      => Ok(build_v11(answers, valid, guesses, hard_mode, objective, weights)),
+        _          => {
+            // A chain names its own guesses (in 'standard(...)' parts), so extra ones would be ignored
+            let chain = Strategy::parse(strategy)?;
+            if !guesses.is_empty() {
+                return Err(format!("Strategy chain '{chain}' takes its guesses in 'standard(...)' parts, not after it; found {}.", guesses.iter().map(|g| g.to_string()).collect::<Vec<String>>().join(", ")));
+            }
+
+            Ok(chain.build(answers, valid, hard_mode, objective, weights))
+        }
     }
 }
 
-struct BuilderState {
+// Out-of-cluster guesses are only considered for clusters up to this size, to limit recursive rework
+const OUT_OF_CLUSTER_MAX_COUNT: usize = 32;
+
+struct BuilderState<'a> {
     pub map: HashMap<ResponseSet, Vec<Word>>,
-    pub turns_before: usize
+    pub valid: &'a [Word],
+    pub turns_before: usize,
+    pub hard_mode: bool,
+    pub objective: Objective,
+    pub weights: Option<&'a AnswerWeights>,
+
+    // The specific guesses made for every cluster in the map so far
+    pub guesses: Vec<Word>
 }
 
-impl BuilderState {
-    pub fn new(answers: &Vec<Word>) -> BuilderState {
+impl BuilderState<'_> {
+    pub fn new<'a>(answers: &Vec<Word>, valid: &'a [Word], hard_mode: bool, objective: Objective, weights: Option<&'a AnswerWeights>) -> BuilderState<'a> {
         let mut map: HashMap<ResponseSet, Vec<Word>> = HashMap::new();
         map.insert(ResponseSet::new(), answers.clone());
 
-        BuilderState { map, turns_before: 0, }
+        BuilderState { map, valid, turns_before: 0, hard_mode, objective, weights, guesses: Vec::new() }
+    }
+
+    /// Turn counts for a cluster with equally likely answers, scaled to the likelihood of its answers
+    pub fn weighted(&self, counts: TurnCounts, cluster: &[Word]) -> TurnCounts {
+        match self.weights {
+            Some(weights) => weights.scale_counts(&counts, cluster),
+            None => counts
+        }
+    }
+
+    /// The weight of a single answer
+    pub fn weight(&self, answer: Word) -> f64 {
+        self.weights.map(|w| w.weight(answer)).unwrap_or(1.0)
+    }
+
+    /// The number of answers in a cluster, or their total weight if answers aren't equally likely
+    pub fn cluster_weight(&self, cluster: &[Word]) -> f64 {
+        self.weights.map(|w| w.total(cluster)).unwrap_or(cluster.len() as f64)
+    }
+
+    /// The ideal outcome for a cluster: the likeliest answer guessed next and every other answer the turn after
+    pub fn ideal(&self, cluster: &[Word], turns_before: usize) -> TurnCounts {
+        match self.weights {
+            Some(weights) => {
+                let heaviest = weights.heaviest(cluster);
+                let mut result = TurnCounts::new();
+                result.add(turns_before + 1, heaviest);
+                result.add(turns_before + 2, weights.total(cluster) - heaviest);
+                result
+            },
+            None => TurnCounts::ideal(cluster.len(), turns_before)
+        }
+    }
+
+    /// Return whether a guess may be used for a cluster (any guess is allowed outside hard mode)
+    pub fn allows(&self, guess: Word, cluster: &[Word]) -> bool {
+        !self.hard_mode || HardMode::from_guesses(&self.guesses, cluster[0]).allows(guess)
     }
 }
 
@@ -63,7 +117,7 @@ impl BuilderState {
 // - If fewer than three letters are known:
 //   - guess DUMPY.
 // - Otherwise, guess randomly in-cluster.
-fn build_v11(answers: &Vec<Word>, _guesses: &Vec<Word>) -> WordleTree {
+fn build_v11(answers: &Vec<Word>, valid: &[Word], _guesses: &Vec<Word>, hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> WordleTree {
     let mut guesses = VecDeque::from(wv("soare, clint"));
     let third = w("dumpy");
 
@@ -73,11 +127,11 @@ fn build_v11(answers: &Vec<Word>, _guesses: &Vec<Word>) -> WordleTree {
             .or_else(|| guess_random_up_to_length(&mut s, &mut p, 2))
             .or_else(|| guess_random_all_merged(&mut s, &mut p));
 
-    build_tree(answers, &mut strategy)
+    build_tree(answers, valid, hard_mode, objective, weights, &mut strategy)
 }
 
 // Play by guessing all provided guesses and then random in-cluster choices.
-fn build_standard(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
+fn build_standard(answers: &Vec<Word>, valid: &[Word], guesses: &Vec<Word>, hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> WordleTree {
     let mut guesses = VecDeque::from(guesses.clone());
 
     let mut strategy: &mut dyn FnMut(&mut BuilderState, &mut WordleTree) -> Option<WordleTree> = &mut |mut s, mut p| 
@@ -85,11 +139,11 @@ fn build_standard(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
             .or_else(|| guess_random_up_to_length(&mut s, &mut p, 2))
             .or_else(|| guess_random_all_merged(&mut s, &mut p));
 
-    build_tree(answers, &mut strategy)
+    build_tree(answers, valid, hard_mode, objective, weights, &mut strategy)
 }
 
 // Play by guessing < 4, otherwise standard. Break out random other guesses to show clusters left.
-fn build_hybrid(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
+fn build_hybrid(answers: &Vec<Word>, valid: &[Word], guesses: &Vec<Word>, hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> WordleTree {
     let mut guesses = VecDeque::from(guesses.clone());
 
     let mut strategy: &mut dyn FnMut(&mut BuilderState, &mut WordleTree) -> Option<WordleTree> = &mut |mut s, mut p| 
@@ -97,11 +151,11 @@ fn build_hybrid(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
             .or_else(|| guess_next_standard(&mut s, &mut p, &mut guesses))
             .or_else(|| guess_random_separate(&mut s, &mut p));
 
-    build_tree(answers, &mut strategy)
+    build_tree(answers, valid, hard_mode, objective, weights, &mut strategy)
 }
 
 // Play by guessing tiny clusters, or playing the next standard guess, or the in-cluster guess using the fewest total turns for across all answers.
-fn build_best(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
+fn build_best(answers: &Vec<Word>, valid: &[Word], guesses: &Vec<Word>, hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> WordleTree {
     let mut guesses = VecDeque::from(guesses.clone());
 
     let mut strategy: &mut dyn FnMut(&mut BuilderState, &mut WordleTree) -> Option<WordleTree> = &mut |mut s, mut p| 
@@ -109,11 +163,11 @@ fn build_best(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
             .or_else(|| guess_next_standard(&mut s, &mut p, &mut guesses))
             .or_else(|| guess_best_until_done(&mut s, &mut p));
 
-    build_tree(answers, &mut strategy)
+    build_tree(answers, valid, hard_mode, objective, weights, &mut strategy)
 }
 
 // Play by guessing tiny clusters, or the next standard guesses, or the alphabetically first possible answer each time
-fn build_first(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
+fn build_first(answers: &Vec<Word>, valid: &[Word], guesses: &Vec<Word>, hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> WordleTree {
     let mut guesses = VecDeque::from(guesses.clone());
 
     let mut strategy: &mut dyn FnMut(&mut BuilderState, &mut WordleTree) -> Option<WordleTree> = &mut |mut s, mut p| 
@@ -121,7 +175,7 @@ fn build_first(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
         .or_else(|| guess_next_standard(&mut s, &mut p, &mut guesses))
         .or_else(|| guess_first_until_done(&mut s, &mut p));
 
-    build_tree(answers, &mut strategy)
+    build_tree(answers, valid, hard_mode, objective, weights, &mut strategy)
 }
 
 // ---- Main Recursive builder function to turn a chain of strategy options into a built tree  ----
@@ -129,9 +183,9 @@ fn build_first(answers: &Vec<Word>, guesses: &Vec<Word>) -> WordleTree {
 /// Build a WordleTree for a given set of answers, taking a closure which returns the next guess to try.
 ///  Pass an "or_else" chain of strategy options to describe a full strategy
 ///  ex: build_tree(&answers, &mut |s, p| next_standard(s, p, &mut guesses).or_else(|| random_guess_each(s, p))));
-fn build_tree(answers: &Vec<Word>, next_guess: &mut dyn FnMut(&mut BuilderState, &mut WordleTree) -> Option<WordleTree>) -> WordleTree {
+fn build_tree(answers: &Vec<Word>, valid: &[Word], hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>, next_guess: &mut dyn FnMut(&mut BuilderState, &mut WordleTree) -> Option<WordleTree>) -> WordleTree {
     let mut root = WordleTree::new_sentinel();
-    let mut state = BuilderState::new(answers);
+    let mut state = BuilderState::new(answers, valid, hard_mode, objective, weights);
 
     if state.map.len() > 0 {
         build_tree_recurse(&mut state, &mut root, next_guess);
@@ -154,86 +208,141 @@ fn build_tree_recurse(state: &mut BuilderState, current: &mut WordleTree, next_g
 
 /// ---- Composable Strategies for building a WordleTree ----
 
-/// For each cluster, choose the in-cluster guess which minimizes total turns. Recurse until all clusters are solved.
+/// For each cluster, choose the in-cluster guess which is best for the objective (fewest total turns by default). Recurse until all clusters are solved.
+///  For clusters of four or more where no in-cluster guess solves every answer the turn after, valid out-of-cluster guesses are also considered; they're marked as probes in the tree.
 fn guess_best_until_done(state: &mut BuilderState, parent: &mut WordleTree) -> Option<WordleTree> {
+    best_until_done(state, parent).0
+}
+
+/// Build guess_best_until_done nodes, also returning the expected answers solved on each turn for the objective to compare.
+fn best_until_done(state: &mut BuilderState, parent: &mut WordleTree) -> (Option<WordleTree>, TurnCounts) {
     let mut last = None;
+    let mut counts = TurnCounts::new();
 
     // Guess all < 4; always better for 1-, 2-, never worse for 3-.
     //  May miss up to 0.67 turns for triples with one safe guess and two unsafe (1 + 2 + 2) vs (1 + 2 + 3)
-    let node = guess_random_up_to_length(state, parent, 4);
+    for (_, cluster) in state.map.iter().filter(|(_, c)| c.len() < 4) {
+        counts.add_counts(&state.weighted(rank::turn_counts_random(cluster, state.turns_before), cluster));
+    }
+
+    let node = guess_random_up_to_length(state, parent, 3);
     if let Some(node) = node { add_except_last(node, parent, &mut last); }
 
     let outer_map = mem::replace(&mut state.map, HashMap::new());
     let mut inner_map = HashMap::new();
 
     for (_, cluster) in outer_map.iter() {
-        let identifier = WordleTreeIdentifier::Cluster(cluster[0]);
-
-        let mut best: Option<WordleTree> = None;
-        let mut worst_turns: Option<f64> = None;
+        let mut best: Option<(WordleTree, TurnCounts)> = None;
+        let mut worst: Option<(f64, TurnCounts)> = None;
 
         for word in cluster {
-            let mut candidate = WordleTree::new(identifier, WordleGuess::Specific(word.clone()));
-
-            inner_map.clear();
-            let excluded_count = rank::split_as_set(cluster, *word, &mut inner_map);
-            candidate.cluster_vector = Some(ClusterVector::from_map(&inner_map));
+            let (candidate, candidate_counts) = best_until_done_after(state, cluster, *word, &mut inner_map);
 
-            mem::swap(&mut state.map, &mut inner_map);
-            state.turns_before += 1;
-            guess_best_until_done(state, &mut candidate).map(|c| candidate.add_child(c));
-            state.turns_before -= 1;
+            if worst.as_ref().is_none_or(|(_, w)| state.objective.compare(&candidate_counts, w) == Ordering::Greater) {
+                worst = Some((candidate.outer_total_turns, candidate_counts.clone()));
+            }
 
-            if excluded_count > 0 {
-                candidate.add_child(WordleTree::new_single_leaf(*word, state.turns_before));
+            if best.as_ref().is_none_or(|(_, b)| state.objective.compare(&candidate_counts, b) == Ordering::Less) {
+                best = Some((candidate, candidate_counts));
             }
+        }
 
-            if let Some(w) = worst_turns {
-                if w < candidate.outer_total_turns {
-                    worst_turns = Some(candidate.outer_total_turns);
+        // If an out-of-cluster guess could be better (every answer solved the turn after it), consider them in order of their ideal outcome
+        let mut out_of_cluster_ideal = TurnCounts::new();
+        out_of_cluster_ideal.add(state.turns_before + 2, state.cluster_weight(cluster));
+
+        if cluster.len() <= OUT_OF_CLUSTER_MAX_COUNT && best.as_ref().is_some_and(|(_, b)| state.objective.compare(&out_of_cluster_ideal, b) == Ordering::Less) {
+            for (ideal, word) in out_of_cluster_options(state, cluster, &mut inner_map) {
+                let best_counts = &best.as_ref().unwrap().1;
+                if state.objective.compare(&ideal, best_counts) != Ordering::Less { break; }
+
+                let (mut candidate, candidate_counts) = best_until_done_after(state, cluster, word, &mut inner_map);
+                if state.objective.compare(&candidate_counts, best_counts) == Ordering::Less {
+                    candidate.probe = true;
+                    best = Some((candidate, candidate_counts));
                 }
-            } else {
-                worst_turns = Some(candidate.outer_total_turns);
+
+                // Stop if a guess split every answer apart; nothing can do better
+                if state.objective.compare(&best.as_ref().unwrap().1, &out_of_cluster_ideal) != Ordering::Greater { break; }
             }
+        }
 
-            if let Some(b) = &best {
-                if b.outer_total_turns > candidate.outer_total_turns {
-                    best = Some(candidate);
+        if let (Some((mut best, best_counts)), Some((worst_turns, worst_counts))) = (best, worst) {
+            if state.objective.compare(&best_counts, &worst_counts) == Ordering::Less {
+                // Ensure node knows the cluster answers; correct count if doubled by add_answers
+                best.add_answers(cluster);
+                best.answer_count = cluster.len();
+
+                // Don't show subtree for 1- and 2- clusters
+                if best.answer_count <= 3 { 
+                    best.subtree = None; 
+                } 
+                
+                // Don't show the subtree for "safe" clusters (all words within distinguished by guess)
+                if let Some(cv) = &best.cluster_vector {
+                    if cv.biggest_cluster() <= 1 {
+                        best.subtree = None;
+                    }
                 }
+
+                counts.add_counts(&best_counts);
+                add_except_last(best, parent, &mut last);
             } else {
-                best = Some(candidate);
+                let any_guess_leaf = WordleTree::new_leaf(cluster.clone(), worst_turns);
+                counts.add_counts(&worst_counts);
+                add_except_last(any_guess_leaf, parent, &mut last);
             }
         }
+    }
 
-        if let Some(mut best) = best {
-            if let Some(worst_turns) = worst_turns {
-                if best.outer_total_turns < worst_turns {
-                    // Ensure node knows the cluster answers; correct count if doubled by add_answers
-                    best.add_answers(cluster);
-                    best.answer_count = cluster.len();
-
-                    // Don't show subtree for 1- and 2- clusters
-                    if best.answer_count <= 3 { 
-                        best.subtree = None; 
-                    } 
-                    
-                    // Don't show the subtree for "safe" clusters (all words within distinguished by guess)
-                    if let Some(cv) = &best.cluster_vector {
-                        if cv.biggest_cluster() <= 1 {
-                            best.subtree = None;
-                        }
-                    }
+    (Some(last.unwrap()), counts)
+}
 
-                    add_except_last(best, parent, &mut last);
-                } else {
-                    let any_guess_leaf = WordleTree::new_leaf(cluster.clone(), worst_turns);
-                    add_except_last(any_guess_leaf, parent, &mut last);
-                }
-            }
+/// Guess 'word' for a cluster and recurse with best_until_done, returning the node and the expected answers solved on each turn.
+fn best_until_done_after(state: &mut BuilderState, cluster: &Vec<Word>, word: Word, inner_map: &mut HashMap<ResponseSet, Vec<Word>>) -> (WordleTree, TurnCounts) {
+    let mut candidate = WordleTree::new(WordleTreeIdentifier::Cluster(cluster[0]), WordleGuess::Specific(word));
+
+    inner_map.clear();
+    let excluded_count = rank::split_as_set(cluster, word, inner_map);
+    candidate.cluster_vector = Some(ClusterVector::from_map(inner_map));
+
+    mem::swap(&mut state.map, inner_map);
+    state.turns_before += 1;
+    state.guesses.push(word);
+    let (child, mut candidate_counts) = best_until_done(state, &mut candidate);
+    child.map(|c| candidate.add_child(c));
+    state.guesses.pop();
+    state.turns_before -= 1;
+
+    if excluded_count > 0 {
+        candidate.add_child(WordleTree::new_single_leaf(word, state.turns_before));
+        candidate_counts.add(state.turns_before + 1, state.weight(word));
+    }
+
+    (candidate, candidate_counts)
+}
+
+/// Valid guesses outside a cluster which split it, with the ideal outcome after each, best first.
+fn out_of_cluster_options(state: &BuilderState, cluster: &Vec<Word>, inner_map: &mut HashMap<ResponseSet, Vec<Word>>) -> Vec<(TurnCounts, Word)> {
+    let mut options = Vec::new();
+
+    for guess in state.valid.iter() {
+        if cluster.contains(guess) || !state.allows(*guess, cluster) { continue; }
+
+        // Never consider guesses which don't split the cluster (they would recurse forever)
+        rank::split_as_set(cluster, *guess, inner_map);
+        if inner_map.len() <= 1 { continue; }
+
+        let mut ideal = TurnCounts::new();
+        for (_, subcluster) in inner_map.iter() {
+            ideal.add_counts(&state.ideal(subcluster, state.turns_before + 1));
         }
+
+        options.push((ideal, *guess));
     }
 
-    Some(last.unwrap())
+    options.sort_by(|l, r| state.objective.compare(&l.0, &r.0).then(l.1.cmp(&r.1)));
+    options
 }
 
 /// For each cluster, guess the first word in the cluster. Recurse until all clusters are solved.
@@ -269,7 +378,9 @@ fn guess_first_until_done(state: &mut BuilderState, parent: &mut WordleTree) ->
         // Recurse for each sub-cluster
         mem::swap(&mut state.map, &mut inner_map);
         state.turns_before += 1;
+        state.guesses.push(guess);
         let last_child = guess_first_until_done(state, &mut node);
+        state.guesses.pop();
         state.turns_before -= 1;
 
         if let Some(last_child) = last_child {
@@ -284,13 +395,22 @@ fn guess_first_until_done(state: &mut BuilderState, parent: &mut WordleTree) ->
 }
 
 /// Guess the next standard guess, if any are left.
-fn guess_next_standard(state: &mut BuilderState, _parent: &mut WordleTree, guesses: &mut VecDeque<Word>) -> Option<WordleTree> {
-    guess_specific(state, &mut guesses.pop_front())
+fn guess_next_standard(state: &mut BuilderState, parent: &mut WordleTree, guesses: &mut VecDeque<Word>) -> Option<WordleTree> {
+    guess_specific(state, parent, &mut guesses.pop_front())
 }
 
 /// Guess a specific word next, if provided.
-fn guess_specific(state: &mut BuilderState, specific: &mut Option<Word>) -> Option<WordleTree> {
+///  In hard mode, clusters where the guess is illegal are guessed randomly instead.
+fn guess_specific(state: &mut BuilderState, parent: &mut WordleTree, specific: &mut Option<Word>) -> Option<WordleTree> {
     if let Some(guess) = specific.take() {
+        // Guess randomly for any clusters where this guess isn't allowed
+        let last = guess_random_where_not_allowed(state, parent, guess);
+        if state.map.is_empty() {
+            return last;
+        } else if let Some(last) = last {
+            parent.add_child(last);
+        }
+
         // Make a node for this guess
         let mut current = WordleTree::new(WordleTreeIdentifier::Any, WordleGuess::Specific(guess));
 
@@ -301,6 +421,7 @@ fn guess_specific(state: &mut BuilderState, specific: &mut Option<Word>) -> Opti
 
         // Replace map for next guess for the new one
         mem::swap(&mut state.map, &mut inner_map);
+        state.guesses.push(guess);
 
         // If the guess itself was in the answers, create a single node for it
         if excluded_count > 0 {
@@ -314,44 +435,43 @@ fn guess_specific(state: &mut BuilderState, specific: &mut Option<Word>) -> Opti
     }
 }
 
-/// Guess a specific word next, if provided.
+/// Guess a specific word next for every cluster with fewer than 'known_letters_under' letters known, in one "KnownLettersUnder" node.
+///  In hard mode, if the guess isn't allowed for any of those clusters, leave them all for the next strategy option (the node would match them too).
 fn guess_specific_under_letter_count(state: &mut BuilderState, parent: &mut WordleTree, specific: Word, known_letters_under: u8) -> Option<WordleTree> {
-    let mut last = None;
+    let under = |responses: &ResponseSet| responses.known_count() < known_letters_under;
+    if !state.map.iter().filter(|(r, _)| under(r)).all(|(_, c)| state.allows(specific, c)) {
+        return None;
+    }
 
     // Clear and take the map
     let mut map = HashMap::new();
     mem::swap(&mut state.map, &mut map);
 
+    let mut current = WordleTree::new(WordleTreeIdentifier::KnownLettersUnder(known_letters_under as usize), WordleGuess::Specific(specific));
+    let mut cluster_vector = ClusterVector::new(Vec::new());
     let mut inner_map = HashMap::new();
 
     // Add the specific guess for each cluster under the target known letter count
-    for (responses, cluster) in map.iter() {
-        if responses.known_count() < known_letters_under {
-            let cluster_word = cluster[0];
-            let mut current = WordleTree::new(WordleTreeIdentifier::Cluster(cluster_word), WordleGuess::Specific(specific));
-            current.answer_count = cluster.len();
-
-            rank::split(cluster, specific, &mut inner_map);
-            current.cluster_vector = Some(ClusterVector::from_map(&inner_map));
-
-            let inner_turns_under_here = rank::total_turns_random_map(&inner_map) as f64;
-            let outer_turns_here = (cluster.len() * (state.turns_before + 1)) as f64 + inner_turns_under_here;
-            current.outer_total_turns = outer_turns_here;
-            
-            if let Some(previous) = last {
-                parent.add_child(previous);
-            }
+    for (_, cluster) in map.iter().filter(|(r, _)| under(r)) {
+        current.answer_count += cluster.len();
 
-            last = Some(current);
-        }
+        rank::split(cluster, specific, &mut inner_map);
+        cluster_vector.add_map(&inner_map);
+
+        let inner_turns_under_here = rank::total_turns_random_map_exact(&inner_map);
+        current.outer_total_turns += (cluster.len() * (state.turns_before + 1)) as f64 + inner_turns_under_here;
     }
 
+    current.cluster_vector = Some(cluster_vector);
+
     // Put back the clusters we didn't use the guess for
-    map.retain(|r, _| r.known_count() >= known_letters_under);
+    map.retain(|r, _| !under(r));
     mem::swap(&mut state.map, &mut map);
 
+    let last = if current.answer_count > 0 { Some(current) } else { None };
+
     if state.map.len() > 0 {
-        // If there are words left, add the last child and request the next strategy option
+        // If there are words left, add the node and request the next strategy option
         if let Some(last) = last {
             parent.add_child(last);
         }
@@ -359,7 +479,7 @@ fn guess_specific_under_letter_count(state: &mut BuilderState, parent: &mut Word
         // Return None to tell the next strategy option to handle the remainder
         return None;
     } else {
-        // Return one of the nodes to stop other strategy attempts
+        // Return the node to stop other strategy attempts
         last
     }
 }
@@ -436,6 +556,29 @@ fn guess_random_separate(state: &mut BuilderState, parent: &mut WordleTree) -> O
     last
 }
 
+/// Guess randomly for each cluster where a specific guess isn't allowed in hard mode. Leave other clusters for the specific guess.
+fn guess_random_where_not_allowed(state: &mut BuilderState, parent: &mut WordleTree, guess: Word) -> Option<WordleTree> {
+    if !state.hard_mode { return None; }
+
+    let mut last = None;
+
+    // Take the clusters where the guess is illegal
+    let map = mem::take(&mut state.map);
+    let (map, illegal): (HashMap<_, _>, HashMap<_, _>) = map.into_iter().partition(|(_, cluster)| state.allows(guess, cluster));
+    state.map = map;
+
+    // Add a 'random leaf' for each one
+    for (_, cluster) in illegal {
+        let inner_turns_under_here = rank::total_turns_random(&cluster);
+        let outer_turns_here = (cluster.len() * state.turns_before) as f64 + inner_turns_under_here;
+
+        let current = WordleTree::new_leaf(cluster, outer_turns_here);
+        add_except_last(current, parent, &mut last);
+    }
+
+    last
+}
+
 /// Guess all remaining words randomly, represented with a single node with the total guess turn cost.
 fn guess_random_all_merged(state: &mut BuilderState, _parent: &mut WordleTree) -> Option<WordleTree> {
     // Add a single leaf to summarize all remaining answers
@@ -483,7 +626,7 @@ mod tests {
         // Should guess PARSE, FATAL before anything.
         //  - FATAL solved in two guesses.
         //  - TALLY, WALTZ left which take 3 + 4 guesses. (7 total)
-        let tree = build_standard(&answers, &guesses);
+        let tree = build_standard(&answers, &[], &guesses, false, Objective::TotalTurns, None);
         assert_eq!(smart_trim(&tree.to_string()), 
 "9 (*, 3) -> parse [0, 0, 1]
     9 (*, 3) -> fatal [0, 1]
@@ -494,7 +637,7 @@ mod tests {
         //  - WALTZ solved in two guesses.
         //  - FATAL, TALLY are singles which take 3 + 3 guesses (6 total)
         let guesses = vec![w("parse"), w("waltz")]; 
-        let tree = build_standard(&answers, &guesses);
+        let tree = build_standard(&answers, &[], &guesses, false, Objective::TotalTurns, None);
         assert_eq!(smart_trim(&tree.to_string()), 
 "8 (*, 3) -> parse [0, 0, 1]
     8 (*, 3) -> waltz [2]
@@ -502,6 +645,69 @@ mod tests {
         2 {waltz}");
     }
 
+    #[test]
+    fn test_build_standard_hard_mode() {
+        let answers = vec![w("dowdy"), w("fatal"), w("tally"), w("waltz")];
+        let guesses = vec![w("parse"), w("clint")];
+
+        // PARSE reveals .A... for FATAL, TALLY, WALTZ; CLINT doesn't use the A, so they are guessed randomly in hard mode.
+        //  - FATAL, TALLY, WALTZ take 3 turns for PARSE + 5.3 turns guessing randomly = 8.3 turns.
+        //  - DOWDY can still use CLINT, and then takes three turns.
+        let tree = build_standard(&answers, &[], &guesses, true, Objective::TotalTurns, None);
+        assert_eq!(smart_trim(&tree.to_string()), 
+"11.3 (*, 4) -> parse [1, 0, 1]
+    8.3 {fatal, tally, waltz}
+    3 (*, 1) -> clint [1]
+        3 (= 1, 1) -> * {dowdy}");
+    }
+
+    #[test]
+    fn test_build_best_objectives() {
+        let answers = crate::wv("dimly, fifty, fishy, fizzy, kiddy, kitty, petty, pinky, vivid, wimpy, windy");
+
+        // KIDDY has the fewest total turns, but FIZZY next leaves {fifty, fishy}, which random guessing sometimes needs turn four to solve
+        let tree = build_best(&answers, &[], &Vec::new(), false, Objective::TotalTurns, None);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("kiddy")));
+        assert_eq!(tree.outer_total_turns, 25.0);
+
+        // FIFTY takes a turn more in total, but no game reaches turn four
+        let tree = build_best(&answers, &[], &Vec::new(), false, Objective::MaxTurns, None);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("fifty")));
+        assert_eq!(tree.outer_total_turns, 26.0);
+
+        // If PINKY is much likelier than the others, guessing it first has the fewest expected turns
+        let weights = AnswerWeights::parse("pinky\t20\nfizzy\t1", &answers).unwrap();
+        let tree = build_best(&answers, &[], &Vec::new(), false, Objective::TotalTurns, Some(&weights));
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("pinky")));
+    }
+
+    #[test]
+    fn test_build_best_out_of_cluster() {
+        let answers = crate::wv("batch, catch, hatch, latch, match, patch, watch");
+        let valid = Word::parse_file(std::path::Path::new("../data/2315/answers.txt"));
+
+        // In-cluster guesses only tell one answer apart at a time
+        let in_cluster = build_best(&answers, &[], &Vec::new(), false, Objective::TotalTurns, None);
+        assert!(!in_cluster.probe);
+
+        // A valid word which can't be the answer splits the cluster into at most pairs, and is marked as such
+        let tree = build_best(&answers, &valid, &Vec::new(), false, Objective::TotalTurns, None);
+        assert!(tree.probe);
+        assert!(!answers.iter().any(|a| tree.next_guess == WordleGuess::Specific(*a)));
+        assert!(tree.outer_total_turns < in_cluster.outer_total_turns);
+        assert!(tree.to_string().lines().next().unwrap().contains(" (probe)"));
+
+        // The tree still reads back, probe included
+        let parsed = WordleTree::parse(tree.to_string().lines()).unwrap();
+        assert!(parsed.probe);
+
+        // Clusters of four are probed too; an outside guess splits every answer apart, so each is solved the turn after it
+        let answers = crate::wv("catch, hatch, latch, match");
+        let tree = build_best(&answers, &valid, &Vec::new(), false, Objective::TotalTurns, None);
+        assert!(tree.probe);
+        assert_eq!(tree.outer_total_turns, 8.0);
+    }
+
     fn w(text: &str) -> Word {
         Word::new(text).unwrap()
     }
@@ -0,0 +1,281 @@
+use std::{collections::VecDeque, fmt::{Display, Formatter}, mem};
+use crate::{word::Word, wordle_tree::{WordleTree, WordleTreeIdentifier}, parser::Parser};
+use super::*;
+
+/// A build strategy described in text, chaining the composable strategy parts with '|'.
+///  Each turn, the parts are tried in order until one handles every remaining cluster.
+///  ex: "random<=3 | standard(soare, clint) | under_letters(dumpy, 3) | best"
+///
+///  Parts:
+///   random<=N             Guess clusters of N or fewer answers randomly.
+///   standard(w1, w2, ...) Guess the next of these words (each use takes the next one).
+///   under_letters(w, N)   Guess 'w' for clusters with fewer than N letters known, in one "known < N" node.
+///   best                  Guess the best word for each cluster until done (out-of-cluster when much better).
+///   first                 Guess the alphabetically first word in each cluster until done.
+///   separate              Guess each cluster randomly, with a node per cluster.
+///   random                Guess all remaining answers randomly, in one node.
+///   each(<strategy>)      Play each cluster with its own copy of a sub-strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strategy {
+    steps: Vec<StrategyStep>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum StrategyStep {
+    RandomUpToLength(usize),
+    Standard(VecDeque<Word>),
+    UnderLetters(Word, u8),
+    Best,
+    First,
+    Separate,
+    Random,
+    Each(Strategy),
+}
+
+impl Strategy {
+    pub fn parse(text: &str) -> Result<Strategy, String> {
+        // Separate '|' and '<=' so the parser sees them as their own tokens
+        let text = text.replace('|', " | ").replace("<=", " <= ");
+        let mut parser = Parser::new(text.lines());
+
+        let strategy = Strategy::parse_chain(&mut parser)?;
+        if !parser.current.is_empty() {
+            return Err(parser.error("Expected '|' or the end of the strategy"));
+        }
+
+        Ok(strategy)
+    }
+
+    fn parse_chain(parser: &mut Parser) -> Result<Strategy, String> {
+        let mut steps = vec![Strategy::parse_step(parser)?];
+
+        while parser.current == "|" {
+            parser.next()?;
+            steps.push(Strategy::parse_step(parser)?);
+        }
+
+        Ok(Strategy { steps })
+    }
+
+    fn parse_step(parser: &mut Parser) -> Result<StrategyStep, String> {
+        let name = parser.current.clone();
+        parser.next()?;
+
+        let step = match name.as_str() {
+            "random" if parser.current == "<=" => {
+                parser.next()?;
+                let length = parser.as_usize()?;
+                parser.next()?;
+                StrategyStep::RandomUpToLength(length)
+            },
+            "random" => StrategyStep::Random,
+            "best" => StrategyStep::Best,
+            "first" => StrategyStep::First,
+            "separate" => StrategyStep::Separate,
+            "standard" => {
+                parser.require("(")?;
+                let mut guesses = VecDeque::new();
+                loop {
+                    guesses.push_back(Strategy::parse_word(parser)?);
+                    if parser.current != "," { break; }
+                    parser.next()?;
+                }
+                parser.require(")")?;
+                StrategyStep::Standard(guesses)
+            },
+            "under_letters" => {
+                parser.require("(")?;
+                let word = Strategy::parse_word(parser)?;
+                parser.require(",")?;
+                let count = parser.as_usize()?;
+                parser.next()?;
+                parser.require(")")?;
+                StrategyStep::UnderLetters(word, count.min(5) as u8)
+            },
+            "each" => {
+                parser.require("(")?;
+                let inner = Strategy::parse_chain(parser)?;
+                parser.require(")")?;
+                StrategyStep::Each(inner)
+            },
+            _ => return Err(format!("Unknown strategy part '{name}'. Use 'random<=N', 'standard(words)', 'under_letters(word, N)', 'best', 'first', 'separate', 'random', or 'each(strategy)'."))
+        };
+
+        Ok(step)
+    }
+
+    fn parse_word(parser: &mut Parser) -> Result<Word, String> {
+        match parser.as_word()? {
+            Some(word) => {
+                parser.next()?;
+                Ok(word)
+            },
+            None => Err(parser.error("A specific word is required here"))
+        }
+    }
+
+    /// Build a tree for the answers with this strategy.
+    pub fn build(&self, answers: &Vec<Word>, valid: &[Word], hard_mode: bool, objective: Objective, weights: Option<&AnswerWeights>) -> WordleTree {
+        let mut run = self.clone();
+        build_tree(answers, valid, hard_mode, objective, weights, &mut |s, p| run.next_guess(s, p))
+    }
+
+    /// Try each part in order until one handles every remaining cluster.
+    fn next_guess(&mut self, state: &mut BuilderState, parent: &mut WordleTree) -> Option<WordleTree> {
+        for step in self.steps.iter_mut() {
+            let result = match step {
+                StrategyStep::RandomUpToLength(length) => guess_random_up_to_length(state, parent, *length),
+                StrategyStep::Standard(guesses) => guess_next_standard(state, parent, guesses),
+                StrategyStep::UnderLetters(word, count) => guess_specific_under_letter_count(state, parent, *word, *count),
+                StrategyStep::Best => guess_best_until_done(state, parent),
+                StrategyStep::First => guess_first_until_done(state, parent),
+                StrategyStep::Separate => guess_random_separate(state, parent),
+                StrategyStep::Random => guess_random_all_merged(state, parent),
+                StrategyStep::Each(inner) => guess_each(state, parent, inner),
+            };
+
+            if result.is_some() { return result; }
+        }
+
+        None
+    }
+}
+
+/// For each cluster, build the rest of the game with a fresh copy of a sub-strategy.
+///  Each cluster gets a node identified by its first answer, so the sub-strategies can differ per branch.
+fn guess_each(state: &mut BuilderState, parent: &mut WordleTree, inner: &Strategy) -> Option<WordleTree> {
+    let mut last = None;
+
+    // Build clusters in order, so ties and hard mode rules come out the same every time
+    let mut clusters = mem::take(&mut state.map).into_iter().collect::<Vec<_>>();
+    clusters.sort_by(|l, r| l.1.cmp(&r.1));
+
+    for (responses, cluster) in clusters {
+        let cluster_word = cluster[0];
+        state.map.insert(responses, cluster);
+
+        // Guesses made for this cluster don't apply to the next one
+        let guesses_before = state.guesses.len();
+        let mut run = inner.clone();
+        let mut wrapper = WordleTree::new_sentinel();
+        build_tree_recurse(state, &mut wrapper, &mut |s, p| run.next_guess(s, p));
+        state.guesses.truncate(guesses_before);
+
+        for mut node in wrapper.subtree.unwrap_or_default() {
+            if node.identifier == WordleTreeIdentifier::Any {
+                node.identifier = WordleTreeIdentifier::Cluster(cluster_word);
+            }
+
+            add_except_last(node, parent, &mut last);
+        }
+    }
+
+    last
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 { f.write_str(" | ")?; }
+
+            match step {
+                StrategyStep::RandomUpToLength(length) => write!(f, "random<={length}")?,
+                StrategyStep::Standard(guesses) => write!(f, "standard({})", guesses.iter().map(|g| g.to_string()).collect::<Vec<String>>().join(", "))?,
+                StrategyStep::UnderLetters(word, count) => write!(f, "under_letters({word}, {count})")?,
+                StrategyStep::Best => f.write_str("best")?,
+                StrategyStep::First => f.write_str("first")?,
+                StrategyStep::Separate => f.write_str("separate")?,
+                StrategyStep::Random => f.write_str("random")?,
+                StrategyStep::Each(inner) => write!(f, "each({inner})")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{wv, smart_trim};
+    use super::*;
+
+    #[test]
+    fn strategy_parse() {
+        let strategy = Strategy::parse("random<=3|standard(soare,clint) | under_letters(dumpy, 3) | each(standard(fatal) | best)").unwrap();
+        assert_eq!(strategy.to_string(), "random<=3 | standard(soare, clint) | under_letters(dumpy, 3) | each(standard(fatal) | best)");
+        assert_eq!(Strategy::parse(&strategy.to_string()).unwrap(), strategy);
+
+        assert!(Strategy::parse("").is_err());
+        assert!(Strategy::parse("best | guess").is_err());
+        assert!(Strategy::parse("standard()").is_err());
+        assert!(Strategy::parse("standard(soare").is_err());
+        assert!(Strategy::parse("random<=three").is_err());
+        assert!(Strategy::parse("best best").is_err());
+    }
+
+    #[test]
+    fn strategy_matches_builders() {
+        let answers = wv("fatal, tally, waltz");
+        let guesses = wv("parse, fatal");
+
+        // The 'standard' builder is the same chain in the language
+        let strategy = Strategy::parse("standard(parse, fatal) | random<=2 | random").unwrap();
+        let tree = strategy.build(&answers, &[], false, Objective::TotalTurns, None);
+        assert_eq!(tree.to_string(), build_standard(&answers, &[], &guesses, false, Objective::TotalTurns, None).to_string());
+
+        // Chains passed to build take no separate guesses
+        let built = build("standard(parse, fatal) | random<=2 | random", &answers, &[], &Vec::new(), false, Objective::TotalTurns, None).unwrap();
+        assert_eq!(built.to_string(), tree.to_string());
+        assert_eq!(build("standard(parse) | random", &answers, &[], &guesses, false, Objective::TotalTurns, None).err().unwrap(),
+            "Strategy chain 'standard(parse) | random' takes its guesses in 'standard(...)' parts, not after it; found parse, fatal.");
+    }
+
+    #[test]
+    fn strategy_each() {
+        let answers = wv("dowdy, fatal, tally, waltz");
+
+        // After PARSE, each cluster plays its own copy of the sub-strategy:
+        //  {fatal, tally, waltz} guesses FATAL, then the pair randomly; {dowdy} is guessed next.
+        let strategy = Strategy::parse("standard(parse) | each(random<=1 | standard(fatal) | random)").unwrap();
+        let tree = strategy.build(&answers, &[], false, Objective::TotalTurns, None);
+        assert_eq!(smart_trim(&tree.to_string()),
+"11 (*, 4) -> parse [1, 0, 1]
+    9 (fatal, 3) -> fatal [0, 1]
+        7 (*, 2) -> * {tally, waltz}
+        2 {fatal}
+    2 (= 1, 1) -> * {dowdy}");
+    }
+
+    #[test]
+    fn strategy_under_letters() {
+        let answers = wv("dowdy, fatal, tally, waltz");
+
+        // After PARSE, {dowdy} (no letters known) and {fatal, tally, waltz} (one known) both guess CLINT from a single node
+        let strategy = Strategy::parse("standard(parse) | under_letters(clint, 2) | random").unwrap();
+        let tree = strategy.build(&answers, &[], false, Objective::TotalTurns, None);
+        assert_eq!(smart_trim(&tree.to_string()),
+"14.3 (*, 4) -> parse [1, 0, 1]
+    14.3 (known < 2, 4) -> clint [1, 0, 1]");
+        assert_eq!(format!("{:.2}", tree.evaluate(&answers, false).turn_counts.total_turns()), format!("{:.2}", tree.outer_total_turns));
+    }
+
+    #[test]
+    fn strategy_each_hard_mode() {
+        let answers = wv("fatal, goody, mummy, tally, waltz");
+
+        // After PARSE, {fatal, tally, waltz} guesses WALTZ and then TALLY. Those guesses add no hard mode rules for
+        //  {goody, mummy} (TALLY would have shown its 'y'), so WALTZ is still allowed there.
+        let strategy = Strategy::parse("standard(parse) | each(standard(waltz, tally) | random)").unwrap();
+        let tree = strategy.build(&answers, &[], true, Objective::TotalTurns, None);
+        assert_eq!(smart_trim(&tree.to_string()),
+"18 (*, 5) -> parse [0, 1, 1]
+    9 (fatal, 3) -> waltz [2]
+        7 (*, 2) -> tally [1]
+            4 (*, 1) -> * {fatal}
+            3 {tally}
+        2 {waltz}
+    9 (goody, 2) -> waltz [0, 1]
+        9 (*, 2) -> tally [0, 1]
+            9 (*, 2) -> * {goody, mummy}");
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}};
+use crate::{rank, word::Word, wordle_tree::*};
+
+/// How hard a WordleTree is for a person to memorize.
+///  Every specific guess must be remembered, every situation other than "any" must be recognized from the responses so far,
+///  and deeper trees mean remembering longer sequences. Random guesses (in-cluster) need no memorization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Complexity {
+    pub guesses: usize,         // Nodes with a next guess to remember (a word, list, or guessing policy)
+    pub patterns: usize,        // Of those, nodes which only apply in a situation to recognize (anything but "*")
+    pub depth: usize,           // The latest turn on which a remembered guess is played
+}
+
+impl Complexity {
+    pub fn measure(tree: &WordleTree) -> Complexity {
+        let mut result = Complexity::default();
+        measure_recurse(tree, 1, &mut result);
+        result
+    }
+
+    /// A single number to compare trees by; each part counts equally.
+    pub fn score(&self) -> usize {
+        self.guesses + self.patterns + self.depth
+    }
+}
+
+impl Display for Complexity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} guesses, {} patterns, depth {})", self.score(), self.guesses, self.patterns, self.depth)
+    }
+}
+
+/// Add up complexity for a node and its subtree.
+fn measure_recurse(node: &WordleTree, turn: usize, result: &mut Complexity) {
+    if node.next_guess != WordleGuess::Random {
+        result.guesses += 1;
+        if node.identifier != WordleTreeIdentifier::Any { result.patterns += 1; }
+        result.depth = result.depth.max(turn);
+    }
+
+    if let Some(subtree) = &node.subtree {
+        for child in subtree.iter() {
+            measure_recurse(child, turn + 1, result);
+        }
+    }
+}
+
+/// One strategy on the Pareto front of complexity vs. total turns.
+pub struct ParetoPoint {
+    pub complexity: Complexity,
+    pub total_turns: f64,
+    pub collapsed: Vec<String>,     // The nodes (identifier -> guess) switched to random guessing to get here, in order
+}
+
+/// Starting from a tree, switch the subtree with the smallest turn cost per complexity removed to random guessing, one at a time, until only the first guess is left.
+///  Returns the strategies found which no other is both simpler and better than, simplest last.
+///  Turns come from the tree's own totals, with random guessing computed exactly for each collapsed node's answers.
+pub fn pareto_front(mut tree: WordleTree, answers: &[Word]) -> Vec<ParetoPoint> {
+    // Find the clusters each node applies to, to compute the turns for random guessing there
+    let clusters = tree.clusters_by_node(answers);
+
+    let mut cluster_cache = HashMap::new();
+    let mut random_turns_by_path: HashMap<Vec<usize>, Option<f64>> = HashMap::new();
+    let mut random_turns = |path: &Vec<usize>| -> Option<f64> {
+        *random_turns_by_path.entry(path.clone()).or_insert_with(|| {
+            let turns_before = path.len();
+            clusters.get(path).map(|node_clusters| node_clusters.iter().map(|c| (turns_before * c.len()) as f64 + rank::total_turns_random_cached(c, &mut cluster_cache)).sum())
+        })
+    };
+
+    let mut points = vec![ParetoPoint { complexity: Complexity::measure(&tree), total_turns: tree.outer_total_turns, collapsed: Vec::new() }];
+
+    loop {
+        // Find the collapse costing the fewest turns per complexity removed
+        let current = Complexity::measure(&tree);
+        let mut nodes = Vec::new();
+        subtree_scores(&tree, 1, &mut Vec::new(), &mut nodes);
+
+        // Deepest specific guesses first, to find the depth left after removing each subtree
+        let mut by_depth = nodes.iter().map(|(path, turn, _)| (*turn, path)).collect::<Vec<_>>();
+        by_depth.sort_by_key(|(turn, _)| std::cmp::Reverse(*turn));
+
+        let mut best: Option<(f64, Vec<usize>, f64)> = None;
+
+        for (path, _, subtree_score) in nodes.iter().filter(|(p, _, _)| !p.is_empty()) {
+            let depth_after = by_depth.iter().find(|(_, other)| !other.starts_with(path)).map(|(turn, _)| *turn).unwrap_or(0);
+            let removed = subtree_score + current.depth - depth_after;
+
+            // Nodes no answers reach (in hand-edited trees) change no games, so collapsing them is free
+            let added_turns = random_turns(path).map_or(0.0, |turns| turns - tree.node_at(path).outer_total_turns);
+            let cost = added_turns / removed.max(1) as f64;
+
+            if best.as_ref().is_none_or(|(c, _, _)| cost < *c) {
+                best = Some((cost, path.clone(), added_turns));
+            }
+        }
+
+        let Some((_, path, added_turns)) = best else { break; };
+
+        // Collapse it, updating the totals for it and every ancestor
+        let described = tree.node_at(&path).describe();
+        let mut node = &mut tree;
+        node.outer_total_turns += added_turns;
+        for i in path.iter() {
+            node = &mut node.subtree.as_mut().unwrap()[*i];
+            node.outer_total_turns += added_turns;
+        }
+
+        node.next_guess = WordleGuess::Random;
+        node.probe = false;
+        node.subtree = None;
+        node.cluster_vector = None;
+
+        let mut collapsed = points.last().unwrap().collapsed.clone();
+        collapsed.push(described);
+        points.push(ParetoPoint { complexity: Complexity::measure(&tree), total_turns: tree.outer_total_turns, collapsed });
+    }
+
+    // Keep only the points no other point beats on both complexity and turns
+    let dominated = |p: &ParetoPoint| points.iter().any(|o| o.complexity.score() <= p.complexity.score() && o.total_turns <= p.total_turns && (o.complexity.score() < p.complexity.score() || o.total_turns < p.total_turns));
+    let keep = points.iter().map(|p| !dominated(p)).collect::<Vec<bool>>();
+    points.into_iter().zip(keep).filter(|(_, k)| *k).map(|(p, _)| p).collect()
+}
+
+/// Find each node with a specific guess, with the turn it is played on and the guesses and patterns in its subtree, returning the subtree total.
+fn subtree_scores(node: &WordleTree, turn: usize, path: &mut Vec<usize>, result: &mut Vec<(Vec<usize>, usize, usize)>) -> usize {
+    let mut score = 0;
+
+    if let Some(subtree) = &node.subtree {
+        for (i, child) in subtree.iter().enumerate() {
+            path.push(i);
+            score += subtree_scores(child, turn + 1, path, result);
+            path.pop();
+        }
+    }
+
+    if node.next_guess != WordleGuess::Random {
+        score += 1;
+        if node.identifier != WordleTreeIdentifier::Any { score += 1; }
+        result.push((path.clone(), turn, score));
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::{wv, smart_trim};
+    use crate::objective::Objective;
+    use crate::wordle_tree::{WordleTree, builders};
+    use super::*;
+
+    #[test]
+    fn complexity_measure() {
+        let text =
+"8448  (*, 2315)   -> clint
+    8181  (*, 2314)   -> soare
+        7282  (*, 767)    -> *
+        114   (> ..are, 25) -> gybed
+            36    (> ...E., 7)      -> perms
+        63    (> .O.re, 14) -> mawky
+        5     {afire, azure}
+";
+        let tree = WordleTree::parse(text.lines()).unwrap();
+        let complexity = Complexity::measure(&tree);
+        assert_eq!(complexity, Complexity { guesses: 5, patterns: 3, depth: 4 });
+        assert_eq!(complexity.score(), 12);
+        assert_eq!(complexity.to_string(), "12 (5 guesses, 3 patterns, depth 4)");
+
+        // Patterns, lengths, and known letter counts must be recognized too
+        let text =
+"100   (*, 2315)  -> soare
+    60    (< 40, 30) -> *
+    50    (known < 2, 20) -> clint
+    40    (?O?RE, 10) -> borne
+    30    (3+, 10) -> *
+";
+        let tree = WordleTree::parse(text.lines()).unwrap();
+        assert_eq!(Complexity::measure(&tree), Complexity { guesses: 3, patterns: 2, depth: 2 });
+    }
+
+    #[test]
+    fn complexity_pareto() {
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let tree = builders::build("standard", &answers, &[], &wv("soare, clint"), false, Objective::TotalTurns, None).unwrap();
+        let total_turns = tree.outer_total_turns;
+
+        // Guessing randomly after SOARE is simpler and takes more turns than always guessing CLINT second
+        let front = pareto_front(tree, &answers);
+        assert_eq!(front.len(), 2);
+        assert_eq!(front[0].complexity, Complexity { guesses: 2, patterns: 0, depth: 2 });
+        assert_eq!(front[0].total_turns, total_turns);
+        assert!(front[0].collapsed.is_empty());
+
+        assert_eq!(front[1].complexity, Complexity { guesses: 1, patterns: 0, depth: 1 });
+        assert!(front[1].total_turns > total_turns);
+        assert_eq!(front[1].collapsed, vec!["(*, 2315) -> clint".to_string()]);
+    }
+
+    #[test]
+    fn complexity_pareto_dominated() {
+        let answers = wv("dowdy, fatal, tally, waltz");
+        let tree = builders::build("standard", &answers, &[], &wv("parse, fatal"), false, Objective::TotalTurns, None).unwrap();
+        assert_eq!(smart_trim(&tree.to_string()),
+"12 (*, 4) -> parse [1, 0, 1]
+    12 (*, 4) -> fatal [1, 1]
+        7 (= 2, 2) -> * {tally, waltz}
+        3 (= 1, 1) -> * {dowdy}
+        2 {fatal}");
+
+        // Guessing randomly after PARSE is simpler *and* better here (FATAL isn't the best guess for {fatal, tally, waltz}), so only it is on the front
+        let front = pareto_front(tree, &answers);
+        assert_eq!(front.len(), 1);
+        assert_eq!(front[0].complexity.score(), 2);
+        assert_eq!(format!("{:.2}", front[0].total_turns), "10.33");
+    }
+
+    #[test]
+    fn complexity_pareto_unreached() {
+        // No answers get ..... from PARSE, so collapsing MAWKY is free and comes first
+        let answers = wv("fatal, tally, waltz");
+        let tree = WordleTree::parse(
+"8 (*, 3) -> parse
+    6 (> .A..., 3) -> fatal
+    4 (> ....., 2) -> mawky".lines()).unwrap();
+
+        let front = pareto_front(tree, &answers);
+        assert_eq!(front.len(), 2);
+        assert_eq!(front[0].complexity, Complexity { guesses: 2, patterns: 1, depth: 2 });
+        assert_eq!(front[0].total_turns, 8.0);
+        assert_eq!(front[0].collapsed, vec!["(> ....., 2) -> mawky".to_string()]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt::{Display, Formatter};
+use crate::{word::Word, wordle_tree::*};
+
+// Turn changes smaller than this are rounding in the written totals
+const TURNS_EPSILON: f64 = 0.05;
+
+/// What changed between two WordleTree strategies.
+///  Nodes are matched by the path of identifiers to them, so whitespace and child order don't matter.
+pub struct TreeDiff {
+    pub changes: Vec<NodeChange>,
+}
+
+pub struct NodeChange {
+    pub path: Vec<WordleTreeIdentifier>,    // Identifiers from the root to the node
+    pub change: Change,
+}
+
+pub enum Change {
+    Added { next_guess: WordleGuess, answer_count: usize, outer_total_turns: f64, node_count: usize },
+    Removed { next_guess: WordleGuess, answer_count: usize, outer_total_turns: f64, node_count: usize },
+    Changed {
+        next_guess: Option<(WordleGuess, WordleGuess)>,
+        answer_count: Option<(usize, usize)>,
+        answers_added: Vec<Word>,
+        answers_removed: Vec<Word>,
+        outer_total_turns: Option<(f64, f64)>,
+    },
+}
+
+impl WordleTree {
+    /// Find the differences from this tree to 'other'. Added and removed subtrees are reported once, at their top node.
+    pub fn diff(&self, other: &WordleTree) -> TreeDiff {
+        let mut changes = Vec::new();
+        let mut path = vec![self.identifier];
+
+        if self.identifier == other.identifier {
+            diff_recurse(self, other, &mut path, &mut changes);
+        } else {
+            changes.push(NodeChange { path: path.clone(), change: removed(self) });
+            changes.push(NodeChange { path: vec![other.identifier], change: added(other) });
+        }
+
+        TreeDiff { changes }
+    }
+}
+
+fn diff_recurse(left: &WordleTree, right: &WordleTree, path: &mut Vec<WordleTreeIdentifier>, changes: &mut Vec<NodeChange>) {
+    if let Some(change) = changed(left, right) {
+        changes.push(NodeChange { path: path.clone(), change });
+    }
+
+    let mut left_children = canonical_children(left);
+    let right_children = canonical_children(right);
+
+    for right_child in right_children {
+        path.push(right_child.identifier);
+
+        // Match the first unmatched child with the same identifier
+        match left_children.iter().position(|l| l.identifier == right_child.identifier) {
+            Some(i) => {
+                let left_child = left_children.remove(i);
+                diff_recurse(left_child, right_child, path, changes);
+            },
+            None => changes.push(NodeChange { path: path.clone(), change: added(right_child) })
+        }
+
+        path.pop();
+    }
+
+    for left_child in left_children {
+        path.push(left_child.identifier);
+        changes.push(NodeChange { path: path.clone(), change: removed(left_child) });
+        path.pop();
+    }
+}
+
+/// Children in the order they are written out (answer count descending, then random before specific, then identifier)
+fn canonical_children(node: &WordleTree) -> Vec<&WordleTree> {
+    let mut children = node.subtree.iter().flatten().collect::<Vec<&WordleTree>>();
+    children.sort_by(|l, r| r.cmp(l));
+    children
+}
+
+fn added(node: &WordleTree) -> Change {
+    Change::Added { next_guess: node.next_guess.clone(), answer_count: node.answer_count, outer_total_turns: node.outer_total_turns, node_count: node.node_count() }
+}
+
+fn removed(node: &WordleTree) -> Change {
+    Change::Removed { next_guess: node.next_guess.clone(), answer_count: node.answer_count, outer_total_turns: node.outer_total_turns, node_count: node.node_count() }
+}
+
+fn changed(left: &WordleTree, right: &WordleTree) -> Option<Change> {
+    let next_guess = (left.next_guess != right.next_guess).then_some((left.next_guess.clone(), right.next_guess.clone()));
+    let answer_count = (left.answer_count != right.answer_count).then_some((left.answer_count, right.answer_count));
+    let outer_total_turns = ((left.outer_total_turns - right.outer_total_turns).abs() >= TURNS_EPSILON).then_some((left.outer_total_turns, right.outer_total_turns));
+
+    // Answers are only compared when both trees list them
+    let (mut answers_added, mut answers_removed) = (Vec::new(), Vec::new());
+    if let (Some(left_answers), Some(right_answers)) = (&left.answers, &right.answers) {
+        answers_added = right_answers.iter().filter(|a| !left_answers.contains(a)).copied().collect();
+        answers_removed = left_answers.iter().filter(|a| !right_answers.contains(a)).copied().collect();
+        answers_added.sort();
+        answers_removed.sort();
+    }
+
+    if next_guess.is_none() && answer_count.is_none() && outer_total_turns.is_none() && answers_added.is_empty() && answers_removed.is_empty() {
+        return None;
+    }
+
+    Some(Change::Changed { next_guess, answer_count, answers_added, answers_removed, outer_total_turns })
+}
+
+fn path_string(path: &[WordleTreeIdentifier]) -> String {
+    path.iter().map(|identifier| match identifier {
+        WordleTreeIdentifier::Response(_, _) => format!("(> {identifier})"),
+        _ => format!("({identifier})")
+    }).collect::<Vec<String>>().join(" ")
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for NodeChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = path_string(&self.path);
+
+        match &self.change {
+            Change::Added { next_guess, answer_count, outer_total_turns, node_count } => {
+                write!(f, "+ {path} -> {next_guess}  ({}, {} turns, {})", lint::count_of(*answer_count, "answer"), write_turns(*outer_total_turns, 1.0, false), lint::count_of(*node_count, "node"))
+            },
+            Change::Removed { next_guess, answer_count, outer_total_turns, node_count } => {
+                write!(f, "- {path} -> {next_guess}  ({}, {} turns, {})", lint::count_of(*answer_count, "answer"), write_turns(*outer_total_turns, 1.0, false), lint::count_of(*node_count, "node"))
+            },
+            Change::Changed { next_guess, answer_count, answers_added, answers_removed, outer_total_turns } => {
+                let mut parts = Vec::new();
+                if let Some((from, to)) = next_guess { parts.push(format!("guess {from} => {to}")); }
+                if let Some((from, to)) = answer_count { parts.push(format!("answers {from} => {to}")); }
+                if !answers_added.is_empty() { parts.push(format!("+{{{}}}", answers_added.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(", "))); }
+                if !answers_removed.is_empty() { parts.push(format!("-{{{}}}", answers_removed.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(", "))); }
+                if let Some((from, to)) = outer_total_turns { parts.push(format!("turns {} => {} ({:+.1})", write_turns(*from, 1.0, false), write_turns(*to, 1.0, false), to - from)); }
+
+                write!(f, "~ {path}  {}", parts.join("; "))
+            }
+        }
+    }
+}
+
+impl Display for TreeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in self.changes.iter() {
+            writeln!(f, "{change}")?;
+        }
+
+        let count = |kind: fn(&Change) -> bool| self.changes.iter().filter(|c| kind(&c.change)).count();
+        write!(f, "{} added, {} removed, {} changed",
+            count(|c| matches!(c, Change::Added { .. })),
+            count(|c| matches!(c, Change::Removed { .. })),
+            count(|c| matches!(c, Change::Changed { .. })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_trees() {
+        let left = WordleTree::parse(
+"8448  (*, 2315)   -> clint
+    8181  (*, 2314)   -> soare
+        7282  (*, 767)    -> *
+        114   (> ..are, 25) -> gybed
+        63    (> .O.re, 14) -> mawky
+        5     {afire, azure}
+".lines()).unwrap();
+
+        // Same tree with children reordered and spacing changed: no differences
+        let reordered = WordleTree::parse(
+"8448 (*, 2315) -> clint
+    8181 (*, 2314) -> soare
+        5 {afire, azure}
+        63 (> .O.re, 14) -> mawky
+        114 (> ..are, 25) -> gybed
+        7282 (*, 767) -> *
+".lines()).unwrap();
+        assert!(left.diff(&reordered).is_empty());
+
+        let right = WordleTree::parse(
+"8440  (*, 2315)   -> clint
+    8173  (*, 2314)   -> soare
+        7282  (*, 767)    -> *
+        106   (> ..are, 25) -> dumpy
+        20    (> .o..., 9) -> fatal
+        5     {afire, azure, azote}
+".lines()).unwrap();
+
+        assert_eq!(left.diff(&right).to_string(),
+"~ (*)  turns 8448 => 8440 (-8.0)
+~ (*) (*)  turns 8181 => 8173 (-8.0)
+~ (*) (*) (> ..are)  guess gybed => dumpy; turns 114 => 106 (-8.0)
++ (*) (*) (> .o...) -> fatal  (9 answers, 20 turns, 1 node)
+~ (*) (*) (afire)  answers 2 => 3; +{azote}
+- (*) (*) (> .O.re) -> mawky  (14 answers, 63 turns, 1 node)
+1 added, 1 removed, 4 changed");
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use crate::{rank, word::Word, hard_mode::HardMode, objective::TurnCounts, wordle_tree::*};
+
+/// The exact expected outcome of playing a WordleTree for every answer once.
+///  Games follow the tree just as TreePlayer does; where it has no specific guess, the remaining answers are guessed
+///  uniformly at random, and the expected turns for that are computed exactly rather than sampled.
+pub struct Evaluation {
+    pub turn_counts: TurnCounts,                    // Expected answers solved on each turn, across every answer
+    node_counts: HashMap<Vec<usize>, TurnCounts>,   // The same, for the games ending at each node (by path of child indices)
+}
+
+impl WordleTree {
+    /// Compute the exact expected turns for every answer with this tree.
+    ///  In hard mode, a tree guess which doesn't use every revealed hint switches the game to random guessing, as in TreePlayer.
+    pub fn evaluate(&self, answers: &[Word], hard_mode: bool) -> Evaluation {
+        let mut result = Evaluation { turn_counts: TurnCounts::new(), node_counts: HashMap::new() };
+        let mut cache = HashMap::new();
+        evaluate_recurse(self, answers.to_vec(), 0, 0, HardMode::new(), hard_mode, &mut Vec::new(), &mut cache, &mut result);
+        result
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn evaluate_recurse(node: &WordleTree, cluster: Vec<Word>, turns_before: usize, known_letters: usize, rules: HardMode, hard_mode: bool, path: &mut Vec<usize>, cache: &mut HashMap<Vec<Word>, TurnCounts>, result: &mut Evaluation) {
+    // Guessing policies choose from the answers left, so they are evaluated exactly too
+    let guess = match node.next_guess.choose(&cluster) {
+        Some(word) if !hard_mode || rules.allows(word) => word,
+        _ => {
+            // Guess randomly for the rest of these games
+            let counts = rank::turn_counts_random_cached(&cluster, turns_before, cache);
+            result.add(path, &counts);
+            return;
+        }
+    };
+
+    if cluster.contains(&guess) {
+        result.add(path, &TurnCounts::solved_on(turns_before + 1));
+    }
+
+    let mut map = HashMap::new();
+    rank::split(&cluster, guess, &mut map);
+
+    for (response, subcluster) in map {
+        let mut rules = rules;
+        rules.add(guess, response);
+
+        let known_letters = known_letters + response.known_count() as usize;
+        match node.matching_child(Some(guess), Some(response), known_letters, &subcluster) {
+            Some((i, child)) => {
+                path.push(i);
+                evaluate_recurse(child, subcluster, turns_before + 1, known_letters, rules, hard_mode, path, cache, result);
+                path.pop();
+            },
+            None => {
+                // No node for this situation; guess randomly from here
+                let counts = rank::turn_counts_random_cached(&subcluster, turns_before + 1, cache);
+                result.add(path, &counts);
+            }
+        }
+    }
+}
+
+impl Evaluation {
+    fn add(&mut self, path: &[usize], counts: &TurnCounts) {
+        self.turn_counts.add_counts(counts);
+        self.node_counts.entry(path.to_vec()).or_default().add_counts(counts);
+    }
+
+    pub fn total_turns(&self) -> f64 {
+        self.turn_counts.total_turns()
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.turn_counts.total_turns() / self.turn_counts.answer_count()
+    }
+
+    /// The expected outcome for the games passing through the node at 'path' (child indices from the root of the evaluated tree).
+    pub fn node_turn_counts(&self, tree: &WordleTree, path: &[usize]) -> TurnCounts {
+        let mut node = tree;
+        for i in path.iter() {
+            node = &node.subtree.as_ref().unwrap()[*i];
+        }
+
+        let mut result = TurnCounts::new();
+        self.add_node_counts(node, &mut path.to_vec(), &mut result);
+        result
+    }
+
+    fn add_node_counts(&self, node: &WordleTree, path: &mut Vec<usize>, result: &mut TurnCounts) {
+        if let Some(counts) = self.node_counts.get(path) {
+            result.add_counts(counts);
+        }
+
+        if let Some(subtree) = &node.subtree {
+            for (i, child) in subtree.iter().enumerate() {
+                path.push(i);
+                self.add_node_counts(child, path, result);
+                path.pop();
+            }
+        }
+    }
+
+    /// Write out the evaluated tree with the exact turns through each node, in the same layout as TreePlayer::to_string.
+    pub fn to_string(&self, tree: &WordleTree, options: &WordleTreeToStringOptions) -> String {
+        let mut result = String::new();
+        self.add_with_turns(tree, &mut Vec::new(), options, &mut result);
+        result
+    }
+
+    fn add_with_turns(&self, node: &WordleTree, path: &mut Vec<usize>, options: &WordleTreeToStringOptions, result: &mut String) {
+        let mut counts = TurnCounts::new();
+        self.add_node_counts(node, &mut path.clone(), &mut counts);
+
+        // Hide this subtree if no games reach it
+        if counts.answer_count() == 0.0 && !options.show_zero_turn_paths {
+            return;
+        }
+
+        pad_to_length(result.len() + 4 * path.len(), result);
+        let start = result.len();
+
+        let game_count = if options.show_average_turns { counts.answer_count().max(1.0) } else { 1.0 };
+        result.push_str(&write_turns(counts.total_turns(), game_count, options.show_average_turns));
+        result.push(' ');
+        if node.outer_total_turns > 0.0 {
+            pad_to_length(start + 6, result);
+        }
+
+        node.add_self_to_string(options, 0, result);
+
+        if let Some(subtree) = &node.subtree {
+            for (i, child) in subtree.iter().enumerate() {
+                path.push(i);
+                self.add_with_turns(child, path, options, result);
+                path.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::{wv, smart_trim};
+    use crate::objective::Objective;
+    use crate::response::Response;
+    use crate::wordle_tree::{builders, tree_player::TreePlayer};
+    use super::*;
+
+    #[test]
+    fn evaluate_exact() {
+        let answers = wv("dowdy, fatal, tally, waltz");
+        let tree = builders::build("standard", &answers, &[], &wv("parse, fatal"), false, Objective::TotalTurns, None).unwrap();
+
+        // FATAL solves itself on turn two, DOWDY is alone on turn three, and TALLY and WALTZ take three and four.
+        //  As with TreePlayer, games won by a guess count for the node guessing it, not the leaf for the answer.
+        let evaluation = tree.evaluate(&answers, false);
+        assert_eq!(evaluation.turn_counts.to_string(), "[0, 1, 2, 1]");
+        assert_eq!(evaluation.total_turns(), tree.outer_total_turns);
+        assert_eq!(evaluation.average_turns(), 3.0);
+
+        let mut options = WordleTreeToStringOptions::default();
+        options.show_zero_turn_paths = true;
+        options.show_original_turns = false;
+        assert_eq!(smart_trim(&evaluation.to_string(&tree, &options)),
+"12 (*, 4) -> parse [1, 0, 1]
+    12 (*, 4) -> fatal [1, 1]
+        0 {fatal}
+        3 (= 1, 1) -> * {dowdy}
+        7 (= 2, 2) -> * {tally, waltz}");
+
+        // Random guessing after PARSE: DOWDY on turn two, and {fatal, tally, waltz} take 5.33 turns after one each
+        let tree = WordleTree::parse("(*, 4) -> parse".lines()).unwrap();
+        let evaluation = tree.evaluate(&answers, false);
+        assert_eq!(format!("{:.2}", evaluation.total_turns()), "10.33");
+        assert_eq!(evaluation.node_turn_counts(&tree, &[]), evaluation.turn_counts);
+    }
+
+    #[test]
+    fn evaluate_hard_mode() {
+        // CLINT ignores the A revealed by PARSE for {fatal, tally, waltz}, so those games are random a turn sooner in hard mode.
+        //  (CLINT doesn't split them, so it just costs them a turn each otherwise.) DOWDY gets CLINT either way.
+        let answers = wv("dowdy, fatal, tally, waltz");
+        let tree = WordleTree::parse("(*, 4) -> parse\n    (*, 4) -> clint".lines()).unwrap();
+
+        let easy = tree.evaluate(&answers, false);
+        let hard = tree.evaluate(&answers, true);
+        assert_eq!(format!("{:.2}", easy.total_turns()), "14.33");
+        assert_eq!(format!("{:.2}", hard.total_turns()), "11.33");
+    }
+
+    #[test]
+    fn evaluate_policies() {
+        let mut answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        answers.sort();
+
+        // Guessing policies all the way down never guess randomly, so playing every answer with TreePlayer gives the exact turns too
+        let tree = WordleTree::parse(
+"(*, 2315) -> soare
+    (*, 2314) -> clint|dumpy
+        (*, 2313) -> *common
+            (*, 2312) -> *first
+                (*, 2311) -> *first
+                    (*, 2310) -> *first
+                        (*, 2309) -> *first
+                            (*, 2308) -> *first
+                                (*, 2307) -> *first".lines()).unwrap();
+
+        let mut player = TreePlayer::new(&tree);
+        let mut total_turns = 0;
+        for answer in answers.iter() {
+            let mut answers_left = answers.clone();
+            for turn in 1.. {
+                let guess = player.choose(&Vec::new(), turn, &answers_left).unwrap();
+                if guess == *answer {
+                    total_turns += turn;
+                    break;
+                }
+
+                let response = Response::score(guess, *answer);
+                answers_left.retain(|a| Response::score(guess, *a) == response);
+            }
+        }
+
+        assert_eq!(tree.evaluate(&answers, false).total_turns(), total_turns as f64);
+    }
+
+    #[test]
+    fn evaluate_matches_build() {
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let tree = builders::build("standard", &answers, &[], &wv("soare, clint"), false, Objective::TotalTurns, None).unwrap();
+
+        // Every answer is counted once; the builder estimates the same tree within a few turns per hundred answers
+        let evaluation = tree.evaluate(&answers, false);
+        assert_eq!(evaluation.turn_counts.answer_count().round(), 2315.0);
+        assert!((evaluation.total_turns() - tree.outer_total_turns).abs() < 0.03 * 2315.0);
+    }
+}
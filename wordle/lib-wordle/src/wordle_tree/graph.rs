@@ -0,0 +1,276 @@
+use crate::wordle_tree::*;
+
+// Nodes are colored from green at or under GOOD_AVERAGE_TURNS, through yellow, to red at or over BAD_AVERAGE_TURNS
+const GOOD_AVERAGE_TURNS: f64 = 3.0;
+const BAD_AVERAGE_TURNS: f64 = 5.0;
+const COLORS: [(f64, f64, f64); 3] = [(155.0, 227.0, 155.0), (245.0, 224.0, 140.0), (242.0, 154.0, 154.0)];
+const NO_TURNS_COLOR: &str = "#ffffff";
+
+// SVG layout, in pixels, for a 12px monospace font
+const CHAR_WIDTH: f64 = 7.2;
+const LINE_HEIGHT: f64 = 15.0;
+const PADDING: f64 = 6.0;
+const MARGIN: f64 = 10.0;
+const HORIZONTAL_GAP: f64 = 12.0;
+const VERTICAL_GAP: f64 = 36.0;
+
+/// A node to draw, with the lines of its label
+struct GraphNode {
+    lines: Vec<String>,
+    color: String,
+    depth: usize,
+    children: Vec<usize>,
+}
+
+impl WordleTree {
+    /// Write this tree as a Graphviz DOT digraph, with nodes labeled and colored by average turns.
+    ///  Subtrees with fewer than 'collapse_under' answers are drawn as their top node only (0 to draw everything).
+    ///  The options choose what labels show as they do for the text form; the simulation options don't apply.
+    pub fn to_dot(&self, options: &WordleTreeToStringOptions, collapse_under: usize) -> String {
+        let nodes = graph_nodes(self, options, collapse_under);
+
+        let mut result = String::new();
+        result.push_str("digraph strategy {\n");
+        result.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"monospace\", fontsize=12];\n");
+
+        for (i, node) in nodes.iter().enumerate() {
+            let label = node.lines.iter().map(|l| dot_escape(l)).collect::<Vec<String>>().join("\\n");
+            result.push_str(&format!("    n{i} [label=\"{label}\", fillcolor=\"{}\"];\n", node.color));
+        }
+
+        for (i, node) in nodes.iter().enumerate() {
+            for child in node.children.iter() {
+                result.push_str(&format!("    n{i} -> n{child};\n"));
+            }
+        }
+
+        result.push_str("}\n");
+        result
+    }
+
+    /// Write this tree as a standalone SVG image, laid out top-down with each parent centered over its children.
+    ///  Labels, colors, and collapsing are the same as for to_dot.
+    pub fn to_svg(&self, options: &WordleTreeToStringOptions, collapse_under: usize) -> String {
+        let nodes = graph_nodes(self, options, collapse_under);
+
+        // Size each box, and the widest and tallest at each depth
+        let sizes = nodes.iter().map(|n| {
+            let chars = n.lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            (chars as f64 * CHAR_WIDTH + 2.0 * PADDING, n.lines.len() as f64 * LINE_HEIGHT + 2.0 * PADDING)
+        }).collect::<Vec<(f64, f64)>>();
+
+        let depth_count = nodes.iter().map(|n| n.depth + 1).max().unwrap_or(0);
+        let mut level_tops = vec![MARGIN; depth_count + 1];
+        for d in 0..depth_count {
+            let height = nodes.iter().zip(sizes.iter()).filter(|(n, _)| n.depth == d).map(|(_, s)| s.1).fold(0.0, f64::max);
+            level_tops[d + 1] = level_tops[d] + height + VERTICAL_GAP;
+        }
+
+        let mut widths = vec![0.0; nodes.len()];
+        subtree_width(&nodes, &sizes, 0, &mut widths);
+
+        let mut centers = vec![0.0; nodes.len()];
+        place(&nodes, &widths, 0, MARGIN, &mut centers);
+
+        let width = widths[0] + 2.0 * MARGIN;
+        let height = level_tops[depth_count] - VERTICAL_GAP + MARGIN;
+
+        let mut result = String::new();
+        result.push_str(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" font-family=\"monospace\" font-size=\"12\">\n"));
+
+        // Edges first, so boxes are drawn over them
+        for (i, node) in nodes.iter().enumerate() {
+            for child in node.children.iter() {
+                let (x1, y1) = (centers[i], level_tops[node.depth] + sizes[i].1);
+                let (x2, y2) = (centers[*child], level_tops[nodes[*child].depth]);
+                result.push_str(&format!("  <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" stroke=\"#888888\"/>\n"));
+            }
+        }
+
+        for (i, node) in nodes.iter().enumerate() {
+            let (w, h) = sizes[i];
+            let (x, y) = (centers[i] - w / 2.0, level_tops[node.depth]);
+            result.push_str(&format!("  <rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{w:.1}\" height=\"{h:.1}\" rx=\"4\" fill=\"{}\" stroke=\"#555555\"/>\n", node.color));
+
+            result.push_str(&format!("  <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">", centers[i], y + PADDING));
+            for line in node.lines.iter() {
+                result.push_str(&format!("<tspan x=\"{:.1}\" dy=\"{LINE_HEIGHT}\">{}</tspan>", centers[i], xml_escape(line)));
+            }
+            result.push_str("</text>\n");
+        }
+
+        result.push_str("</svg>\n");
+        result
+    }
+}
+
+/// Flatten the tree into the nodes to draw, in pre-order (the root first)
+fn graph_nodes(tree: &WordleTree, options: &WordleTreeToStringOptions, collapse_under: usize) -> Vec<GraphNode> {
+    let mut result = Vec::new();
+    add_graph_node(tree, 0, options, collapse_under, &mut result);
+    result
+}
+
+fn add_graph_node(node: &WordleTree, depth: usize, options: &WordleTreeToStringOptions, collapse_under: usize, result: &mut Vec<GraphNode>) -> usize {
+    let index = result.len();
+    let collapsed = node.answer_count < collapse_under;
+    let hidden = if collapsed { node.node_count() - 1 } else { 0 };
+    result.push(GraphNode { lines: label_lines(node, options, hidden), color: turns_color(node), depth, children: Vec::new() });
+
+    if !collapsed {
+        for child in node.ordered_children() {
+            let child_index = add_graph_node(child, depth + 1, options, collapse_under, result);
+            result[index].children.push(child_index);
+        }
+    }
+
+    index
+}
+
+/// The label for a node: what the text form writes for it, one part per line
+fn label_lines(node: &WordleTree, options: &WordleTreeToStringOptions, hidden: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    // Answer leaves are just their answers, as in the text form
+    let skip_identifier = node.identifier.is_cluster() && node.next_guess == WordleGuess::Random && node.answer_count <= LIST_ANSWERS_MAX_COUNT && !options.always_show_identifiers;
+    if !skip_identifier {
+        match node.identifier {
+            WordleTreeIdentifier::Response(_, _) => lines.push(format!("(> {}, {})", node.identifier, node.answer_count)),
+            _ => lines.push(format!("({}, {})", node.identifier, node.answer_count)),
+        }
+
+        lines.push(format!("-> {}{}", node.next_guess, if node.probe { " (probe)" } else { "" }));
+    }
+
+    if node.outer_total_turns != 0.0 && options.show_original_turns {
+        let turns = write_turns(node.outer_total_turns, node.answer_count.max(1) as f64, options.show_average_turns);
+        lines.push(format!("{turns} {}", if options.show_average_turns { "avg turns" } else { "turns" }));
+    }
+
+    if let (true, Some(cluster_vector)) = (options.show_cluster_vectors, &node.cluster_vector) {
+        let cv = cluster_vector.to_string();
+        if !cv.is_empty() { lines.push(cv); }
+    }
+
+    if skip_identifier || options.show_answers {
+        if let Some(answers) = node.answers.as_ref().filter(|a| !a.is_empty() && a.len() <= LIST_ANSWERS_MAX_COUNT) {
+            let mut answers = answers.clone();
+            answers.sort();
+            lines.push(format!("{{{}}}", answers.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(", ")));
+        }
+    }
+
+    if let Some(comment) = &node.comment {
+        lines.push(comment.clone());
+    }
+
+    if hidden > 0 {
+        lines.push(format!("+{hidden} nodes collapsed"));
+    }
+
+    lines
+}
+
+/// Green to yellow to red by the average turns for games through the node; white if the tree has no turns for it
+fn turns_color(node: &WordleTree) -> String {
+    if node.outer_total_turns <= 0.0 || node.answer_count == 0 {
+        return NO_TURNS_COLOR.to_string();
+    }
+
+    let average = node.outer_total_turns / node.answer_count as f64;
+    let position = ((average - GOOD_AVERAGE_TURNS) / (BAD_AVERAGE_TURNS - GOOD_AVERAGE_TURNS)).clamp(0.0, 1.0) * 2.0;
+    let (from, to) = if position < 1.0 { (COLORS[0], COLORS[1]) } else { (COLORS[1], COLORS[2]) };
+    let t = if position < 1.0 { position } else { position - 1.0 };
+
+    let mix = |l: f64, r: f64| (l + (r - l) * t).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+fn subtree_width(nodes: &[GraphNode], sizes: &[(f64, f64)], index: usize, widths: &mut Vec<f64>) -> f64 {
+    let children = &nodes[index].children;
+    let children_width = children.iter().map(|c| subtree_width(nodes, sizes, *c, widths)).sum::<f64>() + HORIZONTAL_GAP * children.len().saturating_sub(1) as f64;
+
+    widths[index] = sizes[index].0.max(children_width);
+    widths[index]
+}
+
+/// Center each node in the space for its subtree, starting at 'left', with its children side by side beneath it
+fn place(nodes: &[GraphNode], widths: &[f64], index: usize, left: f64, centers: &mut Vec<f64>) {
+    centers[index] = left + widths[index] / 2.0;
+
+    let children = &nodes[index].children;
+    let children_width = children.iter().map(|c| widths[*c]).sum::<f64>() + HORIZONTAL_GAP * children.len().saturating_sub(1) as f64;
+
+    let mut next = left + (widths[index] - children_width) / 2.0;
+    for child in children.iter() {
+        place(nodes, widths, *child, next, centers);
+        next += widths[*child] + HORIZONTAL_GAP;
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE: &str =
+"12  (*, 4)    -> parse  [1, 0, 1]
+    7   (*, 3)    -> fatal
+        4   {tally}
+        10  (= 2, 2) -> * {dowdy, waltz}";
+
+    #[test]
+    fn graph_dot() {
+        let tree = WordleTree::parse(TREE.lines()).unwrap();
+        let options = WordleTreeToStringOptions::default();
+
+        // Children are drawn in the text form's order (bigger clusters first), not the order they were written in
+        assert_eq!(tree.to_dot(&options, 0),
+r##"digraph strategy {
+    node [shape=box, style="rounded,filled", fontname="monospace", fontsize=12];
+    n0 [label="(*, 4)\n-> parse\n12 turns\n[1, 0, 1]", fillcolor="#9be39b"];
+    n1 [label="(*, 3)\n-> fatal\n7 turns", fillcolor="#9be39b"];
+    n2 [label="(= 2, 2)\n-> *\n10 turns\n{dowdy, waltz}", fillcolor="#f29a9a"];
+    n3 [label="4 turns\n{tally}", fillcolor="#f5e08c"];
+    n0 -> n1;
+    n1 -> n2;
+    n1 -> n3;
+}
+"##);
+
+        // Averages and identifiers carry over from the text options; the FATAL subtree (3 answers) collapses under 4
+        let mut options = WordleTreeToStringOptions::default();
+        options.show_average_turns = true;
+        options.always_show_identifiers = true;
+        let dot = tree.to_dot(&options, 4);
+        assert!(dot.contains("n0 [label=\"(*, 4)\\n-> parse\\n3.000 avg turns\\n[1, 0, 1]\""));
+        assert!(dot.contains("n1 [label=\"(*, 3)\\n-> fatal\\n2.333 avg turns\\n+2 nodes collapsed\""));
+        assert!(!dot.contains("    n2 ["));
+    }
+
+    #[test]
+    fn graph_svg() {
+        let tree = WordleTree::parse(TREE.lines()).unwrap();
+        let options = WordleTreeToStringOptions::default();
+
+        let svg = tree.to_svg(&options, 0);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert!(svg.contains(">-&gt; parse</tspan>"));
+
+        // The root is centered over its only child
+        let x = |i: usize| svg.lines().filter(|l| l.starts_with("  <text")).nth(i).unwrap().split('"').nth(1).unwrap().to_string();
+        assert_eq!(x(0), x(1));
+
+        assert_eq!(tree.to_svg(&options, 4).matches("<rect").count(), 2);
+    }
+}
@@ -0,0 +1,271 @@
+use std::str::Lines;
+use crate::{response::Response, word::Word, wordle_tree::*};
+
+// Conditions are padded to this width; children are indented to line up under their parent's guess
+const CONDITION_WIDTH: usize = 5;
+const SEPARATOR: &str = "  >  ";
+
+/*  The human cheat sheet form is the compact version of a strategy a person memorizes and plays from:
+
+    clint
+    .l...  >  salle
+              .al..  >  royal
+    ..IN.  >  greys (__ing, __ine)
+    3+     >  * (guess)
+    *      >  soare
+
+    The first line is the opening guess. Each other line is "condition  >  guess", under the nearest line above it with less indent
+    (lines without indent are under the opening guess). Conditions are the response to the parent's guess (uppercase green, lowercase
+    yellow, '.' or '_' black), "N+" when at least N green or yellow tiles have been shown across all guesses so far (a letter shown
+    again by a later guess counts again), or "*" for anything else. Guesses are written as in the full form (a word, "*", "*first",
+    "*common", or "word|word"). Anything after the guess is kept as a comment on the node.
+*/
+
+impl WordleTree {
+    /// Whether text is in the human cheat sheet form (the first line is just the opening guess), rather than the full form.
+    pub fn is_human_format(text: Lines) -> bool {
+        let mut lines = text.filter(|l| !l.trim().is_empty());
+        lines.next().is_some_and(|first| !first.contains("->") && !first.contains('{'))
+    }
+
+    /// Parse the human cheat sheet form, returning the line number of each node in file order, as parse_with_line_numbers does.
+    ///  Answer counts, turns, and answers aren't part of this form and are left empty.
+    pub fn parse_human(text: Lines) -> Result<(WordleTree, Vec<usize>), (usize, String)> {
+        let mut line_numbers = Vec::new();
+        let mut parent_stack: Vec<(usize, WordleTree)> = Vec::new();
+
+        for (line_number, line) in text.enumerate().map(|(i, l)| (i + 1, l)) {
+            if line.trim().is_empty() { continue; }
+            let tokens = tokenize(line);
+
+            // The first line is the opening guess
+            if line_numbers.is_empty() {
+                let mut root = WordleTree::new(WordleTreeIdentifier::Any, parse_guess(line_number, tokens[0])?);
+                root.comment = comment(line, &tokens[1..]);
+                parent_stack.push((0, root));
+                line_numbers.push(line_number);
+                continue;
+            }
+
+            // Finish nodes indented at least as far as this line; lines without indent are under the opening guess
+            let indent = tokens[0].0 - 1;
+            while parent_stack.len() > 1 && parent_stack.last().unwrap().0 >= indent {
+                let (_, child) = parent_stack.pop().unwrap();
+                parent_stack.last_mut().unwrap().1.add_child_without_rollup(child);
+            }
+
+            if tokens.len() < 3 || tokens[1].1 != ">" {
+                return Err(error(line_number, tokens.get(1).copied().unwrap_or(tokens[0]), "Expected 'condition  >  guess'"));
+            }
+
+            let last_guess = parent_stack.last().unwrap().1.next_guess.specific();
+            let mut node = WordleTree::new(parse_condition(line_number, tokens[0], last_guess)?, parse_guess(line_number, tokens[2])?);
+            node.comment = comment(line, &tokens[3..]);
+
+            parent_stack.push((indent, node));
+            line_numbers.push(line_number);
+        }
+
+        while parent_stack.len() > 1 {
+            let (_, child) = parent_stack.pop().unwrap();
+            parent_stack.last_mut().unwrap().1.add_child_without_rollup(child);
+        }
+
+        match parent_stack.pop() {
+            Some((_, root)) => Ok((root, line_numbers)),
+            None => Err((1, "@(1, 1) \"\": Out of content when more expected.".to_string())),
+        }
+    }
+
+    /// Write this tree in the human cheat sheet form, with children in the order they are stored.
+    ///  Only Response, KnownLettersAtLeast, and Any identifiers can be written; counts, turns, cluster vectors, and answers are left out.
+    pub fn to_human_string(&self) -> Result<String, String> {
+        let mut result = self.next_guess.to_string();
+        if let Some(comment) = &self.comment {
+            result.push(' ');
+            result.push_str(comment);
+        }
+        result.push('\n');
+
+        for child in self.subtree.iter().flatten() {
+            child.add_to_human_string(0, &mut result)?;
+        }
+
+        Ok(result)
+    }
+
+    fn add_to_human_string(&self, indent: usize, result: &mut String) -> Result<(), String> {
+        let condition = match self.identifier {
+            WordleTreeIdentifier::Response(guess, response) => response.to_knowns_string(&guess),
+            WordleTreeIdentifier::KnownLettersAtLeast(count) => format!("{count}+"),
+            WordleTreeIdentifier::Any => "*".to_string(),
+            _ => return Err(format!("'{}' can't be written in the human form, which only has responses, 'N+', and '*' conditions.", self.describe())),
+        };
+
+        let start = result.len();
+        pad_to_length(start + indent, result);
+        result.push_str(&condition);
+        pad_to_length(start + indent + CONDITION_WIDTH, result);
+        result.push_str(SEPARATOR);
+        result.push_str(&self.next_guess.to_string());
+
+        if let Some(comment) = &self.comment {
+            result.push(' ');
+            result.push_str(comment);
+        }
+        result.push('\n');
+
+        for child in self.subtree.iter().flatten() {
+            child.add_to_human_string(indent + CONDITION_WIDTH + SEPARATOR.len(), result)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Split a line into whitespace-separated tokens with the (1-based) column each starts at
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+
+    for (i, c) in line.char_indices().chain(std::iter::once((line.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                result.push((line[..s].chars().count() + 1, &line[s..i]));
+                start = None;
+            },
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// Everything on the line from the given tokens on
+fn comment(line: &str, rest: &[(usize, &str)]) -> Option<String> {
+    let (column, _) = rest.first()?;
+    Some(line.chars().skip(column - 1).collect::<String>().trim_end().to_string())
+}
+
+fn error(line_number: usize, (column, token): (usize, &str), message: &str) -> (usize, String) {
+    (line_number, format!("@({line_number}, {column}) \"{token}\": {message}"))
+}
+
+fn parse_guess(line_number: usize, token: (usize, &str)) -> Result<WordleGuess, (usize, String)> {
+    WordleGuess::parse(token.1).ok_or_else(|| error(line_number, token, "Not a valid word, '*', '*first', '*common', or 'word|word'"))
+}
+
+fn parse_condition(line_number: usize, token: (usize, &str), last_guess: Option<Word>) -> Result<WordleTreeIdentifier, (usize, String)> {
+    if token.1 == "*" {
+        return Ok(WordleTreeIdentifier::Any);
+    }
+
+    if let Some(count) = token.1.strip_suffix('+').and_then(|c| c.parse::<usize>().ok()) {
+        return Ok(WordleTreeIdentifier::KnownLettersAtLeast(count));
+    }
+
+    let Some(response) = Response::from_knowns_str(token.1) else {
+        return Err(error(line_number, token, "Expected a response, 'N+', or '*'"));
+    };
+
+    let Some(guess) = last_guess else {
+        return Err(error(line_number, token, "Response found without a specific previous guess."));
+    };
+
+    // The letters must be the previous guess's, so typos don't silently match something else
+    if response.to_knowns_string(&guess) != token.1.replace('_', ".") {
+        return Err(error(line_number, token, &format!("Response letters don't match the previous guess '{guess}'.")));
+    }
+
+    Ok(WordleTreeIdentifier::Response(guess, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+    use crate::{w, wv};
+    use super::*;
+
+    const SHEET: &str =
+"clint
+.l...  >  salle
+          .al..  >  royal
+..IN.  >  greys (__ing, __ine)
+3+     >  * (guess)
+*      >  soare
+";
+
+    #[test]
+    fn human_parse() {
+        assert!(WordleTree::is_human_format(SHEET.lines()));
+        assert!(!WordleTree::is_human_format("8215  (*, 2315)   -> clint".lines()));
+        assert!(!WordleTree::is_human_format("{fatal}".lines()));
+
+        let (tree, line_numbers) = WordleTree::parse_with_line_numbers(SHEET.lines()).unwrap();
+        assert_eq!(line_numbers, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(tree.next_guess, WordleGuess::Specific(w("clint")));
+
+        let children = tree.subtree.as_ref().unwrap();
+        assert_eq!(children.len(), 4);
+        assert_eq!(children[0].describe(), "(> .l..., 0) -> salle");
+        assert_eq!(children[0].subtree.as_ref().unwrap()[0].identifier, WordleTreeIdentifier::Response(w("salle"), Response::from_knowns_str(".al..").unwrap()));
+        assert_eq!(children[1].comment, Some("(__ing, __ine)".to_string()));
+        assert_eq!(children[2].identifier, WordleTreeIdentifier::KnownLettersAtLeast(3));
+        assert_eq!(children[2].next_guess, WordleGuess::Random);
+        assert_eq!(children[3].identifier, WordleTreeIdentifier::Any);
+
+        // Three letters known goes random unless a response is listed; fewer goes to SOARE
+        let clint = w("clint");
+        let choose = |answer: &str| {
+            let response = Response::score(clint, w(answer));
+            tree.matching_child(Some(clint), Some(response), known_letters(&[clint], w(answer)), &wv(answer)).unwrap().1.next_guess.clone()
+        };
+        assert_eq!(choose("filet"), WordleGuess::Random);
+        assert_eq!(choose("wring"), WordleGuess::Specific(w("greys")));
+        assert_eq!(choose("sweat"), WordleGuess::Specific(w("soare")));
+
+        // The same sheet round trips exactly
+        assert_eq!(tree.to_human_string().unwrap(), SHEET);
+
+        // Known letter counts are written the same way in the full form
+        let full = WordleTree::parse("(*, 10) -> clint\n    (3+, 4) -> *".lines()).unwrap();
+        assert_eq!(full.subtree.as_ref().unwrap()[0].describe(), "(3+, 4) -> *");
+    }
+
+    #[test]
+    fn human_errors() {
+        assert_eq!(WordleTree::parse("clint\n*  >  *\n    .l...  >  salle".lines()).err().unwrap(), "@(3, 5) \".l...\": Response found without a specific previous guess.");
+        assert_eq!(WordleTree::parse("clint\n.s...  >  salle".lines()).err().unwrap(), "@(2, 1) \".s...\": Response letters don't match the previous guess 'clint'.");
+        assert_eq!(WordleTree::parse("clint\n.l...  >  womb".lines()).err().unwrap(), "@(2, 11) \"womb\": Not a valid word, '*', '*first', '*common', or 'word|word'");
+
+        // Cluster identifiers have no human form
+        let tree = WordleTree::parse("(*, 2315) -> clint\n    (filet, 6) -> *".lines()).unwrap();
+        assert!(tree.to_human_string().is_err());
+    }
+
+    #[test]
+    fn human_sheet_matches_full_tree() {
+        // Line 46 of the cheat sheet has a typo ('womb'), which is reported with its line
+        let text = fs::read_to_string("../data/v13_human.txt").unwrap();
+        let error = WordleTree::parse_with_line_numbers(text.lines()).err().unwrap();
+        assert_eq!(error, (46, "@(46, 21) \"womb\": Not a valid word, '*', '*first', '*common', or 'word|word'".to_string()));
+
+        // With it corrected, the sheet reads and writes back as the same lines, apart from spacing
+        let lines = text.lines().map(|l| if l.ends_with(" womb") { format!("{l}s") } else { l.to_string() }).collect::<Vec<String>>();
+        let sheet = WordleTree::parse(lines.join("\n").lines()).unwrap();
+        let written = sheet.to_human_string().unwrap();
+        let spaced = |l: &str| l.split_whitespace().collect::<Vec<&str>>().join(" ");
+        assert_eq!(written.lines().map(spaced).collect::<Vec<String>>(), lines.iter().map(|l| spaced(l)).filter(|l| !l.is_empty()).collect::<Vec<String>>());
+        assert!(WordleTree::parse(written.lines()).unwrap() == sheet);
+
+        // The cheat sheet plays within a few turns of the full v13 tree it was written from
+        let mut answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        answers.sort();
+        let full = WordleTree::parse(fs::read_to_string("../data/v13.txt").unwrap().lines()).unwrap();
+
+        let sheet_turns = sheet.evaluate(&answers, false).total_turns();
+        let full_turns = full.evaluate(&answers, false).total_turns();
+        assert!((sheet_turns - full_turns).abs() < 5.0, "{sheet_turns} vs {full_turns}");
+    }
+}
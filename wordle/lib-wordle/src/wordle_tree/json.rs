@@ -0,0 +1,207 @@
+use crate::{cluster_vector::ClusterVector, json::Json, response::Response, word::Word, wordle_tree::*};
+
+/*  The JSON form of a WordleTree keeps everything the text form summarizes or leaves out (full cluster vectors, every answer, comments):
+
+    {
+      "outer_total_turns": 8448,
+      "identifier": {"type": "any"},
+      "answer_count": 2315,
+      "next_guess": "clint",
+      "probe": false,
+      "cluster_vector": [...],
+      "answers": [...],
+      "comment": "...",
+      "subtree": [...]
+    }
+
+    Identifiers are {"type": "any"}, {"type": "known_under", "count": 2}, {"type": "known_at_least", "count": 3},
+    {"type": "under_length", "length": 40}, {"type": "equals_length", "length": 2}, {"type": "pattern", "pattern": "?O?RE"},
+    {"type": "response", "guess": "soare", "response": ".O.re"}, or {"type": "cluster", "word": "fatal"}.
+    Guesses are written as in the text form ("clint", "*", "*first", "*common", or "clint|dumpy").
+    "cluster_vector", "answers", "comment", and "subtree" are left out when the node has none.
+*/
+
+impl WordleTree {
+    pub fn to_json(&self) -> Json {
+        let mut fields = vec![
+            ("outer_total_turns", Json::from(self.outer_total_turns)),
+            ("identifier", identifier_to_json(&self.identifier)),
+            ("answer_count", Json::from(self.answer_count)),
+            ("next_guess", Json::from(self.next_guess.to_string())),
+            ("probe", Json::from(self.probe)),
+        ];
+
+        if let Some(cluster_vector) = &self.cluster_vector {
+            fields.push(("cluster_vector", cluster_vector.to_json()));
+        }
+
+        if let Some(answers) = &self.answers {
+            fields.push(("answers", words_to_json(answers)));
+        }
+
+        if let Some(comment) = &self.comment {
+            fields.push(("comment", Json::from(comment.as_str())));
+        }
+
+        if let Some(subtree) = &self.subtree {
+            fields.push(("subtree", Json::Array(subtree.iter().map(|child| child.to_json()).collect())));
+        }
+
+        Json::object(fields)
+    }
+
+    /// Read a tree written by to_json. Errors include the path of child indices to the node, like "subtree[3].subtree[0]: Missing 'answer_count'".
+    pub fn from_json(json: &Json) -> Result<WordleTree, String> {
+        from_json_recurse(json, &mut String::new())
+    }
+}
+
+fn from_json_recurse(json: &Json, path: &mut String) -> Result<WordleTree, String> {
+    let at = |path: &str, message: String| if path.is_empty() { message } else { format!("{path}: {message}") };
+
+    let node = || -> Result<WordleTree, String> {
+        let next_guess = json.str_field("next_guess")?;
+
+        Ok(WordleTree {
+            outer_total_turns: json.f64_field("outer_total_turns")?,
+            identifier: identifier_from_json(json.field("identifier")?)?,
+            answer_count: json.usize_field("answer_count")?,
+            next_guess: WordleGuess::parse(next_guess).ok_or_else(|| format!("'{next_guess}' is not a valid word, '*', '*first', '*common', or 'word|word'"))?,
+            probe: json.get("probe").and_then(|p| p.as_bool()).unwrap_or(false),
+            answers: json.get("answers").map(words_from_json).transpose()?,
+            cluster_vector: json.get("cluster_vector").map(ClusterVector::from_json).transpose()?,
+            comment: json.get("comment").map(|c| c.as_str().map(|c| c.to_string()).ok_or("'comment' must be a string")).transpose()?,
+            subtree: None,
+        })
+    };
+
+    let mut result = node().map_err(|message| at(path, message))?;
+
+    if json.get("subtree").is_some() {
+        let children = json.array_field("subtree").map_err(|message| at(path, message))?;
+        let mut subtree = Vec::new();
+
+        for (i, child) in children.iter().enumerate() {
+            let length = path.len();
+            if !path.is_empty() { path.push('.'); }
+            path.push_str(&format!("subtree[{i}]"));
+
+            subtree.push(from_json_recurse(child, path)?);
+            path.truncate(length);
+        }
+
+        result.subtree = Some(subtree);
+    }
+
+    Ok(result)
+}
+
+fn identifier_to_json(identifier: &WordleTreeIdentifier) -> Json {
+    match identifier {
+        WordleTreeIdentifier::Any => Json::object(vec![("type", "any".into())]),
+        WordleTreeIdentifier::KnownLettersUnder(count) => Json::object(vec![("type", "known_under".into()), ("count", (*count).into())]),
+        WordleTreeIdentifier::KnownLettersAtLeast(count) => Json::object(vec![("type", "known_at_least".into()), ("count", (*count).into())]),
+        WordleTreeIdentifier::UnderLength(length) => Json::object(vec![("type", "under_length".into()), ("length", (*length).into())]),
+        WordleTreeIdentifier::EqualsLength(length) => Json::object(vec![("type", "equals_length".into()), ("length", (*length).into())]),
+        WordleTreeIdentifier::Pattern(pattern) => Json::object(vec![("type", "pattern".into()), ("pattern", pattern_string(pattern).into())]),
+        WordleTreeIdentifier::Response(guess, response) => Json::object(vec![("type", "response".into()), ("guess", guess.to_string().into()), ("response", response.to_knowns_string(guess).into())]),
+        WordleTreeIdentifier::Cluster(word) => Json::object(vec![("type", "cluster".into()), ("word", word.to_string().into())]),
+    }
+}
+
+fn identifier_from_json(json: &Json) -> Result<WordleTreeIdentifier, String> {
+    let word = |key: &str| json.str_field(key).and_then(|text| Word::new(text).ok_or_else(|| format!("'{text}' is not a valid word")));
+
+    match json.str_field("type")? {
+        "any" => Ok(WordleTreeIdentifier::Any),
+        "known_under" => Ok(WordleTreeIdentifier::KnownLettersUnder(json.usize_field("count")?)),
+        "known_at_least" => Ok(WordleTreeIdentifier::KnownLettersAtLeast(json.usize_field("count")?)),
+        "under_length" => Ok(WordleTreeIdentifier::UnderLength(json.usize_field("length")?)),
+        "equals_length" => Ok(WordleTreeIdentifier::EqualsLength(json.usize_field("length")?)),
+        "pattern" => {
+            let text = json.str_field("pattern")?;
+            parse_pattern(text).map(WordleTreeIdentifier::Pattern).ok_or_else(|| format!("'{text}' is not a valid letter pattern"))
+        },
+        "response" => {
+            let guess = word("guess")?;
+            let text = json.str_field("response")?;
+            match Response::from_knowns_str(text) {
+                Some(response) if response.to_knowns_string(&guess) == text.replace('_', ".") => Ok(WordleTreeIdentifier::Response(guess, response)),
+                _ => Err(format!("'{text}' is not a valid response to '{guess}'")),
+            }
+        },
+        "cluster" => Ok(WordleTreeIdentifier::Cluster(word("word")?)),
+        other => Err(format!("Unknown identifier type '{other}'")),
+    }
+}
+
+pub fn words_to_json(words: &[Word]) -> Json {
+    Json::Array(words.iter().map(|w| Json::from(w.to_string())).collect())
+}
+
+pub fn words_from_json(json: &Json) -> Result<Vec<Word>, String> {
+    let values = json.as_array().ok_or("Answers must be an array of words")?;
+    values.iter().map(|v| v.as_str().and_then(Word::new).ok_or_else(|| format!("'{v}' is not a valid word"))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+    use crate::{wv, objective::Objective, wordle_tree::builders};
+    use super::*;
+
+    #[test]
+    fn json_tree_round_trip() {
+        // Every identifier and guess kind, a comment, and a probe survive the round trip
+        let text =
+"100   (*, 2315)  -> soare (probe)  [1, 2, 3, 4, 5, 6, 7]  {fatal, tally}
+    60    (< 40, 30) -> *first
+    50    (known < 2, 20) -> clint|dumpy
+    40    (?O?RE, 10) -> *common
+    30    (3+, 10) -> *
+    20    (> .O.re, 8) -> mawky
+        10    (= 2, 2) -> *
+    5     {afire, azure}
+";
+        let mut tree = WordleTree::parse(text.lines()).unwrap();
+        tree.comment = Some("open \"strong\"".to_string());
+
+        let json = tree.to_json();
+        let read = WordleTree::from_json(&Json::parse(&json.to_pretty_string()).unwrap()).unwrap();
+        assert_eq!(read.to_json(), json);
+        assert_eq!(read.to_string(), tree.to_string());
+        assert_eq!(read.comment, tree.comment);
+        assert_eq!(read.cluster_vector, Some(ClusterVector::new(vec![1, 2, 3, 4, 5, 6, 7])));
+
+        let response = &json.array_field("subtree").unwrap()[4];
+        assert_eq!(response.field("identifier").unwrap().to_string(), r#"{"type":"response","guess":"soare","response":".O.re"}"#);
+    }
+
+    #[test]
+    fn json_tree_lossless() {
+        // The text form summarizes big cluster vectors with '..' and drops them when read; JSON keeps them
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let tree = builders::build("standard", &answers, &[], &wv("soare, clint"), false, Objective::TotalTurns, None).unwrap();
+        let from_text = WordleTree::parse(tree.to_string().lines()).unwrap();
+        let from_json = WordleTree::from_json(&Json::parse(&tree.to_json().to_string()).unwrap()).unwrap();
+
+        assert_eq!(from_text.cluster_vector, None);
+        assert_eq!(from_json.cluster_vector, tree.cluster_vector);
+        assert_eq!(from_json.to_json(), tree.to_json());
+
+        // Real strategy files round trip too
+        let v13 = WordleTree::parse(fs::read_to_string("../data/v13.txt").unwrap().lines()).unwrap();
+        assert_eq!(WordleTree::from_json(&v13.to_json()).unwrap().to_string(), v13.to_string());
+    }
+
+    #[test]
+    fn json_tree_errors() {
+        let error = |text: &str| WordleTree::from_json(&Json::parse(text).unwrap()).err().unwrap();
+        let node = r#""outer_total_turns": 10, "answer_count": 4, "next_guess": "parse""#;
+
+        assert_eq!(error(&format!("{{{node}}}")), "Missing 'identifier'");
+        assert_eq!(error(&format!(r#"{{{node}, "identifier": {{"type": "most"}}}}"#)), "Unknown identifier type 'most'");
+        assert_eq!(error(&format!(r#"{{{node}, "identifier": {{"type": "any"}}, "subtree": [{{{node}, "identifier": {{"type": "response", "guess": "parse", "response": ".s..."}}}}]}}"#)),
+            "subtree[0]: '.s...' is not a valid response to 'parse'");
+    }
+}
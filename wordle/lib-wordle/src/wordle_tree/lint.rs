@@ -0,0 +1,244 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}};
+use crate::{cluster_vector::ClusterVector, rank, word::Word, wordle_tree::*};
+
+// Smaller clusters falling through to random guessing are usually intentional
+const FALL_THROUGH_MIN_ANSWERS: usize = 3;
+
+/// A problem found in a strategy tree file, with the line it is on.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LintIssue {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parse a tree and replay every answer through it, as TreePlayer would, reporting:
+///  - Text which doesn't parse (including Response nodes under a parent without a specific guess)
+///  - Answer counts (except in the human cheat sheet form, which has none), {answers}, and [cluster vectors] which don't match the answers reaching the node
+///  - Nodes which no answers reach (such as Cluster identifiers which never match)
+///  - Siblings which would match but are shadowed by a less specific Cluster or Response identifier listed before them
+///  - Clusters of several answers which match no child and fall through to random guessing
+pub fn lint(text: &str, answers: &[Word]) -> Vec<LintIssue> {
+    let (tree, line_numbers) = match WordleTree::parse_with_line_numbers(text.lines()) {
+        Ok(result) => result,
+        Err((line, message)) => return vec![LintIssue { line, message }],
+    };
+
+    // Parsed nodes are numbered in file order
+    let mut lines = HashMap::new();
+    number_nodes(&tree, &mut Vec::new(), &mut line_numbers.into_iter(), &mut lines);
+
+    let clusters = tree.known_clusters_by_node(answers);
+    let check_counts = !WordleTree::is_human_format(text.lines());
+    let mut issues = Vec::new();
+    lint_recurse(&tree, None, false, check_counts, &mut Vec::new(), &lines, &clusters, &mut issues);
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+fn number_nodes(node: &WordleTree, path: &mut Vec<usize>, line_numbers: &mut impl Iterator<Item = usize>, result: &mut HashMap<Vec<usize>, usize>) {
+    result.insert(path.clone(), line_numbers.next().unwrap_or(0));
+
+    if let Some(subtree) = &node.subtree {
+        for (i, child) in subtree.iter().enumerate() {
+            path.push(i);
+            number_nodes(child, path, line_numbers, result);
+            path.pop();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn lint_recurse(node: &WordleTree, last_guess: Option<Word>, is_shadowed: bool, check_counts: bool, path: &mut Vec<usize>, lines: &HashMap<Vec<usize>, usize>, clusters: &HashMap<Vec<usize>, Vec<(Vec<Word>, usize)>>, issues: &mut Vec<LintIssue>) {
+    let line = lines[path];
+    let mut issue = |line: usize, message: String| issues.push(LintIssue { line, message });
+
+    match clusters.get(path) {
+        None => {
+            // Answer leaves for the parent's guess are written out, but the game is already won
+            let solved_by_parent = node.answers.as_ref().is_some_and(|a| a.len() == 1 && Some(a[0]) == last_guess);
+
+            if !solved_by_parent && !is_shadowed {
+                match node.identifier {
+                    WordleTreeIdentifier::Cluster(word) => issue(line, format!("Cluster identifier '{word}' never matches; no answers reach this node.")),
+                    _ => issue(line, format!("No answers reach '{}'.", node.identifier)),
+                }
+            }
+
+            // Nothing below an unreached node is reached either
+            return;
+        },
+        Some(node_clusters) => {
+            let mut reached = node_clusters.iter().flat_map(|(cluster, _)| cluster.iter().copied()).collect::<Vec<Word>>();
+            reached.sort();
+
+            if check_counts && node.answer_count != reached.len() {
+                let reach = if reached.len() == 1 { "reaches" } else { "reach" };
+                issue(line, format!("Answer count is {}, but {} {reach} this node.", node.answer_count, count_of(reached.len(), "answer")));
+            }
+
+            if let Some(listed) = &node.answers {
+                let mut listed = listed.clone();
+                listed.sort();
+
+                let missing = reached.iter().filter(|a| !listed.contains(a)).map(|a| a.to_string()).collect::<Vec<String>>();
+                let extra = listed.iter().filter(|a| !reached.contains(a)).map(|a| a.to_string()).collect::<Vec<String>>();
+                if !missing.is_empty() { issue(line, format!("Answers {{{}}} reach this node but aren't listed.", missing.join(", "))); }
+                if !extra.is_empty() { issue(line, format!("Answers {{{}}} are listed but don't reach this node.", extra.join(", "))); }
+            }
+        }
+    }
+
+    // Cluster vectors are for the split the node's guess makes of the answers reaching it (ones summarized with '..' aren't kept when parsed)
+    if let Some(listed) = &node.cluster_vector {
+        if node.next_guess != WordleGuess::Random {
+            let mut actual = ClusterVector::new(Vec::new());
+            let mut map = HashMap::new();
+
+            for (cluster, _) in clusters[path].iter() {
+                let Some(guess) = node.next_guess.choose(cluster) else { continue; };
+                rank::split(cluster, guess, &mut map);
+                actual.add_map(&map);
+            }
+
+            if *listed != actual {
+                issue(line, format!("Cluster vector is {listed}, but the answers reaching this node split into {actual}."));
+            }
+        }
+    }
+
+    let Some(subtree) = &node.subtree else { return; };
+    let mut shadowed = Vec::new();
+
+    if node.next_guess != WordleGuess::Random {
+        let mut shadowed_by: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut fall_through: Vec<Vec<Word>> = Vec::new();
+        let mut map = HashMap::new();
+
+        for (cluster, known_letters) in clusters[path].iter() {
+            let Some(guess) = node.next_guess.choose(cluster) else { continue; };
+            rank::split(cluster, guess, &mut map);
+
+            for (response, subcluster) in map.drain() {
+                let known_letters = known_letters + response.known_count() as usize;
+                match node.matching_child(Some(guess), Some(response), known_letters, &subcluster) {
+                    Some((chosen, _)) => {
+                        // The most specific match, if siblings were not checked in order
+                        let mut best = chosen;
+                        for (i, child) in subtree.iter().enumerate() {
+                            if child.identifier.matches(Some(guess), Some(response), known_letters, &subcluster) && child.identifier.is_more_specific(&subtree[best].identifier) {
+                                best = i;
+                            }
+                        }
+
+                        if best != chosen {
+                            *shadowed_by.entry((best, chosen)).or_default() += subcluster.len();
+                        }
+                    },
+                    None => {
+                        if subcluster.len() >= FALL_THROUGH_MIN_ANSWERS {
+                            fall_through.push(subcluster);
+                        }
+                    }
+                }
+            }
+        }
+
+        shadowed = shadowed_by.into_iter().collect::<Vec<((usize, usize), usize)>>();
+        shadowed.sort();
+        for ((best, chosen), count) in shadowed.iter().copied() {
+            let chosen_line = lines[&[path.as_slice(), &[chosen]].concat()];
+            issue(lines[&[path.as_slice(), &[best]].concat()], format!("Shadowed by the less specific '{}' on line {chosen_line}, listed before it, for {}.", subtree[chosen].identifier, count_of(count, "answer")));
+        }
+
+        if !fall_through.is_empty() {
+            fall_through.sort_by(|l, r| r.len().cmp(&l.len()).then(l.cmp(r)));
+            let answer_count = fall_through.iter().map(|c| c.len()).sum::<usize>();
+            let largest = fall_through[0].iter().map(|a| a.to_string()).collect::<Vec<String>>().join(", ");
+            let (clusters, answers) = (count_of(fall_through.len(), "cluster"), count_of(answer_count, "answer"));
+            let (matches, falls) = if fall_through.len() == 1 { ("matches", "falls") } else { ("match", "fall") };
+            issue(line, format!("{clusters} ({answers}) after '{}' {matches} no child and {falls} through to random guessing; largest {{{largest}}}.", node.next_guess));
+        }
+    }
+
+    let last_guess = node.next_guess.specific();
+    for (i, child) in subtree.iter().enumerate() {
+        path.push(i);
+        lint_recurse(child, last_guess, shadowed.iter().any(|((best, _), _)| *best == i), check_counts, path, lines, clusters, issues);
+        path.pop();
+    }
+}
+
+/// "1 answer" or "3 answers"
+pub(crate) fn count_of(count: usize, noun: &str) -> String {
+    if count == 1 { format!("{count} {noun}") } else { format!("{count} {noun}s") }
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::{wv, objective::Objective, wordle_tree::builders};
+    use super::*;
+
+    fn lint_strings(text: &str, answers: &[Word]) -> Vec<String> {
+        lint(text, answers).iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn lint_clean() {
+        // Trees written by the builders replay exactly
+        let answers = wv("dowdy, fatal, tally, waltz");
+        let tree = builders::build("standard", &answers, &[], &wv("parse, fatal"), false, Objective::TotalTurns, None).unwrap();
+        assert!(lint(&tree.to_string(), &answers).is_empty());
+
+        let answers = Word::parse_file(Path::new("../data/2315/answers.txt"));
+        let tree = builders::build("standard", &answers, &[], &wv("soare, clint"), false, Objective::TotalTurns, None).unwrap();
+        assert!(lint(&tree.to_string(), &answers).is_empty());
+    }
+
+    #[test]
+    fn lint_cluster_vectors() {
+        let answers = wv("bobby, boxer, dowdy, fatal, tally, waltz");
+
+        // PARSE leaves {bobby, dowdy}, {fatal, tally, waltz}, and {boxer}; TALLY then splits {fatal, waltz} apart
+        let text =
+"(*, 6) -> parse [1, 1, 1]
+    (> .A..., 3) -> tally [2]
+    (> ....., 2) -> dowdy [2]";
+
+        assert_eq!(lint_strings(text, &answers), vec![
+            "line 3: Cluster vector is [2], but the answers reaching this node split into [1].",
+        ]);
+    }
+
+    #[test]
+    fn lint_problems() {
+        let answers = wv("bobby, boxer, dowdy, fatal, tally, waltz");
+
+        assert_eq!(lint_strings("(*, 6) -> *\n    (> ....., 6) -> fatal", &answers),
+            vec!["line 2: @(2, 8) \".....\": Response node found without known specific previous guess."]);
+
+        // PARSE: {bobby, dowdy} get nothing, {fatal, tally, waltz} get .A..., and {boxer} gets ..r.e
+        let text =
+"(*, 6) -> parse
+    (> ....., 2) -> dowdy {bobby, boxer}
+    (bobby, 2) -> *
+    (dowdy, 2) -> *
+    (> ..r.e, 2) -> boxer";
+
+        assert_eq!(lint_strings(text, &answers), vec![
+            "line 1: 1 cluster (3 answers) after 'parse' matches no child and falls through to random guessing; largest {fatal, tally, waltz}.",
+            "line 2: Answers {dowdy} reach this node but aren't listed.",
+            "line 2: Answers {boxer} are listed but don't reach this node.",
+            "line 3: Shadowed by the less specific '.....' on line 2, listed before it, for 2 answers.",
+            "line 4: Cluster identifier 'dowdy' never matches; no answers reach this node.",
+            "line 5: Answer count is 2, but 1 answer reaches this node.",
+        ]);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}};
+use crate::{cluster_vector::ClusterVector, rank, response::Response, word::Word, wordle_tree::*};
+
+/// A WordleTree replayed on a different answer list.
+pub struct Retargeted {
+    pub tree: WordleTree,
+    pub notes: Vec<RetargetNote>,   // Nodes whose clusters split or changed, in tree order, where new guesses may be needed
+}
+
+pub struct RetargetNote {
+    pub node: String,               // The node as "(identifier, answer count) -> guess", with counts for the new answers (old ones if removed)
+    pub change: RetargetChange,
+}
+
+pub enum RetargetChange {
+    Removed,                                            // No answers reach the node on the new list, so it was dropped
+    Changed { added: Vec<Word>, removed: Vec<Word> },   // Answers joined or left clusters which end here (guessed randomly or falling through)
+    Split { guess: WordleGuess, clusters: Vec<Vec<Word>> },    // New answers got responses to the guess here which no old answer did
+    Guess { old: Word, new: Word },                     // The guessing policy here picks a different word for the new answers
+}
+
+// The answers on the old and new lists with the same responses to every guess so far, and the green or yellow tiles shown for them
+type ClusterPair = (Vec<Word>, Vec<Word>, usize);
+
+impl WordleTree {
+    /// Replay this tree, written for 'old_answers', on 'new_answers'.
+    ///  Each cluster follows the path its old answers took (where the old and new answers pick the same guess), so clusters whose first answer changed still reach their node.
+    ///  Cluster identifiers are renamed to the new first answer, and answer counts, {answers}, cluster vectors, and total turns are recomputed.
+    ///  Nodes no answers reach any more (including ones no old answers reached) are dropped, except the leaf for a guess which is still an answer.
+    ///  The answer lists may be in any order.
+    pub fn retarget(&self, old_answers: &[Word], new_answers: &[Word]) -> Retargeted {
+        let mut notes = Vec::new();
+        let mut reached = Vec::new();
+
+        // Cluster identifiers name the alphabetically first answer, so work from sorted lists (answer files aren't)
+        let sorted = |answers: &[Word]| { let mut answers = answers.to_vec(); answers.sort(); answers.dedup(); answers };
+        let (old_answers, new_answers) = (sorted(old_answers), sorted(new_answers));
+        let pairs = vec![(old_answers, new_answers.clone(), 0)];
+
+        let Some(mut tree) = retarget_recurse(self, pairs, &mut Vec::new(), &mut reached, &mut notes) else {
+            return Retargeted { tree: WordleTree::new(self.identifier, WordleGuess::Random), notes };
+        };
+
+        // Recompute the total turns for every node the new answers reach
+        let evaluation = tree.evaluate(&new_answers, false);
+        let turns = reached.iter().map(|path| evaluation.node_turn_counts(&tree, path).total_turns()).collect::<Vec<f64>>();
+        for (path, total_turns) in reached.iter().zip(turns) {
+            let node = path.iter().fold(&mut tree, |node, i| &mut node.subtree.as_mut().unwrap()[*i]);
+            node.outer_total_turns = total_turns;
+        }
+
+        Retargeted { tree, notes }
+    }
+}
+
+fn retarget_recurse(node: &WordleTree, pairs: Vec<ClusterPair>, path: &mut Vec<usize>, reached: &mut Vec<Vec<usize>>, notes: &mut Vec<RetargetNote>) -> Option<WordleTree> {
+    let mut new = pairs.iter().flat_map(|(_, n, _)| n.iter().copied()).collect::<Vec<Word>>();
+    if new.is_empty() {
+        notes.push(RetargetNote { node: node.describe(), change: RetargetChange::Removed });
+        return None;
+    }
+
+    // A Cluster node gets a single cluster, which now may start with a different answer
+    new.sort();
+    let identifier = match node.identifier {
+        WordleTreeIdentifier::Cluster(_) => WordleTreeIdentifier::Cluster(new[0]),
+        identifier => identifier,
+    };
+
+    let mut result = WordleTree {
+        outer_total_turns: node.outer_total_turns,
+        identifier,
+        answer_count: new.len(),
+        next_guess: node.next_guess.clone(),
+        probe: node.probe,
+        answers: node.answers.as_ref().map(|_| new.clone()),
+        cluster_vector: None,
+        comment: node.comment.clone(),
+        subtree: None,
+    };
+    reached.push(path.clone());
+
+    // Find which child each cluster goes to, following the old answers where there are any
+    let mut by_child: HashMap<usize, Vec<ClusterPair>> = HashMap::new();
+    let mut ended_here = Vec::new();
+    let mut split = Vec::new();
+    let mut guess_changes = Vec::new();
+
+    match node.next_guess {
+        WordleGuess::Random => ended_here = pairs,
+        _ => {
+            let mut cluster_vector = ClusterVector::new(Vec::new());
+            let (mut old_map, mut new_map) = (HashMap::new(), HashMap::new());
+
+            for (old, new, known_letters) in pairs.into_iter() {
+                // Guessing policies choose from the answers left, so the new list may pick a different word than the old one did
+                let Some(guess) = node.next_guess.choose(&new).or_else(|| node.next_guess.choose(&old)) else { continue; };
+                let old_guess = node.next_guess.choose(&old).unwrap_or(guess);
+
+                rank::split(&new, guess, &mut new_map);
+                cluster_vector.add_map(&new_map);
+
+                if old_guess == guess {
+                    ended_here.push((old.iter().filter(|a| **a == guess).copied().collect(), new.iter().filter(|a| **a == guess).copied().collect(), known_letters));
+                }
+
+                let mut route = |response: Response, old_sub: Vec<Word>, new_sub: Vec<Word>| {
+                    let route = if old_sub.is_empty() { &new_sub } else { &old_sub };
+                    let known_letters = known_letters + response.known_count() as usize;
+
+                    match node.matching_child(Some(guess), Some(response), known_letters, route) {
+                        Some((i, _)) => by_child.entry(i).or_default().push((old_sub, new_sub, known_letters)),
+                        None => ended_here.push((old_sub, new_sub, known_letters)),
+                    }
+                };
+
+                if old_guess != guess {
+                    // The old answers' responses were to another word, so the new answers find their own way
+                    if !guess_changes.contains(&(old_guess, guess)) {
+                        guess_changes.push((old_guess, guess));
+                    }
+
+                    let mut new_subs = new_map.drain().collect::<Vec<(Response, Vec<Word>)>>();
+                    new_subs.sort();
+                    for (response, new_sub) in new_subs {
+                        route(response, Vec::new(), new_sub);
+                    }
+
+                    continue;
+                }
+
+                rank::split(&old, guess, &mut old_map);
+
+                let mut responses = old_map.keys().chain(new_map.keys()).copied().collect::<Vec<Response>>();
+                responses.sort();
+                responses.dedup();
+
+                for response in responses {
+                    let old_sub = old_map.remove(&response).unwrap_or_default();
+                    let new_sub = new_map.remove(&response).unwrap_or_default();
+
+                    // Report clusters of only new answers where they first appear
+                    if old_sub.is_empty() && !old.is_empty() {
+                        split.push(new_sub.clone());
+                    }
+
+                    route(response, old_sub, new_sub);
+                }
+            }
+
+            if node.cluster_vector.is_some() {
+                result.cluster_vector = Some(cluster_vector);
+            }
+        }
+    }
+
+    // Note answers joining or leaving the clusters which end here
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    for (old, new, _) in ended_here.iter().filter(|(old, _, _)| !old.is_empty()) {
+        added.extend(new.iter().filter(|a| !old.contains(a)));
+        removed.extend(old.iter().filter(|a| !new.contains(a)));
+    }
+
+    if !added.is_empty() || !removed.is_empty() {
+        added.sort();
+        removed.sort();
+        notes.push(RetargetNote { node: result.describe(), change: RetargetChange::Changed { added, removed } });
+    }
+
+    for (old, new) in guess_changes {
+        notes.push(RetargetNote { node: result.describe(), change: RetargetChange::Guess { old, new } });
+    }
+
+    if !split.is_empty() {
+        split.sort_by(|l, r| r.len().cmp(&l.len()).then(l.cmp(r)));
+        notes.push(RetargetNote { node: result.describe(), change: RetargetChange::Split { guess: node.next_guess.clone(), clusters: split } });
+    }
+
+    if let Some(subtree) = &node.subtree {
+        let mut children = Vec::new();
+
+        for (i, child) in subtree.iter().enumerate() {
+            if let Some(child_pairs) = by_child.remove(&i) {
+                path.push(children.len());
+                if let Some(child) = retarget_recurse(child, child_pairs, path, reached, notes) {
+                    children.push(child);
+                }
+                path.pop();
+            } else {
+                // No old or new answers reach this child, except the leaf for this guess being the answer, which is kept while it still is one
+                let solved_here = child.answers.as_ref().is_some_and(|a| a.len() == 1 && node.next_guess.specific() == Some(a[0]));
+                if solved_here && new.contains(&child.answers.as_ref().unwrap()[0]) {
+                    children.push(child.clone());
+                } else {
+                    notes.push(RetargetNote { node: child.describe(), change: RetargetChange::Removed });
+                }
+            }
+        }
+
+        if !children.is_empty() {
+            result.subtree = Some(children);
+        }
+    }
+
+    Some(result)
+}
+
+fn words_string(words: &[Word]) -> String {
+    format!("{{{}}}", words.iter().map(|w| w.to_string()).collect::<Vec<String>>().join(", "))
+}
+
+impl Display for RetargetNote {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.change {
+            RetargetChange::Removed => write!(f, "- {}  no answers reach it", self.node),
+            RetargetChange::Changed { added, removed } => {
+                let mut parts = Vec::new();
+                if !added.is_empty() { parts.push(format!("+{}", words_string(added))); }
+                if !removed.is_empty() { parts.push(format!("-{}", words_string(removed))); }
+                write!(f, "~ {}  {}", self.node, parts.join(" "))
+            },
+            RetargetChange::Guess { old, new } => write!(f, "~ {}  guesses '{new}' instead of '{old}'", self.node),
+            RetargetChange::Split { guess, clusters } => {
+                write!(f, "+ {}  new clusters after '{guess}': {}", self.node, clusters.iter().map(|c| words_string(c)).collect::<Vec<String>>().join(" "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{wv, smart_trim};
+    use super::*;
+
+    #[test]
+    fn retarget_answers() {
+        let old_answers = wv("bobby, boxer, dowdy, fatal, tally, waltz");
+        let new_answers = wv("daddy, dowdy, fatal, mummy, sassy, tally, waltz");
+
+        // PARSE: {bobby, dowdy} get nothing, {fatal, tally, waltz} get .A..., and BOXER gets ..r.e
+        let tree = WordleTree::parse(
+"13 (*, 6) -> parse
+    5 (bobby, 2) -> dowdy
+    7 (> .A..., 3) -> tally [2]
+    2 {boxer}".lines()).unwrap();
+
+        let retargeted = tree.retarget(&old_answers, &new_answers);
+        let notes = retargeted.notes.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        assert_eq!(notes, vec![
+            "+ (*, 7) -> parse  new clusters after 'parse': {sassy}",
+            "~ (dowdy, 2) -> dowdy  -{bobby}",
+            "+ (dowdy, 2) -> dowdy  new clusters after 'dowdy': {mummy}",
+            "+ (> .A..., 4) -> tally  new clusters after 'tally': {daddy}",
+            "- (boxer, 1) -> *  no answers reach it",
+        ]);
+
+        // Now {dowdy, mummy} go to the (renamed) DOWDY node, and the new answers are guessed randomly where they split off
+        assert_eq!(smart_trim(&retargeted.tree.to_string()),
+"18 (*, 7) -> parse
+    11 (> .A..., 4) -> tally [3]
+    5 (dowdy, 2) -> dowdy");
+    }
+
+    #[test]
+    fn retarget_unreached() {
+        let old_answers = wv("tally, fatal, tally");
+        let new_answers = wv("waltz, tally, fatal");
+
+        // No answers on either list get P.... from PARSE, so that node is dropped rather than kept with stale counts.
+        //  The lists aren't sorted (like answer files), but the Cluster node is still named for the first answer and gets its turns.
+        let tree = WordleTree::parse(
+"5 (*, 2) -> parse
+    3 (fatal, 2) -> tally
+    2 (> P...., 1) -> dumpy".lines()).unwrap();
+
+        let retargeted = tree.retarget(&old_answers, &new_answers);
+        let notes = retargeted.notes.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        assert_eq!(notes, vec![
+            "+ (fatal, 3) -> tally  new clusters after 'tally': {waltz}",
+            "- (> P...., 1) -> dumpy  no answers reach it",
+        ]);
+
+        assert_eq!(smart_trim(&retargeted.tree.to_string()),
+"8 (*, 3) -> parse
+    8 (fatal, 3) -> tally");
+    }
+
+    #[test]
+    fn retarget_guess_policies() {
+        let old_answers = wv("fatal, tally, waltz");
+        let new_answers = wv("daily, fatal, tally, waltz");
+
+        // '*first' guessed FATAL for the old answers, but guesses DAILY for the new ones, as TreePlayer would
+        let tree = WordleTree::parse(
+"6 (*, 3) -> *first
+    2 (tally, 1) -> tally
+    1 {fatal}".lines()).unwrap();
+
+        let retargeted = tree.retarget(&old_answers, &new_answers);
+        let notes = retargeted.notes.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        assert_eq!(notes, vec!["~ (*, 4) -> *first  guesses 'daily' instead of 'fatal'"]);
+
+        // DAILY splits off {fatal, waltz} (.A.l.), which now reaches the FATAL leaf, and {tally}
+        assert_eq!(smart_trim(&retargeted.tree.to_string()),
+"8 (*, 4) -> *first
+    5 {fatal, waltz}
+    2 (tally, 1) -> tally");
+    }
+}
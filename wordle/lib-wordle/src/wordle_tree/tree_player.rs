@@ -1,13 +1,18 @@
 use std::{collections::HashMap, mem};
-use crate::{wordle_tree::*, word::Word, response::Response};
+use crate::{wordle_tree::*, word::Word, response::Response, hard_mode::HardMode, check::SimulationReport};
 
 pub struct TreePlayer<'a> {
     tree: &'a WordleTree,
     current: Option<&'a WordleTree>,
+    last_guess: Option<Word>,
+
+    hard_mode: bool,
+    rules: HardMode,
+    known_letters: usize,
 
     game_count: usize,
     last_turn: usize,
-    turn_counts: HashMap<Vec<usize>, (usize, usize)>,
+    turn_counts: HashMap<Vec<usize>, SimulationReport>,
     path: Vec<usize>,
 }
 
@@ -16,6 +21,11 @@ impl TreePlayer<'_> {
         TreePlayer {
             tree,
             current: None,
+            last_guess: None,
+
+            hard_mode: false,
+            rules: HardMode::new(),
+            known_letters: 0,
 
             game_count: 0,
             last_turn: 0,
@@ -24,18 +34,32 @@ impl TreePlayer<'_> {
         }
     }
 
+    /// Play by hard mode rules; tree guesses which don't use all revealed hints are skipped for random guesses.
+    pub fn set_hard_mode(&mut self, hard_mode: bool) {
+        self.hard_mode = hard_mode;
+    }
+
+    /// Compute the exact expected turns for every answer with this strategy (and the hard mode setting), rather than simulating games.
+    pub fn evaluate(&self, answers: &[Word]) -> evaluate::Evaluation {
+        self.tree.evaluate(answers, self.hard_mode)
+    }
+
     /// Identify the next guess for this strategy in the current situation, or None if random guesses should be used.
     pub fn choose(&mut self, guesses: &Vec<Word>, turn: usize, answers_left: &Vec<Word>) -> Option<Word> {
         self.next_for_game(guesses, turn, answers_left);
 
-        if let Some(c) = self.current {
-            match c.next_guess {
-                WordleGuess::Specific(word) => Some(word),
-                WordleGuess::Random => None
+        // Guessing policies choose from the answers left
+        self.last_guess = self.current.and_then(|c| c.next_guess.choose(answers_left));
+
+        if let Some(word) = self.last_guess {
+            if self.hard_mode && !self.rules.allows(word) {
+                // Illegal in hard mode; guess randomly for the rest of the game
+                self.current = None;
+                self.last_guess = None;
             }
-        } else {
-            None
         }
+
+        self.last_guess
     }
 
     /// Before a turn, use the current situation (answers_left) to figure out which node in the tree applies to this game.
@@ -43,6 +67,8 @@ impl TreePlayer<'_> {
         // If a new game has started, mark down total turns until win in the previous one
         if turn <= 1 {
             self.current = Some(&self.tree);
+            self.rules = HardMode::new();
+            self.known_letters = 0;
 
             if self.last_turn > 0 {
                 self.score();
@@ -54,42 +80,20 @@ impl TreePlayer<'_> {
             return;
         }
 
-        let mut last_guess = None;
-        if let Some(tree) = self.current {
-            if let WordleGuess::Specific(word) = tree.next_guess {
-                last_guess = Some(word);
-            }
-        }
-        
+        let last_guess = self.last_guess;
         let mut last_response = None; 
         if let Some(guess) = last_guess {
             if let Some(first_answer) = answers_left.get(0) { 
-                last_response = Some(Response::score(guess, *first_answer));
+                let response = Response::score(guess, *first_answer);
+                self.rules.add(guess, response);
+                self.known_letters += response.known_count() as usize;
+                last_response = Some(response);
             }
         }
 
         if let Some(c) = self.current {
             // Look for the most specific matching child (Cluster > Length > Any)
-            let mut best: Option<(usize, &WordleTree)> = None;
-
-            if let Some(subtree) = &c.subtree {
-                for (i, child) in subtree.iter().enumerate() {
-                    if child.identifier.matches(last_guess, last_response, answers_left) {
-                        //let _child_id = child.identifier.to_string();
-                        let can_stop = child.identifier.is_cluster();
-
-                        if let Some(b) = best {
-                            if child.identifier.is_more_specific(&b.1.identifier) {
-                                best = Some((i, child));
-                            }
-                        } else {
-                            best = Some((i, child));
-                        }
-
-                        if can_stop { break; }
-                    }
-                }
-            }
+            let best = c.matching_child(last_guess, last_response, self.known_letters, answers_left);
 
             if let Some(best) = best {
                 self.path.push(best.0);
@@ -109,19 +113,35 @@ impl TreePlayer<'_> {
         self.turn_counts.clear();
     }
 
+    /// Add the statistics collected by another TreePlayer (for the same tree), to combine games simulated separately
+    pub fn add_play_stats(&mut self, other: &mut TreePlayer) {
+        self.score();
+        other.score();
+
+        self.game_count += other.game_count;
+        for (path, games) in other.turn_counts.iter() {
+            self.turn_counts.entry(path.clone()).or_default().add_report(games);
+        }
+    }
+
     /// Find the cluster containing 'word' after all specific guesses (before random guessing)
     pub fn cluster(&mut self, word: Word, answers: &Vec<Word>, at_turn: usize) -> Vec<Word> {
-        let mut turn = 1;
+        self.cluster_and_guesses(word, answers, at_turn).0
+    }
+
+    /// Return the answers left when playing for 'word' until 'at_turn', and the guesses made to get there.
+    pub fn cluster_and_guesses(&mut self, word: Word, answers: &Vec<Word>, at_turn: usize) -> (Vec<Word>, Vec<Word>) {
+        let mut guesses = Vec::new();
         let mut answers_left = answers.clone();
 
-        while let Some(guess) = self.choose(&Vec::new(), turn, &answers_left) {
+        while let Some(guess) = self.choose(&guesses, guesses.len() + 1, &answers_left) {
             let response = Response::score(guess, word);
             answers_left.retain(|a| Response::score(guess, *a) == response);
-            turn += 1;
-            if turn >= at_turn { break; }
+            guesses.push(guess);
+            if guesses.len() + 1 >= at_turn { break; }
         }
 
-        answers_left
+        (answers_left, guesses)
     }
 
     /// After each game, track total turns and games played for the last node reached.
@@ -132,9 +152,7 @@ impl TreePlayer<'_> {
             mem::swap(&mut path,&mut self.path);
 
             // Add turns for this game
-            let counts = self.turn_counts.entry(path).or_default();
-            counts.0 += self.last_turn;
-            counts.1 += 1;
+            self.turn_counts.entry(path).or_default().add_turns(self.last_turn);
             
             // Ensure calls from to_string don't "flush" the last game repeatedly
             self.last_turn = 0;
@@ -143,29 +161,29 @@ impl TreePlayer<'_> {
 
     /// Recursively add up total turns under a given WordleTree node
     pub fn total_turns(&mut self, node: &WordleTree, path: &mut Vec<usize>) -> (usize, usize) {
+        let report = self.node_report(node, path);
+        (report.total_turns(), report.game_count)
+    }
+
+    /// Recursively add up the games (turn histogram and totals) played under a given WordleTree node
+    pub fn node_report(&mut self, node: &WordleTree, path: &mut Vec<usize>) -> SimulationReport {
         // Ensure the last game is scored
         self.score();
 
-        // Compute rolled up total turns
-        let mut outer_total_turns = (0, 0);
+        let mut report = SimulationReport::new();
         if let Some(subtree) = &node.subtree {
             for (i, child) in subtree.iter().enumerate() {
                 path.push(i);
-
-                let inner_turns = self.total_turns(child, path);
-                outer_total_turns.0 += inner_turns.0;
-                outer_total_turns.1 += inner_turns.1;
-
+                report.add_report(&self.node_report(child, path));
                 path.pop();
             }
         }
 
-        if let Some(turns) = self.turn_counts.get(path) {
-            outer_total_turns.0 += turns.0;
-            outer_total_turns.1 += turns.1;
+        if let Some(games) = self.turn_counts.get(path) {
+            report.add_report(games);
         }
 
-        outer_total_turns
+        report
     }
 
     /// Write out the WordleTree with the turn counts observed with each node.
@@ -178,7 +196,8 @@ impl TreePlayer<'_> {
 
     fn add_with_scores(&mut self, node: &WordleTree, path: &mut Vec<usize>, answer_count: usize, options: &WordleTreeToStringOptions, result: &mut String) {
         // Compute total turns through this node
-        let outer_total_turns = self.total_turns(node, path);
+        let report = self.node_report(node, path);
+        let outer_total_turns = (report.total_turns(), report.game_count);
 
         // Hide this subtree if unvisited
         if outer_total_turns.0 == 0 && options.show_zero_turn_paths == false {
@@ -208,6 +227,14 @@ impl TreePlayer<'_> {
 
         node.add_self_to_string(options, 0, result);
 
+        if options.show_turn_histogram {
+            // Before the line break
+            result.pop();
+            result.push_str("  ");
+            result.push_str(&report.histogram_string());
+            result.push('\n');
+        }
+
         // Write subtree
         if let Some(subtree) = &node.subtree {
             for (i, child) in subtree.iter().enumerate() {
@@ -294,6 +321,11 @@ mod tests {
         4 (= 3, 5) -> first";
         assert_eq!(smart_trim(&scored_text), smart_trim(expected));
 
+        // Show the turns each game through each node took
+        options.show_turn_histogram = true;
+        let scored_text = player.to_string(answers_left.len(), &options);
+        assert!(scored_text.lines().all(|line| line.ends_with("[0, 0, 0, 1, 0, 0; 0 lost]")));
+
         // Ask for clusters for TALLY - should be alone, because it goes down to a specific guess for itself
         let answers_left = vec![w("fatal"), w("tally"), w("waltz")];
         assert_eq!(player.cluster(w("tally"), &answers_left, 4), vec![w("tally")]);
@@ -302,4 +334,29 @@ mod tests {
         let answers_left = vec![w("odder"), w("order"), w("ruder"), w("udder")];
         assert_eq!(player.cluster(w("odder"), &answers_left, 4), answers_left);
     }
+
+    #[test]
+    fn player_hard_mode() {
+        let tree = WordleTree::parse(SAMPLE_TREE.lines()).unwrap();
+        let mut player = TreePlayer::new(&tree);
+        player.set_hard_mode(true);
+
+        // parse -> .a... for "fatal"; CLINT doesn't include the A, so it is skipped for random guesses
+        let guesses = Vec::new();
+        let answers_left = vec![w("fatal"), w("tally"), w("waltz")];
+        assert_eq!(player.choose(&guesses, 1, &answers_left), Some(w("parse")));
+        assert_eq!(player.choose(&guesses, 2, &answers_left), None);
+        assert_eq!(player.choose(&guesses, 3, &answers_left), None);
+
+        // Rules reset for each game; CLINT is legal when PARSE reveals nothing
+        let answers_left = vec![w("dowdy")];
+        assert_eq!(player.choose(&guesses, 1, &answers_left), Some(w("parse")));
+        assert_eq!(player.choose(&guesses, 2, &answers_left), Some(w("clint")));
+
+        // Without hard mode, CLINT is played
+        player.set_hard_mode(false);
+        let answers_left = vec![w("fatal"), w("tally"), w("waltz")];
+        assert_eq!(player.choose(&guesses, 1, &answers_left), Some(w("parse")));
+        assert_eq!(player.choose(&guesses, 2, &answers_left), Some(w("clint")));
+    }
 }
\ No newline at end of file